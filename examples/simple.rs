@@ -50,8 +50,25 @@ fn get_model() -> Model {
         NAVY, OLIVE, LAVENDER, BROWN, BEIGE, CORAL, GREY, MAGENTA, TURQUOISE,
     ];
 
+    // Log the constraints and conflicts of each node of the search tree
+    let verbose = false;
+
     let start = Instant::now();
-    let solution = cbs.solve(&config);
+    let mut solution = None;
+    while let Some(node) = cbs.solve_iter(&config) {
+        if verbose || node.conflicts.is_empty() {
+            println!("{}", node.get_debug_snapshot(config.n_agents));
+        }
+        if node.conflicts.is_empty() {
+            solution = Some(
+                node.get_solutions(config.n_agents)
+                    .into_iter()
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            );
+            break;
+        }
+    }
     let duration = start.elapsed();
 
     if let Some(solution) = &solution {
@@ -1,5 +1,9 @@
+mod reservation;
 mod search;
+mod timeline;
 mod transition;
 
+pub use reservation::*;
 pub use search::*;
+pub use timeline::*;
 pub use transition::*;
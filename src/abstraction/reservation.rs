@@ -0,0 +1,192 @@
+use std::{fmt::Debug, hash::Hash, sync::Arc};
+
+use fxhash::FxHashMap;
+
+use crate::{Interval, LimitValues, SippState, Solution};
+
+/// Reservation of a state or an action by a given agent during a given interval.
+#[derive(Debug, Clone, Copy)]
+pub struct Reservation<C>
+where
+    C: PartialEq + Eq + PartialOrd + Ord + LimitValues,
+{
+    pub agent: usize,
+    pub interval: Interval<C>,
+}
+
+/// Space-time reservation table that maps the states and actions (pairs of states)
+/// to the agents that occupy them over time.
+#[derive(Debug)]
+pub struct ReservationTable<S, C>
+where
+    S: Eq + Hash + Clone,
+    C: PartialEq + Eq + PartialOrd + Ord + LimitValues + Copy,
+{
+    states: FxHashMap<S, Vec<Reservation<C>>>,
+    actions: FxHashMap<(S, S), Vec<Reservation<C>>>,
+}
+
+impl<S, C> Default for ReservationTable<S, C>
+where
+    S: Eq + Hash + Clone,
+    C: PartialEq + Eq + PartialOrd + Ord + LimitValues + Copy,
+{
+    fn default() -> Self {
+        Self {
+            states: Default::default(),
+            actions: Default::default(),
+        }
+    }
+}
+
+impl<S, C> ReservationTable<S, C>
+where
+    S: Debug + Eq + Hash + Clone,
+    C: PartialEq + Eq + PartialOrd + Ord + LimitValues + Copy + Default,
+{
+    /// Reserves the states and actions visited by the given agent along its path.
+    /// The agent occupies its last state until the end of the horizon.
+    pub fn reserve<A, DC>(
+        &mut self,
+        agent: usize,
+        solution: &Solution<Arc<SippState<S, C>>, A, C, DC>,
+    ) {
+        for (i, (state, time)) in solution.steps.iter().enumerate() {
+            let state = &state.internal_state;
+            let end = solution
+                .steps
+                .get(i + 1)
+                .map_or(C::max_value(), |(_, time)| *time);
+
+            if solution.actions.get(i).is_some_and(|a| a.action.is_some()) {
+                // The agent leaves the state and occupies the action until it reaches the next state
+                self.reserve_state(agent, state, Interval::new(*time, *time));
+                let next = &solution.steps[i + 1].0.internal_state;
+                self.actions
+                    .entry((state.clone(), next.clone()))
+                    .or_default()
+                    .push(Reservation {
+                        agent,
+                        interval: Interval::new(*time, end),
+                    });
+            } else {
+                self.reserve_state(agent, state, Interval::new(*time, end));
+            }
+        }
+    }
+
+    fn reserve_state(&mut self, agent: usize, state: &S, interval: Interval<C>) {
+        self.states
+            .entry(state.clone())
+            .or_default()
+            .push(Reservation { agent, interval });
+    }
+
+    /// Returns true if no agent occupies the given state during the given interval.
+    pub fn is_free(&self, state: &S, interval: &Interval<C>) -> bool {
+        self.get_state_reservations(state)
+            .iter()
+            .all(|r| !r.interval.overlaps(interval))
+    }
+
+    /// Returns true if no agent performs the action connecting the two given states
+    /// during the given interval.
+    pub fn is_edge_free(&self, from: &S, to: &S, interval: &Interval<C>) -> bool {
+        self.get_action_reservations(from, to)
+            .iter()
+            .all(|r| !r.interval.overlaps(interval))
+    }
+
+    /// Returns the reservations of the given state.
+    pub fn get_state_reservations(&self, state: &S) -> &[Reservation<C>] {
+        self.states.get(state).map_or(&[], |r| r.as_slice())
+    }
+
+    /// Returns the reservations of the action connecting the two given states.
+    pub fn get_action_reservations(&self, from: &S, to: &S) -> &[Reservation<C>] {
+        self.actions
+            .get(&(from.clone(), to.clone()))
+            .map_or(&[], |r| r.as_slice())
+    }
+
+    /// Returns the earliest time, not before the given time, at which an agent other than
+    /// the given one starts occupying the given state.
+    pub fn next_state_reservation(&self, state: &S, agent: usize, time: C) -> Option<C> {
+        Self::next_reservation(self.get_state_reservations(state), agent, time)
+    }
+
+    /// Returns the earliest time, not before the given time, at which an agent other than
+    /// the given one starts performing the action connecting the two given states.
+    pub fn next_action_reservation(&self, from: &S, to: &S, agent: usize, time: C) -> Option<C> {
+        Self::next_reservation(self.get_action_reservations(from, to), agent, time)
+    }
+
+    fn next_reservation(reservations: &[Reservation<C>], agent: usize, time: C) -> Option<C> {
+        reservations
+            .iter()
+            .filter(|r| r.agent != agent && r.interval.start >= time)
+            .map(|r| r.interval.start)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, GraphNodeId, Interval, ReservationTable, ReverseResumableAStar,
+        SafeIntervalPathPlanning, SimpleHeuristic, SimpleState, SimpleWorld, SippConfig, Task,
+    };
+
+    #[test]
+    fn test_reserve_path() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(3)),
+            OrderedFloat(0.0),
+        ));
+        let config = SippConfig::new(
+            task.clone(),
+            Default::default(),
+            Default::default(),
+            Arc::new(ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            )),
+            1e-6.into(),
+        );
+        let solution = solver.solve(&config).unwrap();
+
+        let mut reservations = ReservationTable::default();
+        reservations.reserve(0, &solution);
+
+        let state = |i| SimpleState(GraphNodeId(i));
+        let interval = |start, end| Interval::new(OrderedFloat(start), OrderedFloat(end));
+
+        // The agent is in transit between the first two states
+        assert!(reservations.is_free(&state(1), &interval(0.2, 0.8)));
+        assert!(!reservations.is_edge_free(&state(0), &state(1), &interval(0.2, 0.8)));
+        assert!(reservations.is_edge_free(&state(1), &state(0), &interval(0.2, 0.8)));
+
+        // The agent passes through the second state
+        assert!(!reservations.is_free(&state(1), &interval(0.9, 1.1)));
+        assert!(reservations.is_free(&state(1), &interval(1.5, 2.0)));
+
+        // The agent stays at its goal state
+        assert!(reservations.is_free(&state(3), &interval(0.0, 2.5)));
+        assert!(!reservations.is_free(&state(3), &interval(10.0, 20.0)));
+
+        // Other states are never occupied
+        assert!(reservations.is_free(&state(size), &Interval::default()));
+        assert_eq!(reservations.get_state_reservations(&state(3))[0].agent, 0);
+    }
+}
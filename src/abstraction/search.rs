@@ -1,18 +1,23 @@
 use std::{
-    cmp::Ordering,
-    hash::Hash,
+    borrow::Borrow,
+    cmp::{Ordering, Reverse},
+    fmt::Debug,
+    hash::{Hash, Hasher},
     marker::PhantomData,
     ops::{Add, Sub},
     sync::Arc,
 };
 
-use fxhash::FxHashMap;
-use tuple::A2;
+use fxhash::{FxHashMap, FxHasher};
+use tuple::{A2, T2};
 
-use crate::{Move, State, Task, TransitionSystem};
+use crate::{
+    Move, ReservationTable, SippState, State, Task, Trajectory, TransitionSystem, Waypoint,
+};
 
 /// Wrapper around an action that also contains the cost of the action.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Action<A, DC> {
     pub action: Option<A>,
     pub cost: DC,
@@ -33,6 +38,7 @@ impl<A, DC> Action<A, DC> {
 
 /// Description of a solution to a search problem
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Solution<S, A, C, DC>
 where
     C: Default,
@@ -55,6 +61,493 @@ where
     }
 }
 
+impl<S, A, C, DC> Solution<S, A, C, DC>
+where
+    C: Default + Copy + Sub<C, Output = DC>,
+{
+    /// Returns the total energy consumed along the solution with the given energy model.
+    /// The duration of each action is deduced from the times of the surrounding steps.
+    pub fn energy<E, M>(&self, model: &M) -> E
+    where
+        E: Default + Add<E, Output = E>,
+        M: EnergyModel<A, DC, E>,
+    {
+        self.actions
+            .iter()
+            .enumerate()
+            .fold(E::default(), |energy, (i, action)| {
+                let duration = self.steps[i + 1].1 - self.steps[i].1;
+                energy
+                    + match &action.action {
+                        Some(action) => model.action_energy(action, duration),
+                        None => model.wait_energy(duration),
+                    }
+            })
+    }
+}
+
+impl<S, A, C, DC> Solution<Arc<SippState<S, C>>, A, C, DC>
+where
+    S: Debug + Eq + Clone,
+    A: Copy,
+    C: Ord + Default + Copy + LimitValues + Add<DC, Output = C>,
+    DC: Copy,
+{
+    /// Samples the solution every given time step, from its first step to its last step,
+    /// and returns the move that the given agent performs at each sampled time.
+    pub fn sample(&self, agent: usize, dt: DC) -> Vec<(C, Move<S, A, C>)> {
+        let mut samples = vec![];
+        let (first, last) = match (self.steps.first(), self.steps.last()) {
+            (Some(first), Some(last)) => (first.1, last.1),
+            _ => return samples,
+        };
+
+        let mut i = 0;
+        let mut time = first;
+        while time <= last {
+            // Find the step during which the sampled time falls
+            while i + 1 < self.steps.len() && self.steps[i + 1].1 <= time {
+                i += 1;
+            }
+
+            let m = match self.steps.get(i + 1) {
+                Some((next, end)) => Move::new(
+                    agent,
+                    self.steps[i].0.internal_state.clone(),
+                    next.internal_state.clone(),
+                    self.actions[i].action,
+                    Interval::new(self.steps[i].1, *end),
+                ),
+                None => Move::new(
+                    agent,
+                    self.steps[i].0.internal_state.clone(),
+                    self.steps[i].0.internal_state.clone(),
+                    None,
+                    Interval::new(self.steps[i].1, C::max_value()),
+                ),
+            };
+            samples.push((time, m));
+
+            time = time + dt;
+        }
+
+        samples
+    }
+}
+
+impl<S, A, C, DC> Solution<Arc<SippState<S, C>>, A, C, DC>
+where
+    S: Debug + Eq + Clone,
+    A: Copy,
+    C: Ord + Default + Copy + LimitValues + Sub<C, Output = DC>,
+    DC: Copy,
+{
+    /// Returns the solution without its oscillations, i.e. the moves to a neighbor state that
+    /// are followed by the move back after waiting there, if any. Each oscillation is replaced
+    /// by a wait at the state it leaves, provided that the agent may stay there meanwhile:
+    /// both visits belong to the same safe interval, and the wait does not collide with any
+    /// move of the other solutions in the given transition system.
+    /// The times of the remaining steps, hence the arrival time and the cost, are preserved.
+    pub fn smooth<TS>(&self, transition_system: &TS, others: &[&Self]) -> Self
+    where
+        TS: TransitionSystem<S, A, C, DC>,
+    {
+        let other_moves = others
+            .iter()
+            .enumerate()
+            .map(|(agent, other)| other.to_moves(agent))
+            .collect::<Vec<_>>();
+
+        let mut smoothed = self.clone();
+        while let Some((start, end)) =
+            smoothed.find_removable_oscillation(transition_system, &other_moves, others.len())
+        {
+            // Replace the oscillation by a wait, merged with the preceding wait if any
+            let start = if start > 0 && smoothed.actions[start - 1].action.is_none() {
+                start - 1
+            } else {
+                start
+            };
+            let duration = smoothed.steps[end].1 - smoothed.steps[start].1;
+            smoothed.steps.drain(start + 1..end);
+            smoothed
+                .actions
+                .splice(start..end, std::iter::once(Action::wait(duration)));
+
+            // Merge with the following wait if any
+            if smoothed
+                .actions
+                .get(start + 1)
+                .is_some_and(|a| a.action.is_none())
+            {
+                let duration = smoothed.steps[start + 2].1 - smoothed.steps[start].1;
+                smoothed.steps.remove(start + 1);
+                smoothed.actions.remove(start + 1);
+                smoothed.actions[start] = Action::wait(duration);
+            }
+        }
+
+        smoothed
+    }
+
+    /// Returns the indices of the steps at which the first oscillation that can be replaced
+    /// by a wait starts and ends, if any.
+    fn find_removable_oscillation<TS>(
+        &self,
+        transition_system: &TS,
+        other_moves: &[Vec<Move<S, A, C>>],
+        agent: usize,
+    ) -> Option<(usize, usize)>
+    where
+        TS: TransitionSystem<S, A, C, DC>,
+    {
+        for start in 0..self.actions.len() {
+            if self.actions[start].action.is_none() {
+                continue;
+            }
+
+            // Skip the waits at the neighbor state, then the move back must return to the same state
+            let mut end = start + 1;
+            while end < self.actions.len() && self.actions[end].action.is_none() {
+                end += 1;
+            }
+            if end == self.actions.len() {
+                break;
+            }
+            end += 1;
+
+            let (from, to) = (&self.steps[start].0, &self.steps[end].0);
+            if from.internal_state != to.internal_state || from.safe_interval != to.safe_interval {
+                continue;
+            }
+
+            let wait = Move::new(
+                agent,
+                from.internal_state.clone(),
+                from.internal_state.clone(),
+                None,
+                Interval::new(self.steps[start].1, self.steps[end].1),
+            );
+            let collides = other_moves.iter().flatten().any(|other| {
+                other.interval.overlaps(&wait.interval)
+                    && transition_system.conflict(T2(&wait, other))
+            });
+            if !collides {
+                return Some((start, end));
+            }
+        }
+        None
+    }
+
+    /// Returns the moves that the given agent performs along the solution,
+    /// the last one being the wait at its final state forever.
+    pub fn to_moves(&self, agent: usize) -> Vec<Move<S, A, C>> {
+        (0..self.steps.len())
+            .map(|i| {
+                let (state, start) = &self.steps[i];
+                match self.steps.get(i + 1) {
+                    Some((next, end)) => Move::new(
+                        agent,
+                        state.internal_state.clone(),
+                        next.internal_state.clone(),
+                        self.actions[i].action,
+                        Interval::new(*start, *end),
+                    ),
+                    None => Move::new(
+                        agent,
+                        state.internal_state.clone(),
+                        state.internal_state.clone(),
+                        None,
+                        Interval::new(*start, C::max_value()),
+                    ),
+                }
+            })
+            .collect()
+    }
+}
+
+impl<S, A, C, DC> Solution<Arc<SippState<S, C>>, A, C, DC>
+where
+    S: Debug + Eq,
+    A: PartialEq,
+    C: Ord + Default + Copy + LimitValues + Sub<C, Output = DC>,
+    DC: Ord + Copy + Sub<DC, Output = DC>,
+{
+    /// Returns true if both solutions visit the same sequence of states with the same actions,
+    /// at times and costs that differ by at most the given precision.
+    /// The safe intervals and the identity of the shared states are ignored.
+    pub fn structurally_eq(&self, other: &Self, precision: DC) -> bool {
+        let close = |a: C, b: C| {
+            if a >= b {
+                a - b <= precision
+            } else {
+                b - a <= precision
+            }
+        };
+        let close_durations = |a: DC, b: DC| {
+            if a >= b {
+                a - b <= precision
+            } else {
+                b - a <= precision
+            }
+        };
+
+        close(self.cost, other.cost)
+            && self.steps.len() == other.steps.len()
+            && self.actions.len() == other.actions.len()
+            && self
+                .steps
+                .iter()
+                .zip(other.steps.iter())
+                .all(|(a, b)| a.0.internal_state == b.0.internal_state && close(a.1, b.1))
+            && self
+                .actions
+                .iter()
+                .zip(other.actions.iter())
+                .all(|(a, b)| a.action == b.action && close_durations(a.cost, b.cost))
+    }
+}
+
+/// Analysis of the solutions of several agents that start at the same time.
+pub trait SolutionSet<S, C, DC>
+where
+    C: PartialEq + Eq + PartialOrd + Ord + LimitValues,
+{
+    /// Returns, for each time step, the states occupied by the agents and the agents occupying them.
+    /// An agent in transit occupies both states of its move, so that the agents that follow each other
+    /// closely may share a state, which also reveals the overlaps of conflicting solutions.
+    fn occupancy_timeline(&self, dt: DC) -> Vec<FxHashMap<S, Vec<usize>>>;
+
+    /// Returns, for each agent and each step of its path, the maximum delay of the step
+    /// before the agent occupies a state or an action reserved by another agent.
+    /// The last step has no slack since the agent holds its final state until the end of the horizon.
+    fn step_slack(&self) -> Vec<Vec<DC>>;
+
+    /// Returns the agent that reaches its final state last, along with the indices of its steps
+    /// that have no slack, which end with its final arrival. Among the agents that finish last,
+    /// the one with the most steps without slack is returned, or the first one in case of ties.
+    fn critical_path(&self) -> Option<(usize, Vec<usize>)>;
+
+    /// Returns the indices of the given constraints that are binding, i.e. whose removal could
+    /// change the solutions: the constrained agent arrives at the constrained state when the
+    /// constraint ends, leaves it when the constraint starts, or performs the constrained action
+    /// when the constraint ends, up to the given precision.
+    fn binding_constraints(&self, constraints: &[Constraint<S, C>], precision: DC) -> Vec<usize>;
+
+    /// Returns the trajectory of each agent, whose waypoints are the positions of its states,
+    /// e.g. the positions of the nodes of a graph. The velocities are given by the length
+    /// of each move over its duration.
+    fn to_trajectories<P>(&self, position: P) -> Vec<Trajectory>
+    where
+        P: Fn(&S) -> (f32, f32),
+        C: Into<f32>,
+        DC: Into<f32>;
+}
+
+impl<S, A, C, DC> SolutionSet<S, C, DC> for [Solution<Arc<SippState<S, C>>, A, C, DC>]
+where
+    S: Debug + Eq + Hash + Clone,
+    A: Copy,
+    C: Ord + Default + Copy + LimitValues + Add<DC, Output = C> + Sub<C, Output = DC>,
+    DC: Ord + Copy + Default,
+{
+    fn occupancy_timeline(&self, dt: DC) -> Vec<FxHashMap<S, Vec<usize>>> {
+        let samples = self
+            .iter()
+            .enumerate()
+            .map(|(agent, solution)| solution.sample(agent, dt))
+            .collect::<Vec<_>>();
+        let n_steps = samples.iter().map(|s| s.len()).max().unwrap_or(0);
+
+        (0..n_steps)
+            .map(|k| {
+                let mut occupancy = FxHashMap::<S, Vec<usize>>::default();
+                for (agent, agent_samples) in samples.iter().enumerate() {
+                    // Agents that have finished stay at their last state
+                    let (time, m) = match agent_samples.get(k).or(agent_samples.last()) {
+                        Some(sample) => sample,
+                        None => continue,
+                    };
+                    occupancy.entry(m.from.clone()).or_default().push(agent);
+                    if m.from != m.to && *time > m.interval.start {
+                        occupancy.entry(m.to.clone()).or_default().push(agent);
+                    }
+                }
+                occupancy
+            })
+            .collect()
+    }
+
+    fn step_slack(&self) -> Vec<Vec<DC>> {
+        let mut reservations = ReservationTable::default();
+        for (agent, solution) in self.iter().enumerate() {
+            reservations.reserve(agent, solution);
+        }
+
+        self.iter()
+            .enumerate()
+            .map(|(agent, solution)| {
+                (0..solution.steps.len())
+                    .map(|i| {
+                        let (state, time) = &solution.steps[i];
+                        let (next, end) = match solution.steps.get(i + 1) {
+                            Some(step) => step,
+                            None => return DC::default(),
+                        };
+                        let (state, next) = (&state.internal_state, &next.internal_state);
+                        let moving = solution.actions[i].action.is_some();
+
+                        // The delayed step keeps occupying the current state, and then
+                        // performs the action and reaches the next state later
+                        let limits = [
+                            reservations
+                                .next_state_reservation(state, agent, *time)
+                                .map(|start| start - if moving { *time } else { *end }),
+                            reservations
+                                .next_action_reservation(state, next, agent, *time)
+                                .filter(|_| moving)
+                                .map(|start| start - *end),
+                            reservations
+                                .next_action_reservation(next, state, agent, *time)
+                                .filter(|_| moving)
+                                .map(|start| start - *end),
+                            reservations
+                                .next_state_reservation(next, agent, *end)
+                                .map(|start| start - *end),
+                        ];
+
+                        limits
+                            .into_iter()
+                            .flatten()
+                            .min()
+                            .unwrap_or(C::max_value() - *end)
+                            .max(DC::default())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn critical_path(&self) -> Option<(usize, Vec<usize>)> {
+        let makespan = self
+            .iter()
+            .filter_map(|sol| sol.steps.last())
+            .map(|s| s.1)
+            .max()?;
+
+        self.step_slack()
+            .iter()
+            .enumerate()
+            .filter(|(agent, _)| self[*agent].steps.last().is_some_and(|s| s.1 == makespan))
+            .map(|(agent, slack)| {
+                let steps = slack
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, slack)| **slack <= DC::default())
+                    .map(|(i, _)| i)
+                    .collect::<Vec<_>>();
+                (agent, steps)
+            })
+            .min_by_key(|(agent, steps)| (Reverse(steps.len()), *agent))
+    }
+
+    fn binding_constraints(&self, constraints: &[Constraint<S, C>], precision: DC) -> Vec<usize> {
+        let close = |a: C, b: C| {
+            if a >= b {
+                a - b <= precision
+            } else {
+                b - a <= precision
+            }
+        };
+
+        constraints
+            .iter()
+            .enumerate()
+            .filter(|(_, constraint)| {
+                let solution = match self.get(constraint.agent) {
+                    Some(solution) => solution,
+                    None => return false,
+                };
+                // The steps before moves are at their departure times
+                let moving = |i: usize| {
+                    solution
+                        .actions
+                        .get(i)
+                        .is_some_and(|action| action.action.is_some())
+                };
+
+                (0..solution.steps.len()).any(|i| {
+                    let (state, time) = &solution.steps[i];
+                    if state.internal_state != constraint.state {
+                        return false;
+                    }
+                    match constraint.type_ {
+                        ConstraintType::State => {
+                            (i > 0 && moving(i - 1) && close(*time, constraint.interval.end))
+                                || (moving(i) && close(*time, constraint.interval.start))
+                        }
+                        ConstraintType::Action => {
+                            moving(i)
+                                && constraint.next.as_ref()
+                                    == Some(&solution.steps[i + 1].0.internal_state)
+                                && close(*time, constraint.interval.end)
+                        }
+                    }
+                })
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn to_trajectories<P>(&self, position: P) -> Vec<Trajectory>
+    where
+        P: Fn(&S) -> (f32, f32),
+        C: Into<f32>,
+        DC: Into<f32>,
+    {
+        self.iter()
+            .enumerate()
+            .map(|(agent, solution)| {
+                let waypoints = solution
+                    .steps
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (state, time))| {
+                        let (x, y) = position(&state.internal_state);
+                        let velocity = solution.actions.get(i).map(|action| {
+                            let (next_x, next_y) =
+                                position(&solution.steps[i + 1].0.internal_state);
+                            let distance = ((next_x - x).powi(2) + (next_y - y).powi(2)).sqrt();
+                            let duration: f32 = action.cost.into();
+                            if duration > 0.0 {
+                                distance / duration
+                            } else {
+                                0.0
+                            }
+                        });
+                        Waypoint {
+                            time: (*time).into(),
+                            x,
+                            y,
+                            velocity,
+                        }
+                    })
+                    .collect();
+
+                Trajectory { agent, waypoints }
+            })
+            .collect()
+    }
+}
+
+/// Describes the energy consumed by the agents when performing actions.
+pub trait EnergyModel<A, DC, E> {
+    /// Returns the energy consumed by performing the given action during the given duration.
+    fn action_energy(&self, action: &A, duration: DC) -> E;
+    /// Returns the energy consumed by waiting during the given duration.
+    fn wait_energy(&self, duration: DC) -> E;
+}
+
 /// Defines a heuristic function that can be used by a search algorithm,
 /// for a given transition system and task.
 pub trait Heuristic<TS, S, A, C, DC>
@@ -66,6 +559,12 @@ where
     /// Returns the heuristic value for the given state,
     /// or None if the goal state is not reachable from that state.
     fn get_heuristic(&self, state: &S) -> Option<DC>;
+
+    /// Returns a cheap lower bound of the heuristic value for the given state, which allows
+    /// rejecting the state before computing its heuristic value, or None if no bound is known.
+    fn lower_bound(&self, _state: &S) -> Option<DC> {
+        None
+    }
 }
 
 /// Trait to build simple heuristics on the fly.
@@ -141,6 +640,87 @@ where
             Some(heuristic)
         }
     }
+
+    /// The difference with the first pivot only bounds the maximum difference over all pivots.
+    fn lower_bound(&self, state: &S) -> Option<DC> {
+        if self.task_heuristic.is_some() {
+            return None;
+        }
+        let heuristic_to_pivot = self.heuristic_to_pivots.first()?;
+        match (
+            heuristic_to_pivot.get_heuristic(state),
+            heuristic_to_pivot.get_heuristic(&self.task.goal_state),
+        ) {
+            (Some(h1), Some(h2)) => Some((h2 - h1).max(h1 - h2)),
+            _ => None,
+        }
+    }
+}
+
+/// Heuristic that falls back to a secondary heuristic for the states on which
+/// the primary one cannot answer, e.g. because its precomputed table is incomplete.
+/// The result is admissible if both heuristics are admissible.
+pub struct FallbackHeuristic<TS, S, A, C, DC, H1, H2>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: Hash + Eq + Clone,
+    C: Eq + PartialOrd + Ord + Add<DC, Output = C> + Copy + Default + LimitValues,
+    H1: Heuristic<TS, S, A, C, DC>,
+    H2: Heuristic<TS, S, A, C, DC>,
+{
+    primary: H1,
+    secondary: H2,
+    _phantom: PhantomData<(TS, S, A, C, DC)>,
+}
+
+impl<TS, S, A, C, DC, H1, H2> FallbackHeuristic<TS, S, A, C, DC, H1, H2>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: Hash + Eq + Clone,
+    C: Eq + PartialOrd + Ord + Add<DC, Output = C> + Copy + Default + LimitValues,
+    H1: Heuristic<TS, S, A, C, DC>,
+    H2: Heuristic<TS, S, A, C, DC>,
+{
+    pub fn new(primary: H1, secondary: H2) -> Self {
+        FallbackHeuristic {
+            primary,
+            secondary,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<TS, S, A, C, DC, H1, H2> Heuristic<TS, S, A, C, DC>
+    for FallbackHeuristic<TS, S, A, C, DC, H1, H2>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: Hash + Eq + Clone,
+    C: Eq + PartialOrd + Ord + Add<DC, Output = C> + Copy + Default + LimitValues,
+    H1: Heuristic<TS, S, A, C, DC>,
+    H2: Heuristic<TS, S, A, C, DC>,
+{
+    fn get_heuristic(&self, state: &S) -> Option<DC> {
+        self.primary
+            .get_heuristic(state)
+            .or_else(|| self.secondary.get_heuristic(state))
+    }
+}
+
+impl<TS, S, A, C, DC, H1, H2> MinimalHeuristic<TS, S, A, C, DC>
+    for FallbackHeuristic<TS, S, A, C, DC, H1, H2>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: State + Hash + Eq + Clone,
+    C: Eq + PartialOrd + Ord + Add<DC, Output = C> + Copy + Default + LimitValues,
+    H1: Heuristic<TS, S, A, C, DC> + MinimalHeuristic<TS, S, A, C, DC>,
+    H2: Heuristic<TS, S, A, C, DC> + MinimalHeuristic<TS, S, A, C, DC>,
+{
+    fn build(transition_system: Arc<TS>, task: Arc<Task<S, C>>) -> Self {
+        Self::new(
+            H1::build(transition_system.clone(), task.clone()),
+            H2::build(transition_system, task),
+        )
+    }
 }
 
 /// Generic definition of a search node and the associated ordering functions
@@ -200,11 +780,16 @@ where
 
 /// Definition of the different conflict types.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConflictType {
     /// Conflict involving a frozen agent
     Frozen,
     /// Solving this conflicts delays both agents
     Cardinal,
+    /// One of the agents has reached its goal and stays there, so that it must vacate it to let
+    /// the other agent pass. Such a conflict is resolved after the cardinal ones, which also covers
+    /// the goal-blocking conflicts that delay both agents, but before the ones that delay a single agent.
+    GoalBlocking,
     /// Solving this conflicts delays one agent
     SemiCardinal,
     /// The conflict can be solved without delaying any agent
@@ -221,6 +806,11 @@ where
     pub moves: A2<Move<S, A, C>>,
     pub type_: ConflictType,
     pub overcost: DC,
+    /// Sliding window in which the moves exceed the capacity of their edge, for capacity conflicts.
+    pub window: Option<DC>,
+    /// Increase of the lower bound of each child of the node if the conflict is split on,
+    /// or None for a child that has no solution, once the conflict has been classified.
+    pub increases: Option<A2<Option<DC>>>,
 }
 
 impl<S, A, C, DC> Conflict<S, A, C, DC>
@@ -233,6 +823,23 @@ where
             moves,
             type_: ConflictType::NonCardinal,
             overcost: DC::default(),
+            window: None,
+            increases: None,
+        }
+    }
+
+    /// Returns true if one of the agents stays at its goal forever while the other one passes through it.
+    pub fn is_goal_blocking(&self) -> bool {
+        (self.moves.0.interval.end == C::max_value())
+            != (self.moves.1.interval.end == C::max_value())
+    }
+
+    /// Creates a conflict between the first and the last of too many moves through the same edge
+    /// within the given sliding window.
+    pub fn new_capacity(moves: A2<Move<S, A, C>>, window: DC) -> Self {
+        Self {
+            window: Some(window),
+            ..Self::new(moves)
         }
     }
 }
@@ -306,6 +913,7 @@ pub trait LimitValues {
 
 /// Defines a time interval (start <= end).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Interval<C>
 where
     C: PartialEq + Eq + PartialOrd + Ord + LimitValues,
@@ -341,7 +949,8 @@ where
 }
 
 /// The types of constraints that can be imposed on agents in a search algorithm.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConstraintType {
     /// Constraint that prevents an agent from visiting the given state during a given interval.
     State,
@@ -351,6 +960,7 @@ pub enum ConstraintType {
 
 /// Defines a constraint that can be imposed on a given agent in a search algorithm.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Constraint<S, C>
 where
     C: PartialEq + Eq + PartialOrd + Ord + LimitValues,
@@ -360,6 +970,9 @@ where
     pub next: Option<S>,
     pub interval: Interval<C>,
     pub type_: ConstraintType,
+    /// Weight of the temporal margin sought around the constraint when breaking ties
+    /// between equal-cost paths, no margin is sought if zero.
+    pub buffer_weight: f32,
 }
 
 impl<S, C> Constraint<S, C>
@@ -373,6 +986,7 @@ where
             next: None,
             interval,
             type_: ConstraintType::State,
+            buffer_weight: 0.0,
         }
     }
     pub fn new_action_constraint(agent: usize, state: S, next: S, interval: Interval<C>) -> Self {
@@ -382,10 +996,66 @@ where
             next: Some(next),
             interval,
             type_: ConstraintType::Action,
+            buffer_weight: 0.0,
         }
     }
 }
 
+impl<S, C> Constraint<S, C>
+where
+    S: Hash,
+    C: PartialEq + Eq + PartialOrd + Ord + LimitValues + Hash,
+{
+    /// Returns a hash of all the fields of the constraint, whereas constraints
+    /// are only compared by their intervals.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        self.agent.hash(&mut hasher);
+        self.state.hash(&mut hasher);
+        self.next.hash(&mut hasher);
+        self.interval.hash(&mut hasher);
+        self.type_.hash(&mut hasher);
+        self.buffer_weight.to_bits().hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<S, C> Constraint<S, C>
+where
+    S: PartialEq,
+    C: PartialEq + Eq + PartialOrd + Ord + LimitValues,
+{
+    /// Returns true if all the fields of both constraints are equal, whereas constraints
+    /// are only compared by their intervals.
+    pub fn is_identical(&self, other: &Self) -> bool {
+        self.agent == other.agent
+            && self.state == other.state
+            && self.next == other.next
+            && self.interval == other.interval
+            && self.type_ == other.type_
+            && self.buffer_weight.to_bits() == other.buffer_weight.to_bits()
+    }
+}
+
+/// Returns true if both lists contain the same constraints in any order, comparing all their fields.
+pub fn identical_constraints<S, C, T>(first: &[T], second: &[T]) -> bool
+where
+    S: PartialEq,
+    C: PartialEq + Eq + PartialOrd + Ord + LimitValues,
+    T: Borrow<Constraint<S, C>>,
+{
+    let count = |list: &[T], constraint: &Constraint<S, C>| {
+        list.iter()
+            .filter(|other| constraint.is_identical((*other).borrow()))
+            .count()
+    };
+
+    first.len() == second.len()
+        && first.iter().all(|constraint| {
+            count(first, constraint.borrow()) == count(second, constraint.borrow())
+        })
+}
+
 impl<S, C> PartialEq for Constraint<S, C>
 where
     C: PartialEq + Eq + PartialOrd + Ord + LimitValues,
@@ -448,15 +1118,15 @@ where
         match constraint.type_ {
             ConstraintType::State => {
                 self.state_constraints
-                    .entry(constraint.state.clone())
+                    .entry(constraint.state.canonical().into_owned())
                     .or_default()
                     .push(constraint.as_ref().clone());
             }
             ConstraintType::Action => {
                 self.action_constraints
                     .entry((
-                        constraint.state.clone(),
-                        constraint.next.as_ref().unwrap().clone(),
+                        constraint.state.canonical().into_owned(),
+                        constraint.next.as_ref().unwrap().canonical().into_owned(),
                     ))
                     .or_default()
                     .push(constraint.as_ref().clone());
@@ -464,12 +1134,64 @@ where
         }
     }
 
+    /// Adds all the constraints of the other set to this one.
+    pub fn merge(&mut self, other: &Self) {
+        for (state, constraints) in other.state_constraints.iter() {
+            self.state_constraints
+                .entry(state.clone())
+                .or_default()
+                .extend(constraints.iter().cloned());
+        }
+        for (states, constraints) in other.action_constraints.iter() {
+            self.action_constraints
+                .entry(states.clone())
+                .or_default()
+                .extend(constraints.iter().cloned());
+        }
+    }
+
     pub fn get_state_constraints(&self, state: &S) -> Option<&Vec<Constraint<S, C>>> {
-        self.state_constraints.get(state)
+        self.state_constraints.get(state.canonical().as_ref())
     }
 
     pub fn get_action_constraints(&self, from: &S, to: &S) -> Option<&Vec<Constraint<S, C>>> {
-        self.action_constraints.get(&(from.clone(), to.clone()))
+        self.action_constraints
+            .get(&(from.canonical().into_owned(), to.canonical().into_owned()))
+    }
+
+    /// Returns true if both sets contain the same constraints, comparing all their fields,
+    /// e.g. to tell apart different sets that share the same fingerprint.
+    pub fn is_identical(&self, other: &Self) -> bool {
+        self.state_constraints.len() == other.state_constraints.len()
+            && self.action_constraints.len() == other.action_constraints.len()
+            && self.state_constraints.iter().all(|(state, constraints)| {
+                other
+                    .state_constraints
+                    .get(state)
+                    .is_some_and(|others| identical_constraints(constraints, others))
+            })
+            && self.action_constraints.iter().all(|(states, constraints)| {
+                other
+                    .action_constraints
+                    .get(states)
+                    .is_some_and(|others| identical_constraints(constraints, others))
+            })
+    }
+
+    /// Returns a hash of the constraints of the set that does not depend on their order,
+    /// so that identical sets built in different orders share the same fingerprint.
+    /// Different sets may also share the same fingerprint, which `is_identical` tells apart.
+    pub fn fingerprint(&self) -> u64
+    where
+        C: Hash,
+    {
+        self.state_constraints
+            .values()
+            .chain(self.action_constraints.values())
+            .flatten()
+            .fold(0, |hash, constraint| {
+                hash.wrapping_add(constraint.fingerprint())
+            })
     }
 
     pub fn unify(&mut self) {
@@ -488,11 +1210,13 @@ where
                 {
                     constraint.interval.end =
                         constraint.interval.end.max(constraints[j].interval.end);
+                    constraint.buffer_weight =
+                        constraint.buffer_weight.max(constraints[j].buffer_weight);
                     j += 1;
                 }
 
                 unified_constraints.push(constraint);
-                i = j + 1;
+                i = j;
             }
 
             *constraints = unified_constraints;
@@ -513,11 +1237,13 @@ where
                 {
                     constraint.interval.end =
                         constraint.interval.end.max(constraints[j].interval.end);
+                    constraint.buffer_weight =
+                        constraint.buffer_weight.max(constraints[j].buffer_weight);
                     j += 1;
                 }
 
                 unified_constraints.push(constraint);
-                i = j + 1;
+                i = j;
             }
 
             *constraints = unified_constraints;
@@ -526,3 +1252,616 @@ where
 }
 
 pub type LandmarkSet<S, C> = Vec<Arc<Constraint<S, C>>>;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{self, AtomicUsize},
+        Arc,
+    };
+
+    use ordered_float::OrderedFloat;
+    use tuple::T2;
+
+    use crate::{
+        build_heuristics, simple_graph, Action, CbsConfig, ConflictBasedSearch, Constraint,
+        ConstraintSet, DifferentialHeuristic, EnergyModel, FallbackHeuristic, Graph, GraphEdgeId,
+        GraphNodeId, Heuristic, Interval, MatrixHeuristic, MyTime, ReverseResumableAStar,
+        SafeIntervalPathPlanning, SimpleHeuristic, SimpleState, SimpleWorld, SippConfig, SippState,
+        Solution, SolutionSet, Task, TransitionSystem,
+    };
+
+    struct SimpleEnergy;
+
+    impl EnergyModel<GraphEdgeId, MyTime, f32> for SimpleEnergy {
+        fn action_energy(&self, _action: &GraphEdgeId, duration: MyTime) -> f32 {
+            2.0 * duration.0 + 1.0
+        }
+
+        fn wait_energy(&self, duration: MyTime) -> f32 {
+            0.5 * duration.0
+        }
+    }
+
+    #[test]
+    fn test_energy() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(3)),
+            OrderedFloat(0.0),
+        ));
+
+        // The agent must wait one time unit before entering the second state
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(GraphNodeId(1)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(2.0)),
+        )));
+
+        let config = SippConfig::new(
+            task.clone(),
+            Default::default(),
+            Arc::new(constraints),
+            Arc::new(ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            )),
+            1e-6.into(),
+        );
+        let solution = solver.solve(&config).unwrap();
+
+        // Three moves of duration 1 and a wait of duration 1
+        let energy = solution.energy(&SimpleEnergy);
+        assert!((energy - (3.0 * 3.0 + 0.5)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_occupancy_timeline() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        // Two agents swapping their positions along a row
+        let tasks = vec![
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(size - 1)),
+                OrderedFloat(0.0),
+            )),
+            Arc::new(Task::new(
+                SimpleState(GraphNodeId(size - 1)),
+                SimpleState(GraphNodeId(0)),
+                OrderedFloat(0.0),
+            )),
+        ];
+        let pivots = Arc::new(tasks.iter().map(|t| t.goal_state.clone()).collect());
+        let heuristic_to_pivots = Arc::new(
+            tasks
+                .iter()
+                .map(|t| {
+                    Arc::new(ReverseResumableAStar::new(
+                        transition_system.clone(),
+                        t.clone(),
+                        SimpleHeuristic::new(transition_system.clone(), Arc::new(t.reverse())),
+                    ))
+                })
+                .collect(),
+        );
+        let config = CbsConfig::new(tasks, pivots, heuristic_to_pivots, OrderedFloat(1e-6));
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+
+        let timeline = solutions.occupancy_timeline(OrderedFloat(0.25));
+        assert!(!timeline.is_empty());
+        for occupancy in timeline.iter() {
+            assert!(occupancy.values().all(|agents| agents.len() == 1));
+        }
+        assert_eq!(timeline[0][&SimpleState(GraphNodeId(0))], vec![0]);
+
+        // Both agents are in transit after their departure and occupy both states of their moves
+        assert_eq!(timeline[1].len(), 4);
+        assert_eq!(timeline[1][&SimpleState(GraphNodeId(1))], vec![0]);
+        assert_eq!(
+            timeline.last().unwrap()[&SimpleState(GraphNodeId(0))],
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_step_slack() {
+        // Corridor with a parking spot next to its middle node
+        let mut graph = Graph::new();
+        for x in 0..5 {
+            graph.add_node((x as f32, 0.0));
+        }
+        graph.add_node((2.0, 1.0));
+        for x in 0..4 {
+            graph.add_edge(GraphNodeId(x), GraphNodeId(x + 1), 1.0);
+            graph.add_edge(GraphNodeId(x + 1), GraphNodeId(x), 1.0);
+        }
+        graph.add_edge(GraphNodeId(2), GraphNodeId(5), 1.0);
+        graph.add_edge(GraphNodeId(5), GraphNodeId(2), 1.0);
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        let tasks = [(1, 2), (0, 4)]
+            .iter()
+            .map(|(from, to)| {
+                Arc::new(Task::new(
+                    SimpleState(GraphNodeId(*from)),
+                    SimpleState(GraphNodeId(*to)),
+                    OrderedFloat(0.0),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let pivots = Arc::new(tasks.iter().map(|t| t.goal_state.clone()).collect());
+        let heuristic_to_pivots = Arc::new(
+            tasks
+                .iter()
+                .map(|t| {
+                    Arc::new(ReverseResumableAStar::new(
+                        transition_system.clone(),
+                        t.clone(),
+                        SimpleHeuristic::new(transition_system.clone(), Arc::new(t.reverse())),
+                    ))
+                })
+                .collect(),
+        );
+        let config = CbsConfig::new(tasks, pivots, heuristic_to_pivots, OrderedFloat(1e-6));
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+
+        let slack = solutions.step_slack();
+        assert_eq!(slack[0].len(), solutions[0].steps.len());
+        assert_eq!(slack[1].len(), solutions[1].steps.len());
+
+        // The second agent takes the edge right after the first agent, which cannot be delayed much
+        assert!(slack[0][0] < OrderedFloat(0.5));
+        // Nobody else goes through the start of the corridor
+        assert!(slack[1][0] > OrderedFloat(10.0));
+        // Agents hold their last state
+        assert_eq!(*slack[0].last().unwrap(), OrderedFloat(0.0));
+        assert_eq!(*slack[1].last().unwrap(), OrderedFloat(0.0));
+    }
+
+    #[test]
+    fn test_structurally_eq() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let solve = |goal| {
+            let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+            let task = Arc::new(Task::new(
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(goal)),
+                OrderedFloat(0.0),
+            ));
+            let config = SippConfig::new(
+                task.clone(),
+                Default::default(),
+                Default::default(),
+                Arc::new(ReverseResumableAStar::new(
+                    transition_system.clone(),
+                    task.clone(),
+                    SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+                )),
+                1e-6.into(),
+            );
+            solver.solve(&config).unwrap()
+        };
+
+        let precision = OrderedFloat(1e-6);
+        let solution = solve(size * size - 1);
+        let other = solve(size * size - 1);
+        assert!(!Arc::ptr_eq(&solution.steps[0].0, &other.steps[0].0));
+        assert!(solution.structurally_eq(&other, precision));
+
+        // Costs are compared up to the given precision
+        let mut shifted = other.clone();
+        shifted.cost += 1e-7;
+        assert!(solution.structurally_eq(&shifted, precision));
+        shifted.cost += 1e-3;
+        assert!(!solution.structurally_eq(&shifted, precision));
+
+        assert!(!solution.structurally_eq(&solve(size - 1), precision));
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let solution = |steps: &[(usize, f32, bool)]| {
+            let mut solution = Solution::default();
+            for (node, time, moving) in steps.iter() {
+                solution.steps.push((
+                    Arc::new(SippState {
+                        safe_interval: Interval::default(),
+                        internal_state: SimpleState(GraphNodeId(*node)),
+                    }),
+                    OrderedFloat(*time),
+                ));
+                if *moving {
+                    solution
+                        .actions
+                        .push(Action::new(GraphEdgeId(0), OrderedFloat(1.0)));
+                } else {
+                    solution.actions.push(Action::wait(OrderedFloat(1.0)));
+                }
+            }
+            solution.actions.pop();
+            solution.cost = solution.steps.last().unwrap().1;
+            solution
+        };
+
+        let solutions = [
+            // The bottleneck agent, which the second agent follows closely along a corridor
+            solution(&[
+                (1, 0.0, false),
+                (1, 1.0, true),
+                (2, 2.0, false),
+                (2, 3.0, true),
+                (3, 4.0, true),
+                (4, 5.0, true),
+            ]),
+            solution(&[
+                (0, 0.0, true),
+                (1, 1.0, false),
+                (1, 2.0, true),
+                (2, 3.0, true),
+            ]),
+            // An agent that finishes at the same time, without interacting with the others
+            solution(&[
+                (10, 0.0, true),
+                (11, 1.0, true),
+                (12, 2.0, true),
+                (13, 3.0, true),
+                (14, 4.0, true),
+                (15, 5.0, true),
+            ]),
+        ];
+
+        let slack = solutions.step_slack();
+        assert_eq!(slack[0][..4], [OrderedFloat(0.0); 4]);
+        assert!(slack[0][4] > OrderedFloat(0.0));
+
+        assert_eq!(solutions.critical_path(), Some((0, vec![0, 1, 2, 3, 5])));
+        assert_eq!(solutions[2..].critical_path(), Some((0, vec![5])));
+        assert_eq!(solutions[..0].critical_path(), None);
+    }
+
+    #[test]
+    fn test_fallback_heuristic() {
+        // The distances are precomputed before the third node is connected
+        let mut graph = Graph::new();
+        for x in 0..3 {
+            graph.add_node((x as f32, 0.0));
+        }
+        graph.add_edge(GraphNodeId(0), GraphNodeId(1), 1.0);
+        graph.add_edge(GraphNodeId(1), GraphNodeId(0), 1.0);
+        let distances =
+            Arc::new(graph.all_pairs_distances(|edge| OrderedFloat(graph.get_edge(edge).data)));
+        graph.add_edge(GraphNodeId(1), GraphNodeId(2), 1.0);
+        graph.add_edge(GraphNodeId(2), GraphNodeId(1), 1.0);
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(2)),
+            OrderedFloat(0.0),
+        ));
+        let primary = MatrixHeuristic::new(distances.clone(), task.clone());
+        assert_eq!(primary.get_heuristic(&SimpleState(GraphNodeId(0))), None);
+
+        let heuristic = Arc::new(FallbackHeuristic::new(
+            MatrixHeuristic::new(distances, task.clone()),
+            SimpleHeuristic::new(transition_system.clone(), task.clone()),
+        ));
+        assert_eq!(
+            heuristic.get_heuristic(&SimpleState(GraphNodeId(0))),
+            Some(OrderedFloat(2.0))
+        );
+        assert_eq!(
+            heuristic.get_heuristic(&SimpleState(GraphNodeId(2))),
+            Some(OrderedFloat(0.0))
+        );
+
+        // The search is not pruned at the initial state anymore
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+        let config = SippConfig::new(
+            task,
+            Default::default(),
+            Default::default(),
+            heuristic,
+            1e-6.into(),
+        );
+        let solution = solver.solve(&config).unwrap();
+        assert_eq!(solution.cost, OrderedFloat(2.0));
+    }
+
+    #[test]
+    fn test_binding_constraints() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(2)),
+            OrderedFloat(0.0),
+        ));
+
+        // The first constraint delays the agent, the second one is far from its path
+        let constraints = vec![
+            Constraint::new_state_constraint(
+                0,
+                SimpleState(GraphNodeId(1)),
+                Interval::new(OrderedFloat(0.0), OrderedFloat(2.0)),
+            ),
+            Constraint::new_state_constraint(
+                0,
+                SimpleState(GraphNodeId(5 * size + 5)),
+                Interval::new(OrderedFloat(0.0), OrderedFloat(10.0)),
+            ),
+        ];
+        let mut constraint_set = ConstraintSet::default();
+        for constraint in constraints.iter() {
+            constraint_set.add(&Arc::new(constraint.clone()));
+        }
+
+        let config = SippConfig::new(
+            task.clone(),
+            Default::default(),
+            Arc::new(constraint_set),
+            Arc::new(ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            )),
+            1e-6.into(),
+        );
+        let solution = solver.solve(&config).unwrap();
+        assert_eq!(solution.cost, OrderedFloat(3.0));
+
+        let solutions = [solution];
+        assert_eq!(
+            solutions.binding_constraints(&constraints, OrderedFloat(1e-6)),
+            vec![0]
+        );
+    }
+
+    /// Heuristic that counts its full evaluations, and optionally hides its lower bound.
+    struct CountingHeuristic<H> {
+        heuristic: H,
+        use_lower_bound: bool,
+        evaluations: AtomicUsize,
+    }
+
+    impl<H> Heuristic<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime> for CountingHeuristic<H>
+    where
+        H: Heuristic<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime>,
+    {
+        fn get_heuristic(&self, state: &SimpleState) -> Option<MyTime> {
+            self.evaluations.fetch_add(1, atomic::Ordering::Relaxed);
+            self.heuristic.get_heuristic(state)
+        }
+
+        fn lower_bound(&self, state: &SimpleState) -> Option<MyTime> {
+            if self.use_lower_bound {
+                self.heuristic.lower_bound(state)
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        // The goal state is in the middle of the grid and all the border states are pivots
+        let goal_state = SimpleState(GraphNodeId(5 + 5 * size));
+        let pivots = (0..size * size)
+            .filter(|n| {
+                n % size == 0 || n % size == size - 1 || n / size == 0 || n / size == size - 1
+            })
+            .map(|n| SimpleState(GraphNodeId(n)))
+            .collect::<Vec<_>>();
+        let pivot_tasks = pivots
+            .iter()
+            .map(|pivot| {
+                Arc::new(Task::new(
+                    goal_state.clone(),
+                    pivot.clone(),
+                    OrderedFloat(0.0),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let heuristic_to_pivots = Arc::new(build_heuristics::<_, _, _, _, _, SimpleHeuristic>(
+            &transition_system,
+            &pivot_tasks,
+        ));
+
+        // The tight deadline prunes the successors that do not lead to the goal
+        let task = Arc::new(Task::new_with_deadline(
+            SimpleState(GraphNodeId(0)),
+            goal_state,
+            OrderedFloat(0.0),
+            OrderedFloat(10.0),
+        ));
+
+        let solve = |use_lower_bound: bool| {
+            let heuristic = Arc::new(CountingHeuristic {
+                heuristic: DifferentialHeuristic::new(
+                    task.clone(),
+                    Arc::new(pivots.clone()),
+                    heuristic_to_pivots.clone(),
+                ),
+                use_lower_bound,
+                evaluations: AtomicUsize::new(0),
+            });
+            let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+            let config = SippConfig::new(
+                task.clone(),
+                Default::default(),
+                Default::default(),
+                heuristic.clone(),
+                1e-6.into(),
+            );
+            let solution = solver.solve(&config).unwrap();
+            (
+                solution,
+                heuristic.evaluations.load(atomic::Ordering::Relaxed),
+            )
+        };
+
+        let (bounded, bounded_evaluations) = solve(true);
+        let (unbounded, unbounded_evaluations) = solve(false);
+        assert_eq!(bounded.cost, OrderedFloat(10.0));
+        assert!(bounded.structurally_eq(&unbounded, OrderedFloat(1e-6)));
+        assert!(bounded_evaluations < unbounded_evaluations);
+    }
+
+    #[test]
+    fn test_smooth() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = SimpleWorld::new(graph.clone(), 0.4);
+
+        let solution = |path: &[(usize, f32)]| {
+            let steps = path
+                .iter()
+                .map(|(node, time)| {
+                    let state = SippState {
+                        safe_interval: Interval::default(),
+                        internal_state: SimpleState(GraphNodeId(*node)),
+                    };
+                    (Arc::new(state), OrderedFloat(*time))
+                })
+                .collect::<Vec<_>>();
+            let actions = path
+                .windows(2)
+                .map(|w| {
+                    let duration = OrderedFloat(w[1].1 - w[0].1);
+                    graph
+                        .neighbors(GraphNodeId(w[0].0))
+                        .find(|(_, next, _)| next.0 == w[1].0)
+                        .map_or(Action::wait(duration), |(edge, _, _)| {
+                            Action::new(edge, duration)
+                        })
+                })
+                .collect();
+            Solution {
+                cost: steps.last().unwrap().1,
+                steps,
+                actions,
+            }
+        };
+
+        // The agent steps aside and comes back before heading to its goal
+        let path = solution(&[(11, 0.0), (12, 1.0), (12, 3.0), (11, 4.0), (21, 5.0)]);
+        let smoothed = path.smooth(&transition_system, &[]);
+        assert_eq!(smoothed.actions.len(), 2);
+        assert!(smoothed.actions[0].action.is_none());
+        assert_eq!(smoothed.actions[0].cost, OrderedFloat(4.0));
+        assert_eq!(smoothed.cost, path.cost);
+        assert_eq!(
+            smoothed
+                .steps
+                .iter()
+                .map(|(s, t)| (s.internal_state.0 .0, t.0))
+                .collect::<Vec<_>>(),
+            vec![(11, 0.0), (11, 4.0), (21, 5.0)]
+        );
+
+        // The smoothed path remains free of conflicts with an agent passing by after the return
+        let later = solution(&[(1, 4.5), (11, 5.5), (10, 6.5)]);
+        assert!(!later.to_moves(1).iter().any(|m| {
+            smoothed
+                .to_moves(0)
+                .iter()
+                .any(|n| m.interval.overlaps(&n.interval) && transition_system.conflict(T2(m, n)))
+        }));
+        assert_eq!(path.smooth(&transition_system, &[&later]).actions.len(), 2);
+
+        // The oscillation lets another agent pass through the state, so it is kept
+        let other = solution(&[(1, 0.5), (11, 1.5), (10, 2.5)]);
+        let smoothed = path.smooth(&transition_system, &[&other]);
+        assert_eq!(smoothed.actions.len(), path.actions.len());
+    }
+
+    #[test]
+    fn test_unify() {
+        let (a, b) = (SimpleState(GraphNodeId(0)), SimpleState(GraphNodeId(1)));
+        let mut constraints = ConstraintSet::default();
+        for (start, end) in [(3.0, 5.0), (0.0, 2.0), (7.0, 8.0), (1.0, 4.0)] {
+            let interval = Interval::new(OrderedFloat(start), OrderedFloat(end));
+            constraints.add(&Arc::new(Constraint::new_state_constraint(
+                0,
+                a.clone(),
+                interval,
+            )));
+            constraints.add(&Arc::new(Constraint::new_action_constraint(
+                0,
+                a.clone(),
+                b.clone(),
+                interval,
+            )));
+        }
+        constraints.unify();
+
+        // The three overlapping constraints are merged, and the next one is kept
+        let expected = vec![
+            Interval::new(OrderedFloat(0.0), OrderedFloat(5.0)),
+            Interval::new(OrderedFloat(7.0), OrderedFloat(8.0)),
+        ];
+        let intervals = |constraints: &Vec<Constraint<SimpleState, MyTime>>| {
+            constraints.iter().map(|c| c.interval).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            intervals(constraints.get_state_constraints(&a).unwrap()),
+            expected
+        );
+        assert_eq!(
+            intervals(constraints.get_action_constraints(&a, &b).unwrap()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_is_identical() {
+        let (a, b) = (SimpleState(GraphNodeId(0)), SimpleState(GraphNodeId(1)));
+        let interval = |start, end| Interval::new(OrderedFloat(start), OrderedFloat(end));
+        let constraints = [
+            Constraint::new_state_constraint(0, a.clone(), interval(0.0, 1.0)),
+            Constraint::new_state_constraint(0, a.clone(), interval(2.0, 3.0)),
+            Constraint::new_action_constraint(0, a.clone(), b.clone(), interval(0.0, 1.0)),
+        ];
+        let set = |constraints: &[Constraint<SimpleState, MyTime>]| {
+            let mut set = ConstraintSet::default();
+            for constraint in constraints {
+                set.add(&Arc::new(constraint.clone()));
+            }
+            set
+        };
+
+        // The order in which the constraints are added does not matter
+        let mut reversed = constraints.clone();
+        reversed.reverse();
+        assert!(set(&constraints).is_identical(&set(&reversed)));
+
+        // Constraints with the same intervals are equal, but not identical if any other field differs
+        let mut buffered = constraints.clone();
+        buffered[1].buffer_weight = 1.0;
+        assert_eq!(buffered[1], constraints[1]);
+        assert!(!set(&constraints).is_identical(&set(&buffered)));
+        assert!(!set(&constraints).is_identical(&set(&constraints[..2])));
+    }
+}
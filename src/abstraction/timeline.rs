@@ -0,0 +1,266 @@
+use std::{fmt::Debug, ops::Add, sync::Arc};
+
+use ordered_float::OrderedFloat;
+use tuple::{A2, T2};
+
+use crate::{Interval, LimitValues, Move, MyTime, SippState, Solution, TransitionSystem};
+
+/// Conversion of the times of an agent into a common timeline, on which the solutions
+/// of agents planned with different time bases can be checked for conflicts.
+pub trait TimeBase<C, T> {
+    /// Converts the given time into the common timeline.
+    fn to_common(&self, time: C) -> T;
+
+    /// Converts the given time of the common timeline back into the time base of the agent.
+    fn to_native(&self, time: T) -> C;
+}
+
+/// Time base in which times are counted in units of the given duration, e.g. ticks,
+/// converted into a common timeline in seconds.
+#[derive(Debug, Clone, Copy)]
+pub struct ScaledTimeBase {
+    unit: f32,
+}
+
+impl ScaledTimeBase {
+    pub fn new(unit: f32) -> Self {
+        ScaledTimeBase { unit }
+    }
+}
+
+impl TimeBase<MyTime, MyTime> for ScaledTimeBase {
+    fn to_common(&self, time: MyTime) -> MyTime {
+        if time == MyTime::max_value() || time == MyTime::min_value() {
+            return time;
+        }
+
+        OrderedFloat(time.0 * self.unit)
+    }
+
+    fn to_native(&self, time: MyTime) -> MyTime {
+        if time == MyTime::max_value() || time == MyTime::min_value() {
+            return time;
+        }
+
+        OrderedFloat(time.0 / self.unit)
+    }
+}
+
+impl<S, A, C, DC> Solution<Arc<SippState<S, C>>, A, C, DC>
+where
+    S: Debug + Eq + Clone,
+    A: Copy,
+    C: Ord + Default + Copy + LimitValues,
+{
+    /// Returns the moves that the given agent performs along the solution, one for each of its steps,
+    /// with their intervals converted into the common timeline.
+    pub fn to_common_moves<T, B>(&self, agent: usize, time_base: &B) -> Vec<Move<S, A, T>>
+    where
+        T: Ord + LimitValues,
+        B: TimeBase<C, T> + ?Sized,
+    {
+        (0..self.steps.len())
+            .map(|i| {
+                let (state, start) = &self.steps[i];
+                let start = time_base.to_common(*start);
+                match self.steps.get(i + 1) {
+                    Some((next, end)) => Move::new(
+                        agent,
+                        state.internal_state.clone(),
+                        next.internal_state.clone(),
+                        self.actions[i].action,
+                        Interval::new(start, time_base.to_common(*end)),
+                    ),
+                    None => Move::new(
+                        agent,
+                        state.internal_state.clone(),
+                        state.internal_state.clone(),
+                        None,
+                        Interval::new(start, T::max_value()),
+                    ),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Returns the first pair of moves of two agents that overlap in time and lead to a collision
+/// in the given transition system, whose time is the common timeline of both agents.
+/// The times of different time bases only agree up to the given tolerance, so the moves
+/// are also checked with either of them delayed by the tolerance, which keeps their motion exact.
+pub fn find_conflict_on_common_timeline<TS, S, A, T, DT>(
+    transition_system: &TS,
+    moves: A2<&[Move<S, A, T>]>,
+    tolerance: DT,
+) -> Option<A2<Move<S, A, T>>>
+where
+    TS: TransitionSystem<S, A, T, DT>,
+    S: Clone,
+    A: Clone,
+    T: Ord + LimitValues + Copy + Add<DT, Output = T>,
+    DT: Ord + Default + Copy,
+{
+    find_conflict_indices(transition_system, moves, tolerance)
+        .map(|(i, j)| T2(moves.0[i].clone(), moves.1[j].clone()))
+}
+
+/// Returns the indices of the first pair of moves of two agents that lead to a collision
+/// on the common timeline, up to the given tolerance.
+pub(crate) fn find_conflict_indices<TS, S, A, T, DT>(
+    transition_system: &TS,
+    moves: A2<&[Move<S, A, T>]>,
+    tolerance: DT,
+) -> Option<(usize, usize)>
+where
+    TS: TransitionSystem<S, A, T, DT>,
+    S: Clone,
+    A: Clone,
+    T: Ord + LimitValues + Copy + Add<DT, Output = T>,
+    DT: Ord + Default + Copy,
+{
+    let delayed = |m: &Move<S, A, T>| {
+        let mut m = m.clone();
+        m.interval.start = m.interval.start + tolerance;
+        if m.interval.end != T::max_value() {
+            m.interval.end = m.interval.end + tolerance;
+        }
+        m
+    };
+    let collide = |first: &Move<S, A, T>, second: &Move<S, A, T>| {
+        first.interval.overlaps(&second.interval) && transition_system.conflict(T2(first, second))
+    };
+
+    for (i, first) in moves.0.iter().enumerate() {
+        for (j, second) in moves.1.iter().enumerate() {
+            if collide(first, second)
+                || (tolerance > DT::default()
+                    && (collide(&delayed(first), second) || collide(first, &delayed(second))))
+            {
+                return Some((i, j));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+    use tuple::T2;
+
+    use crate::{
+        find_conflict_on_common_timeline, simple_graph, Action, Graph, GraphEdgeId, GraphNodeId,
+        Interval, MyTime, ScaledTimeBase, SimpleEdgeData, SimpleNodeData, SimpleState, SimpleWorld,
+        SippState, Solution,
+    };
+
+    /// Builds the solution that visits the given nodes at the given times.
+    fn solution(
+        graph: &Graph<SimpleNodeData, SimpleEdgeData>,
+        path: &[(usize, f32)],
+    ) -> Solution<Arc<SippState<SimpleState, MyTime>>, GraphEdgeId, MyTime, MyTime> {
+        let steps = path
+            .iter()
+            .map(|(node, time)| {
+                let state = SippState {
+                    safe_interval: Interval::default(),
+                    internal_state: SimpleState(GraphNodeId(*node)),
+                };
+                (Arc::new(state), OrderedFloat(*time))
+            })
+            .collect::<Vec<_>>();
+        let actions = path
+            .windows(2)
+            .map(|w| {
+                let duration = OrderedFloat(w[1].1 - w[0].1);
+                graph
+                    .neighbors(GraphNodeId(w[0].0))
+                    .find(|(_, next, _)| next.0 == w[1].0)
+                    .map_or(Action::wait(duration), |(edge, _, _)| {
+                        Action::new(edge, duration)
+                    })
+            })
+            .collect();
+        Solution {
+            cost: steps.last().unwrap().1,
+            steps,
+            actions,
+        }
+    }
+
+    #[test]
+    fn test_conflict_on_common_timeline() {
+        let size = 3;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = SimpleWorld::new(graph.clone(), 0.4);
+
+        let seconds = ScaledTimeBase::new(1.0);
+        let ticks = ScaledTimeBase::new(0.1);
+        let tolerance = OrderedFloat(0.0);
+
+        // The first agent plans in seconds and the second one in ticks of a tenth of a second,
+        // they cross the center of the grid at the same time on the common timeline
+        let first = solution(&graph, &[(3, 0.0), (4, 1.0), (5, 2.0)]);
+        let second = solution(&graph, &[(1, 0.0), (1, 5.0), (4, 15.0), (7, 25.0)]);
+
+        let moves = T2(
+            first.to_common_moves(0, &seconds),
+            second.to_common_moves(1, &ticks),
+        );
+        assert!((moves.1[1].interval.start.0 - 0.5).abs() < 1e-6);
+        assert!((moves.1[1].interval.end.0 - 1.5).abs() < 1e-6);
+
+        let conflict =
+            find_conflict_on_common_timeline(&transition_system, T2(&moves.0, &moves.1), tolerance)
+                .unwrap();
+        assert_eq!(conflict.0.to, SimpleState(GraphNodeId(4)));
+        assert_eq!(conflict.1.to, SimpleState(GraphNodeId(4)));
+
+        // Both agents would never meet if the ticks of the second one were read as seconds
+        let moves = T2(
+            first.to_common_moves(0, &seconds),
+            second.to_common_moves(1, &seconds),
+        );
+        let conflict =
+            find_conflict_on_common_timeline(&transition_system, T2(&moves.0, &moves.1), tolerance);
+        assert!(conflict.is_none());
+    }
+
+    #[test]
+    fn test_conflict_within_tolerance() {
+        let size = 3;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = SimpleWorld::new(graph.clone(), 0.4);
+
+        let seconds = ScaledTimeBase::new(1.0);
+        let ticks = ScaledTimeBase::new(0.1);
+
+        // The second agent only crosses the center of the grid once the first agent has left it
+        let first = solution(&graph, &[(3, 0.0), (4, 1.0), (5, 2.0)]);
+        let second = solution(&graph, &[(1, 0.0), (1, 12.0), (4, 22.0), (7, 32.0)]);
+        let moves = T2(
+            first.to_common_moves(0, &seconds),
+            second.to_common_moves(1, &ticks),
+        );
+
+        // The motions are kept exact, so the agents narrowly avoid each other
+        let exact = find_conflict_on_common_timeline(
+            &transition_system,
+            T2(&moves.0, &moves.1),
+            OrderedFloat(0.0),
+        );
+        assert!(exact.is_none());
+
+        // A tick is too coarse to tell, so they collide when the second agent is a tick early
+        let conflict = find_conflict_on_common_timeline(
+            &transition_system,
+            T2(&moves.0, &moves.1),
+            OrderedFloat(0.1),
+        )
+        .unwrap();
+        assert_eq!(conflict.1.to, SimpleState(GraphNodeId(4)));
+        assert!((conflict.1.interval.start.0 - 1.2).abs() < 1e-6);
+    }
+}
@@ -1,4 +1,4 @@
-use std::slice;
+use std::{borrow::Cow, slice, sync::Arc};
 
 use tuple::A2;
 
@@ -9,6 +9,16 @@ pub trait State {
     /// Returns true if the state is mostly equivalent to the other state
     /// when checking whether it is a goal state.
     fn is_equivalent(&self, other: &Self) -> bool;
+
+    /// Returns the canonical form of the state, on which the constraints are imposed and the distances
+    /// of the reverse searches are computed. It is the state itself unless the state carries information
+    /// that does not affect the conflicts, e.g. how it was reached.
+    fn canonical(&self) -> Cow<'_, Self>
+    where
+        Self: Clone,
+    {
+        Cow::Borrowed(self)
+    }
 }
 
 /// Definition of a transition system that contains a set of states and actions,
@@ -30,6 +40,12 @@ where
 
     fn can_wait_at(&self, state: &S) -> bool;
 
+    /// Returns false if the second state is known to be unreachable from the first one.
+    /// By default, all states are assumed to be reachable.
+    fn reachable(&self, _from: &S, _to: &S) -> bool {
+        true
+    }
+
     /// Returns true if the two moves lead to a collision.
     fn conflict(&self, moves: A2<&Move<S, A, C>>) -> bool;
 }
@@ -45,8 +61,13 @@ impl<Action, X: FnMut(Action)> ActionCallback<Action> for X {
     }
 }
 
+/// Predicate satisfied by the goal states of a task.
+pub type GoalPredicate<S> = Arc<dyn Fn(&S) -> bool + Send + Sync>;
+
 /// Definition of a task in a given transition system that can then
 /// be fed to a search algorithm.
+/// By default, the agent parks at its goal state forever once it reaches it,
+/// while a transient agent leaves the system upon arrival.
 pub struct Task<S, C>
 where
     S: State + Eq + Clone,
@@ -55,6 +76,14 @@ where
     pub initial_state: S,
     pub goal_state: S,
     pub initial_cost: C,
+    pub transient: bool,
+    /// Time by which the agent must reach its goal state, if any.
+    pub deadline: Option<C>,
+    /// Whether the agent must leave its initial state without waiting there.
+    pub no_initial_wait: bool,
+    /// Predicate that replaces the goal state to decide which states are goals, if any.
+    /// The goal state is then only a representative of the goal states, e.g. for the pivots.
+    pub goal_predicate: Option<GoalPredicate<S>>,
 }
 
 impl<S, C> Task<S, C>
@@ -67,11 +96,63 @@ where
             initial_state,
             goal_state,
             initial_cost,
+            transient: false,
+            deadline: None,
+            no_initial_wait: false,
+            goal_predicate: None,
+        }
+    }
+
+    /// Creates a task whose goal states are the ones satisfying the given predicate,
+    /// e.g. any state in a region. The heuristic must then estimate the distance
+    /// to the nearest goal state. The given goal state should satisfy the predicate.
+    pub fn new_with_goal_predicate(
+        initial_state: S,
+        goal_state: S,
+        initial_cost: C,
+        goal_predicate: GoalPredicate<S>,
+    ) -> Self {
+        Self {
+            goal_predicate: Some(goal_predicate),
+            ..Self::new(initial_state, goal_state, initial_cost)
+        }
+    }
+
+    /// Creates a task whose goal state must be reached by the given deadline.
+    pub fn new_with_deadline(
+        initial_state: S,
+        goal_state: S,
+        initial_cost: C,
+        deadline: C,
+    ) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..Self::new(initial_state, goal_state, initial_cost)
+        }
+    }
+
+    /// Creates a task whose agent disappears as soon as it reaches its goal state,
+    /// so that the goal state can be used by the other agents afterwards.
+    pub fn new_transient(initial_state: S, goal_state: S, initial_cost: C) -> Self {
+        Self {
+            transient: true,
+            ..Self::new(initial_state, goal_state, initial_cost)
+        }
+    }
+
+    /// Creates a task whose agent must start moving immediately, e.g. to clear a depot.
+    pub fn new_without_initial_wait(initial_state: S, goal_state: S, initial_cost: C) -> Self {
+        Self {
+            no_initial_wait: true,
+            ..Self::new(initial_state, goal_state, initial_cost)
         }
     }
 
     pub fn is_goal_state(&self, state: &S) -> bool {
-        state.is_equivalent(&self.goal_state)
+        match &self.goal_predicate {
+            Some(goal_predicate) => goal_predicate(state),
+            None => state.is_equivalent(&self.goal_state),
+        }
     }
 
     pub fn reverse(&self) -> Self {
@@ -79,12 +160,17 @@ where
             initial_state: self.goal_state.clone(),
             goal_state: self.initial_state.clone(),
             initial_cost: self.initial_cost,
+            transient: self.transient,
+            deadline: None,
+            no_initial_wait: false,
+            goal_predicate: None,
         }
     }
 }
 
 /// Definition of a move in a transition system.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Move<S, A, C>
 where
     C: Ord + LimitValues,
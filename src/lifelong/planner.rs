@@ -1,7 +1,7 @@
 use std::{
     fmt::Debug,
     hash::Hash,
-    ops::{Add, Div, Sub},
+    ops::{Add, Div, Mul, Sub},
     sync::Arc,
 };
 
@@ -9,7 +9,7 @@ use fxhash::FxHashMap;
 
 use crate::{
     CbsConfig, ConflictBasedSearch, Heuristic, Interval, LimitValues, MinimalHeuristic,
-    ReverseResumableAStar, SippState, Solution, State, Task, TransitionSystem,
+    ReverseResumableAStar, SippSolution, SippState, Solution, State, Task, TransitionSystem,
 };
 
 /// A lifelong planner that uses Conflict-Based Search under the hood.
@@ -31,13 +31,21 @@ where
         + LimitValues
         + Send
         + Sync,
-    DC: Debug + Ord + Sub<DC, Output = DC> + Div<f32, Output = DC> + Copy + Default + Send + Sync,
+    DC: Debug
+        + Ord
+        + Sub<DC, Output = DC>
+        + Div<f32, Output = DC>
+        + Mul<f32, Output = DC>
+        + Copy
+        + Default
+        + Send
+        + Sync,
     H: Heuristic<TS, S, A, C, DC> + MinimalHeuristic<TS, S, A, C, DC> + Send + Sync,
 {
     transition_system: Arc<TS>,
     solver: ConflictBasedSearch<TS, S, A, C, DC, H>,
     tasks: Vec<Arc<Task<S, C>>>,
-    solutions: Vec<Solution<Arc<SippState<S, C>>, A, C, DC>>,
+    solutions: Vec<SippSolution<S, A, C, DC>>,
     heuristic_to_pivots: Vec<Arc<ReverseResumableAStar<TS, S, A, C, DC, H>>>,
     collision_precision: DC,
 }
@@ -60,7 +68,15 @@ where
         + LimitValues
         + Send
         + Sync,
-    DC: Debug + Ord + Sub<DC, Output = DC> + Div<f32, Output = DC> + Copy + Default + Send + Sync,
+    DC: Debug
+        + Ord
+        + Sub<DC, Output = DC>
+        + Div<f32, Output = DC>
+        + Mul<f32, Output = DC>
+        + Copy
+        + Default
+        + Send
+        + Sync,
     H: Heuristic<TS, S, A, C, DC> + MinimalHeuristic<TS, S, A, C, DC> + Send + Sync,
 {
     pub fn new(
@@ -114,7 +130,7 @@ where
     pub fn plan(
         &mut self,
         config: &LifelongConfig<S, C>,
-    ) -> Option<&Vec<Solution<Arc<SippState<S, C>>, A, C, DC>>> {
+    ) -> Option<&Vec<SippSolution<S, A, C, DC>>> {
         for (agent, task) in &config.tasks {
             self.tasks[*agent] = task.clone();
             self.heuristic_to_pivots[*agent] = Arc::new(ReverseResumableAStar::new(
@@ -137,7 +153,7 @@ where
             }
         }
 
-        if let Some(solutions) = self.solver.solve(&cbs_config) {
+        if let Some(solutions) = self.solver.solve(&cbs_config).solutions() {
             self.solutions = solutions;
             Some(&self.solutions)
         } else {
@@ -163,41 +179,14 @@ mod tests {
     use ordered_float::OrderedFloat;
 
     use crate::{
-        Graph, GraphNodeId, LifelongConfig, Planner, SimpleEdgeData, SimpleHeuristic,
-        SimpleNodeData, SimpleState, SimpleWorld, Task,
+        simple_graph, GraphNodeId, LifelongConfig, Planner, SimpleHeuristic, SimpleState,
+        SimpleWorld, Task,
     };
 
-    fn simple_graph(size: usize) -> Arc<Graph<SimpleNodeData, SimpleEdgeData>> {
-        let mut graph = Graph::new();
-        for x in 0..size {
-            for y in 0..size {
-                graph.add_node((x as f32, y as f32));
-            }
-        }
-        for x in 0..size {
-            for y in 0..size {
-                let node_id = GraphNodeId(x + y * size);
-                if x > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x - 1 + y * size), 1.0);
-                }
-                if y > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y - 1) * size), 1.0);
-                }
-                if x < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + 1 + y * size), 1.0);
-                }
-                if y < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y + 1) * size), 1.0);
-                }
-            }
-        }
-        Arc::new(graph)
-    }
-
     #[test]
     fn test_simple() {
         let size = 10;
-        let graph = simple_graph(size);
+        let graph = Arc::new(simple_graph(size));
         let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
 
         let initial_states = vec![
@@ -1,22 +1,27 @@
 use std::{
-    cmp::{Ordering, Reverse},
-    collections::BinaryHeap,
+    cmp::Ordering,
+    collections::BTreeSet,
     fmt::Debug,
     hash::Hash,
     marker::PhantomData,
-    ops::{Add, Div, Sub},
+    ops::{Add, Div, Mul, Sub},
     sync::Arc,
+    time::{Duration, Instant},
     vec,
 };
 
 use fxhash::FxHashMap;
 use parking_lot::{Condvar, Mutex};
+#[cfg(feature = "rand")]
+use rand::{rngs::StdRng, SeedableRng};
 use tuple::{A2, T2};
 
 use crate::{
-    Conflict, ConflictType, Constraint, ConstraintSet, ConstraintType, Heuristic, Interval,
-    LSippConfig, LSippStats, LandmarkSet, LimitValues, Move, ReverseResumableAStar, RraStats,
-    SafeIntervalPathPlanningWithLandmarks, SippState, Solution, State, Task, TransitionSystem,
+    find_conflict_indices, identical_constraints, CbsCheckpoint, Conflict, ConflictType,
+    Constraint, ConstraintSet, ConstraintType, Heuristic, Interval, LSippConfig, LSippStats,
+    LandmarkSet, LimitValues, Mdd, Move, NodeRecord, ReverseResumableAStar, RraStats,
+    SafeIntervalPathPlanningWithLandmarks, SippSolution, SippState, Solution, State, Task,
+    TimeBase, TransitionSystem,
 };
 
 struct Critical<S, A, C, DC>
@@ -25,10 +30,94 @@ where
     C: Debug + Default + Copy + Ord + LimitValues,
     DC: Default + Copy + Ord,
 {
-    queue: BinaryHeap<Reverse<Arc<CbsNode<S, A, C, DC>>>>,
+    queue: OpenList<S, A, C, DC>,
     ongoing: usize,
     best: Option<Arc<CbsNode<S, A, C, DC>>>,
+    /// Whether the search has been stopped by the time limit.
+    timed_out: bool,
     stats: CbsStats,
+    /// Statistics of the low-level searches of each agent.
+    agent_stats: Vec<AgentStats>,
+}
+
+/// Open list of the high-level search, sorted by increasing cost.
+/// Nodes with equal costs are sorted in insertion order.
+struct OpenList<S, A, C, DC>
+where
+    S: Debug + State + Eq + Hash + Clone,
+    C: Debug + Default + Copy + Ord + LimitValues,
+    DC: Default + Copy + Ord,
+{
+    nodes: BTreeSet<(Arc<CbsNode<S, A, C, DC>>, usize)>,
+    counter: usize,
+}
+
+impl<S, A, C, DC> Default for OpenList<S, A, C, DC>
+where
+    S: Debug + State + Eq + Hash + Clone,
+    C: Debug + Default + Copy + Ord + LimitValues,
+    DC: Default + Copy + Ord,
+{
+    fn default() -> Self {
+        Self {
+            nodes: BTreeSet::new(),
+            counter: 0,
+        }
+    }
+}
+
+impl<S, A, C, DC> OpenList<S, A, C, DC>
+where
+    S: Debug + State + Eq + Hash + Clone,
+    C: Debug + Default + Copy + Ord + LimitValues,
+    DC: Default + Copy + Ord,
+{
+    fn push(&mut self, node: Arc<CbsNode<S, A, C, DC>>) {
+        self.nodes.insert((node, self.counter));
+        self.counter += 1;
+    }
+
+    /// Returns the node with the lowest cost.
+    fn first(&self) -> Option<&Arc<CbsNode<S, A, C, DC>>> {
+        self.nodes.first().map(|(node, _)| node)
+    }
+
+    /// Returns the node with the highest cost.
+    fn last(&self) -> Option<&Arc<CbsNode<S, A, C, DC>>> {
+        self.nodes.last().map(|(node, _)| node)
+    }
+
+    /// Removes the node with the lowest cost.
+    fn pop_first(&mut self) -> Option<Arc<CbsNode<S, A, C, DC>>> {
+        self.nodes.pop_first().map(|(node, _)| node)
+    }
+
+    /// Removes the node with the fewest conflicts among the nodes whose cost does not exceed
+    /// the given bound, or the node with the lowest cost if none does.
+    fn pop_focal(&mut self, bound: DC) -> Option<Arc<CbsNode<S, A, C, DC>>> {
+        let key = self
+            .nodes
+            .iter()
+            .take_while(|(node, _)| node.total_cost <= bound)
+            .min_by_key(|(node, counter)| (node.conflicts.len(), *counter))
+            .or(self.nodes.first())?
+            .clone();
+        self.nodes.remove(&key);
+        Some(key.0)
+    }
+
+    /// Removes the node with the highest cost.
+    fn pop_last(&mut self) -> Option<Arc<CbsNode<S, A, C, DC>>> {
+        self.nodes.pop_last().map(|(node, _)| node)
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+    }
 }
 
 struct Shared<TS, S, A, C, DC>
@@ -36,6 +125,7 @@ where
     TS: TransitionSystem<S, A, C, DC>,
     S: Debug + State + Eq + Hash + Clone,
     C: Debug
+        + Hash
         + Eq
         + PartialOrd
         + Ord
@@ -49,8 +139,36 @@ where
     transition_system: Arc<TS>,
     critical: Mutex<Critical<S, A, C, DC>>,
     monitor: Condvar,
+    /// Low-level solutions indexed by agent and by fingerprint of its constraints and landmarks,
+    /// along with these constraints and landmarks to tell apart the subproblems whose fingerprints collide.
+    solution_cache: Mutex<SolutionCache<S, A, C, DC>>,
+    /// Diagrams of the optimal paths of the agents, used to classify the conflicts of unconstrained agents.
+    mdds: Mutex<FxHashMap<usize, Arc<Mdd<S, C>>>>,
 }
 
+/// Low-level search of the agents, guided by reverse searches from their goal states.
+type LowLevelSearch<TS, S, A, C, DC, H> = SafeIntervalPathPlanningWithLandmarks<
+    TS,
+    S,
+    A,
+    C,
+    DC,
+    ReverseResumableAStar<TS, S, A, C, DC, H>,
+>;
+
+/// Configuration of the low-level search of an agent.
+type LowLevelConfig<TS, S, A, C, DC, H> =
+    LSippConfig<TS, S, A, C, DC, ReverseResumableAStar<TS, S, A, C, DC, H>>;
+
+type SolutionCache<S, A, C, DC> = FxHashMap<
+    (usize, u64, u64),
+    Vec<(
+        Arc<ConstraintSet<S, C>>,
+        LandmarkSet<S, C>,
+        Option<SippSolution<S, A, C, DC>>,
+    )>,
+>;
+
 /// Implementation of the Conflict-Based Search algorithm.
 pub struct ConflictBasedSearch<TS, S, A, C, DC, H>
 where
@@ -70,7 +188,15 @@ where
         + LimitValues
         + Send
         + Sync,
-    DC: Debug + Ord + Sub<DC, Output = DC> + Div<f32, Output = DC> + Copy + Default + Send + Sync,
+    DC: Debug
+        + Ord
+        + Sub<DC, Output = DC>
+        + Div<f32, Output = DC>
+        + Mul<f32, Output = DC>
+        + Copy
+        + Default
+        + Send
+        + Sync,
     H: Heuristic<TS, S, A, C, DC> + Send + Sync,
 {
     n_threads: usize,
@@ -96,7 +222,15 @@ where
         + LimitValues
         + Send
         + Sync,
-    DC: Debug + Ord + Sub<DC, Output = DC> + Div<f32, Output = DC> + Copy + Default + Send + Sync,
+    DC: Debug
+        + Ord
+        + Sub<DC, Output = DC>
+        + Div<f32, Output = DC>
+        + Mul<f32, Output = DC>
+        + Copy
+        + Default
+        + Send
+        + Sync,
     H: Heuristic<TS, S, A, C, DC> + Send + Sync,
 {
     pub fn new(transition_system: Arc<TS>) -> Self {
@@ -105,12 +239,16 @@ where
             shared: Shared {
                 transition_system,
                 critical: Mutex::new(Critical {
-                    queue: BinaryHeap::new(),
+                    queue: OpenList::default(),
                     ongoing: 0,
                     best: None,
+                    timed_out: false,
                     stats: CbsStats::default(),
+                    agent_stats: vec![],
                 }),
                 monitor: Condvar::new(),
+                solution_cache: Mutex::new(FxHashMap::default()),
+                mdds: Mutex::new(FxHashMap::default()),
             },
             _phantom: PhantomData,
         }
@@ -119,37 +257,28 @@ where
     fn init(
         shared: &Shared<TS, S, A, C, DC>,
         config: &CbsConfig<TS, S, A, C, DC, H>,
-        lsipp: &mut SafeIntervalPathPlanningWithLandmarks<
-            TS,
-            S,
-            A,
-            C,
-            DC,
-            ReverseResumableAStar<TS, S, A, C, DC, H>,
-        >,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
     ) {
         {
             let mut critical = shared.critical.lock();
             critical.queue.clear();
             critical.best = None;
+            critical.timed_out = false;
             critical.stats = CbsStats::default();
+            critical.agent_stats = vec![AgentStats::default(); config.n_agents];
         }
+        shared.solution_cache.lock().clear();
+        shared.mdds.lock().clear();
 
-        if let Some(root) = Self::get_root(config, lsipp) {
+        if let Some(root) = Self::get_root(shared, config, lsipp) {
             Self::enqueue(shared, config, root, lsipp);
         }
     }
 
     fn get_root(
+        shared: &Shared<TS, S, A, C, DC>,
         config: &CbsConfig<TS, S, A, C, DC, H>,
-        lsipp: &mut SafeIntervalPathPlanningWithLandmarks<
-            TS,
-            S,
-            A,
-            C,
-            DC,
-            ReverseResumableAStar<TS, S, A, C, DC, H>,
-        >,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
     ) -> Option<CbsNode<S, A, C, DC>> {
         let mut root = CbsNode::default();
 
@@ -157,22 +286,27 @@ where
         for (agent, task) in config.tasks.iter().enumerate() {
             if config.frozen.contains_key(&agent) {
                 let solution = config.frozen[&agent].clone();
-                root.total_cost = solution.cost + root.total_cost - task.initial_cost;
+                root.total_cost = root.total_cost
+                    - (task.initial_cost - solution.cost) * config.get_weight(agent);
                 root.solutions.push(solution);
                 continue;
             }
 
+            let weight = config.get_weight(agent);
+            let mut constraints = config.get_reservations(agent);
+            constraints.unify();
+
             let config = LSippConfig::new_with_pivots(
                 task.clone(),
-                Default::default(),
+                Arc::new(constraints),
                 Default::default(),
                 config.pivots.clone(),
                 config.heuristic_to_pivots.clone(),
                 config.precision,
             );
 
-            if let Some(solution) = lsipp.solve(&config) {
-                root.total_cost = solution.cost + root.total_cost - task.initial_cost;
+            if let Some(solution) = Self::run_low_level(shared, agent, &config, lsipp) {
+                root.total_cost = root.total_cost - (task.initial_cost - solution.cost) * weight;
                 root.solutions.push(solution);
             } else {
                 return None;
@@ -186,27 +320,45 @@ where
         shared: &Shared<TS, S, A, C, DC>,
         config: &CbsConfig<TS, S, A, C, DC, H>,
         mut node: CbsNode<S, A, C, DC>,
-        lsipp: &mut SafeIntervalPathPlanningWithLandmarks<
-            TS,
-            S,
-            A,
-            C,
-            DC,
-            ReverseResumableAStar<TS, S, A, C, DC, H>,
-        >,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
     ) {
         if Self::compute_conflicts(shared, config, &mut node, lsipp) {
             let mut critical = shared.critical.lock();
-            critical.queue.push(Reverse(Arc::new(node)));
+            critical.stats.generated += 1;
+            critical.stats.max_depth = critical.stats.max_depth.max(node.depth);
+            critical.queue.push(Arc::new(node));
+
+            // Evict the worst nodes if the open list is too large
+            if let Some(max_open_nodes) = config.max_open_nodes {
+                while critical.queue.len() > max_open_nodes {
+                    critical.queue.pop_last();
+                    critical.stats.evicted += 1;
+                }
+            }
+            critical.stats.max_open = critical.stats.max_open.max(critical.queue.len());
         }
     }
 
-    pub fn solve(
-        &mut self,
-        config: &CbsConfig<TS, S, A, C, DC, H>,
-    ) -> Option<Vec<Solution<Arc<SippState<S, C>>, A, C, DC>>> {
+    /// Solves the given configuration and reports why it failed, if it did.
+    pub fn solve(&mut self, config: &CbsConfig<TS, S, A, C, DC, H>) -> SolveOutcome<S, A, C, DC> {
+        if let Some(agent) = self.get_disconnected_agent(config) {
+            return SolveOutcome::Disconnected(agent);
+        }
+
+        let deadline = config.time_limit.map(|limit| Instant::now() + limit);
+
+        // A seeded search runs on a single thread, so that its random numbers are drawn in the same order
+        #[cfg(feature = "rand")]
+        let n_threads = if config.rng.is_some() {
+            1
+        } else {
+            self.n_threads
+        };
+        #[cfg(not(feature = "rand"))]
+        let n_threads = self.n_threads;
+
         std::thread::scope(|s| {
-            for i in 0..self.n_threads {
+            for i in 0..n_threads {
                 let shared = &self.shared;
 
                 let mut lsipp =
@@ -218,7 +370,7 @@ where
 
                 s.spawn(move || {
                     loop {
-                        match Self::get_workload(shared) {
+                        match Self::get_workload(shared, deadline) {
                             WorkLoad::Complete => break,
                             WorkLoad::Starvation => continue,
                             WorkLoad::WorkItem { node } => {
@@ -243,11 +395,306 @@ where
             .iter()
             .map(|h| h.get_stats())
             .sum();
-        critical.best.as_ref().map(|n| {
-            n.get_solutions(config.n_agents)
+
+        let get_solutions = |node: &CbsNode<S, A, C, DC>| {
+            node.get_solutions(config.n_agents)
                 .iter()
                 .map(|sol| (*sol).clone())
                 .collect()
+        };
+
+        if let Some(best) = &critical.best {
+            SolveOutcome::Solved {
+                solutions: get_solutions(best),
+                maybe_suboptimal: critical.stats.evicted > 0,
+            }
+        } else if critical.timed_out {
+            SolveOutcome::TimedOut(critical.queue.first().map(|node| get_solutions(node)))
+        } else if critical.stats.evicted > 0 {
+            SolveOutcome::Truncated
+        } else {
+            SolveOutcome::Infeasible
+        }
+    }
+
+    /// Replans the given agent with a single low-level search while all the other agents keep following
+    /// the given conflict-free plan, which is cheaper than solving the whole configuration again after a local change.
+    /// The paths of the other agents are turned into constraints for the agent as they conflict with its path.
+    /// The pivots and the heuristics of the configuration are reused, only the task of the agent
+    /// or the transition system change. Returns None if the agent cannot reach its goal state
+    /// without conflicts at a cost that does not exceed the cost of its current solution.
+    pub fn reoptimize_agent(
+        &mut self,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        plan: &[SippSolution<S, A, C, DC>],
+        agent: usize,
+        new_task_or_costs: AgentUpdate<TS, S, C>,
+    ) -> Option<Vec<SippSolution<S, A, C, DC>>> {
+        // New costs are taken into account by a solver on the new transition system
+        let (task, solver) = match new_task_or_costs {
+            AgentUpdate::Task(task) => (task, None),
+            AgentUpdate::Costs(transition_system) => (
+                config.tasks[agent].clone(),
+                Some(Self::new(transition_system)),
+            ),
+        };
+        let shared = solver
+            .as_ref()
+            .map_or(&self.shared, |solver| &solver.shared);
+
+        // Transient agents no longer occupy their goal state after their arrival
+        let others = (0..plan.len())
+            .filter(|other| *other != agent)
+            .map(|other| {
+                let mut moves = plan[other].to_moves(other);
+                if config.tasks[other].transient {
+                    moves.pop();
+                }
+                moves
+            })
+            .collect::<Vec<_>>();
+
+        let mut added: Vec<Arc<Constraint<S, C>>> = vec![];
+        let mut lsipp =
+            SafeIntervalPathPlanningWithLandmarks::new(shared.transition_system.clone());
+        loop {
+            let mut constraints = config.get_reservations(agent);
+            for constraint in &added {
+                constraints.add(constraint);
+            }
+            constraints.unify();
+            let lsipp_config = LSippConfig::new_with_pivots(
+                task.clone(),
+                Arc::new(constraints),
+                LandmarkSet::default(),
+                config.pivots.clone(),
+                config.heuristic_to_pivots.clone(),
+                config.precision,
+            );
+            let solution = Self::run_low_level(shared, agent, &lsipp_config, &mut lsipp)?;
+            if solution.cost > plan[agent].cost {
+                return None;
+            }
+
+            let mut own = solution.to_moves(agent);
+            if task.transient {
+                own.pop();
+            }
+            let conflict = others.iter().find_map(|other| {
+                find_conflict_indices(&*shared.transition_system, T2(&own, other), DC::default())
+                    .map(|(i, j)| T2(own[i].clone(), other[j].clone()))
+            });
+
+            match conflict {
+                Some(moves) => {
+                    let constraint = Self::get_constraints(shared, config, &moves).0;
+                    added.push(Arc::new(constraint));
+                }
+                None => {
+                    let mut plan = plan.to_vec();
+                    plan[agent] = solution;
+                    return Some(plan);
+                }
+            }
+        }
+    }
+
+    /// Replans all the agents from their current execution states, e.g. during the execution
+    /// of a previous plan. Each agent starts at the given state and time, and keeps its goal state.
+    /// An agent partway along an edge commits to the next state of the edge, which it reaches
+    /// after its residual travel time. The next state and the edge in both directions are reserved
+    /// for it until then. The tasks and the reservations of the given configuration are restored afterwards.
+    pub fn replan_from(
+        &mut self,
+        current: &[ExecutionState<S, C, DC>],
+        config: &mut CbsConfig<TS, S, A, C, DC, H>,
+    ) -> SolveOutcome<S, A, C, DC> {
+        let tasks = config.tasks.clone();
+        let reservations = config.reservations.clone();
+        let reserved_actions = config.reserved_actions.clone();
+
+        for (agent, current) in current.iter().enumerate() {
+            let task = &config.tasks[agent];
+            let arrival = current.time + current.residual;
+            config.tasks[agent] = Arc::new(Task {
+                initial_state: current.state.clone(),
+                goal_state: task.goal_state.clone(),
+                initial_cost: arrival,
+                transient: task.transient,
+                deadline: task.deadline,
+                no_initial_wait: task.no_initial_wait,
+                goal_predicate: task.goal_predicate.clone(),
+            });
+
+            if arrival > current.time {
+                let interval = Interval::new(current.time, arrival);
+                config
+                    .reservations
+                    .push((current.state.clone(), interval, Some(agent)));
+                if current.from != current.state {
+                    config.reserved_actions.push((
+                        current.from.clone(),
+                        current.state.clone(),
+                        interval,
+                        agent,
+                    ));
+                    config.reserved_actions.push((
+                        current.state.clone(),
+                        current.from.clone(),
+                        interval,
+                        agent,
+                    ));
+                }
+            }
+        }
+
+        let outcome = self.solve(config);
+        config.tasks = tasks;
+        config.reservations = reservations;
+        config.reserved_actions = reserved_actions;
+
+        outcome
+    }
+
+    /// Explains why the given configuration is infeasible, by returning a minimal subset of its
+    /// reservations whose removal makes it feasible. Returns `None` if the configuration is not
+    /// proven infeasible, or if it remains infeasible without any reservation.
+    /// The subset is found by a deletion-based search that solves the configuration once per reservation:
+    /// starting without any reservation, each one is restored unless the configuration becomes infeasible,
+    /// in which case it belongs to the subset. Restoring any reservation of the subset alone
+    /// then makes the configuration infeasible again.
+    pub fn explain_infeasibility(
+        &mut self,
+        config: &mut CbsConfig<TS, S, A, C, DC, H>,
+    ) -> Option<Vec<(S, Interval<C>)>> {
+        if !matches!(self.solve(config), SolveOutcome::Infeasible) {
+            return None;
+        }
+
+        let reservations = std::mem::take(&mut config.reservations);
+        let mut certificate = vec![];
+
+        let feasible = matches!(self.solve(config), SolveOutcome::Solved { .. });
+        if feasible {
+            for reservation in &reservations {
+                config.reservations.push(reservation.clone());
+                if !matches!(self.solve(config), SolveOutcome::Solved { .. }) {
+                    let (state, interval, _) = config.reservations.pop().unwrap();
+                    certificate.push((state, interval));
+                }
+            }
+        }
+
+        config.reservations = reservations;
+
+        feasible.then_some(certificate)
+    }
+
+    /// Anytime variant of the search, which returns a first solution quickly and then improves it.
+    /// The high-level nodes are expanded in a focal list: among the nodes whose cost is within
+    /// the current weight of the lowest cost in the open list, the one with the fewest conflicts
+    /// is expanded first. Each time a solution is found, the weight is lowered to the next one
+    /// of the schedule and the search continues on the same tree, looking for cheaper solutions only.
+    /// Returns the successive solutions with the weight that bounds their suboptimality,
+    /// which is 1.0 for the last solution if it has been proven optimal within the time limit.
+    pub fn solve_anytime(
+        &mut self,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        w0: f32,
+        schedule: &[f32],
+    ) -> Vec<(Vec<SippSolution<S, A, C, DC>>, f32)> {
+        let mut results = vec![];
+        if self.get_disconnected_agent(config).is_some() {
+            return results;
+        }
+
+        let deadline = config.time_limit.map(|limit| Instant::now() + limit);
+        let mut weights = schedule.iter().copied();
+        let mut weight = w0;
+        let mut incumbent: Option<DC> = None;
+
+        let mut lsipp =
+            SafeIntervalPathPlanningWithLandmarks::new(self.shared.transition_system.clone());
+        Self::init(&self.shared, config, &mut lsipp);
+
+        loop {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                self.shared.critical.lock().timed_out = true;
+                break;
+            }
+
+            let node = {
+                let mut critical = self.shared.critical.lock();
+
+                // Only cheaper solutions are looked for
+                if let Some(incumbent) = incumbent {
+                    while critical
+                        .queue
+                        .last()
+                        .is_some_and(|node| node.total_cost >= incumbent - config.precision)
+                    {
+                        critical.queue.pop_last();
+                    }
+                }
+
+                let bound = match critical.queue.first() {
+                    Some(node) => node.total_cost * weight,
+                    None => {
+                        // The tree is exhausted, the last solution is optimal
+                        if let Some((_, bound)) = results.last_mut() {
+                            *bound = 1.0;
+                        }
+                        break;
+                    }
+                };
+                critical.queue.pop_focal(bound).unwrap()
+            };
+
+            if node.conflicts.is_empty() {
+                incumbent = Some(node.total_cost);
+                results.push((
+                    node.get_solutions(config.n_agents)
+                        .iter()
+                        .map(|sol| (*sol).clone())
+                        .collect(),
+                    weight,
+                ));
+                self.shared.critical.lock().best = Some(node);
+
+                match weights.next() {
+                    Some(next) => weight = next,
+                    None => break,
+                }
+                continue;
+            }
+
+            self.shared.critical.lock().stats.expanded += 1;
+            Self::branch_on(&self.shared, config, node, &mut lsipp);
+        }
+
+        let mut critical = self.shared.critical.lock();
+        critical.stats.lsipp_stats += lsipp.get_stats();
+        critical.stats.rra_stats = config
+            .heuristic_to_pivots
+            .iter()
+            .map(|h| h.get_stats())
+            .sum();
+
+        results
+    }
+
+    /// Returns the first agent whose goal state is not reachable from its initial state,
+    /// if the connectivity check is enabled.
+    fn get_disconnected_agent(&self, config: &CbsConfig<TS, S, A, C, DC, H>) -> Option<usize> {
+        if !config.check_connectivity {
+            return None;
+        }
+
+        config.tasks.iter().position(|task| {
+            !self
+                .shared
+                .transition_system
+                .reachable(&task.initial_state, &task.goal_state)
         })
     }
 
@@ -262,7 +709,7 @@ where
             Self::init(&self.shared, config, &mut lsipp)
         }
 
-        match Self::get_workload(&self.shared) {
+        match Self::get_workload(&self.shared, None) {
             WorkLoad::WorkItem { node } => {
                 Self::branch_on(&self.shared, config, node.clone(), &mut lsipp);
                 self.shared.critical.lock().ongoing -= 1;
@@ -278,10 +725,20 @@ where
         }
     }
 
-    fn get_workload(shared: &Shared<TS, S, A, C, DC>) -> WorkLoad<S, A, C, DC> {
+    fn get_workload(
+        shared: &Shared<TS, S, A, C, DC>,
+        deadline: Option<Instant>,
+    ) -> WorkLoad<S, A, C, DC> {
         let mut critical = shared.critical.lock();
 
-        while let Some(Reverse(node)) = critical.queue.pop() {
+        // Stop the search, keeping the open list to report the most promising node
+        if critical.timed_out || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            critical.timed_out = true;
+            shared.monitor.notify_all();
+            return WorkLoad::Complete;
+        }
+
+        while let Some(node) = critical.queue.pop_first() {
             // Check if the node is still relevant
             if let Some(best) = &critical.best {
                 if node.total_cost >= best.total_cost {
@@ -311,49 +768,60 @@ where
         }
     }
 
-    /// Branches on the conflict with the highest priority, creating two successor nodes (if feasible).
+    /// Branches on the conflict with the highest priority, creating two successor nodes (if feasible),
+    /// or one for each agent involved in a capacity conflict.
     fn branch_on(
         shared: &Shared<TS, S, A, C, DC>,
         config: &CbsConfig<TS, S, A, C, DC, H>,
         node: Arc<CbsNode<S, A, C, DC>>,
-        lsipp: &mut SafeIntervalPathPlanningWithLandmarks<
-            TS,
-            S,
-            A,
-            C,
-            DC,
-            ReverseResumableAStar<TS, S, A, C, DC, H>,
-        >,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
     ) {
         // Find the conflict with the highest priority
-        let conflict = node.conflicts.iter().min().unwrap();
-
-        // Get the agents involved in the conflict
-        let agents = T2(conflict.moves.0.agent, conflict.moves.1.agent);
+        let conflict = if config.select_by_lower_bound_increase {
+            Self::select_conflict_by_increase(shared, config, &node, lsipp)
+        } else {
+            Self::select_conflict(config, &node.conflicts)
+        };
 
         // Get the current solutions
         let current_solutions = node.get_solutions(config.n_agents);
 
         // Create the successor nodes, the new constraints and compute the new solutions
-        let (mut successors, mut solutions, constraints) =
+        let (agents, mut successors, mut solutions, constraints) =
             Self::get_successors(shared, config, &node, conflict, lsipp);
 
+        // The agent whose conflicting move starts later usually has more slack
+        let later = if conflict.moves.0.interval.start >= conflict.moves.1.interval.start {
+            0
+        } else {
+            1
+        };
+
         let mut landmark_added = false;
         for (i, (successor, solution)) in successors.drain(..).zip(solutions.drain(..)).enumerate()
         {
             if let (Some(mut successor), Some(solution)) = (successor, solution) {
                 // Set the real parent of the successor node (minimal clone is used in plan_new_paths)
                 successor.parent = Some(node.clone());
+                successor.resolved = Some(conflict.clone());
 
                 // Update the total cost of the successor node
-                successor.total_cost =
-                    node.total_cost - (current_solutions[agents[i]].cost - solution.cost);
+                successor.total_cost = node.total_cost
+                    - (current_solutions[agents[i]].cost - solution.cost)
+                        * config.get_weight(agents[i]);
 
                 // Add the solution to the successor node
                 successor.solutions.push(solution);
 
+                // Explore the branch that constrains the later agent first among equal-cost nodes
+                successor.preferred = config.constrain_later_agent_first && i == later;
+
                 // Try to add a landmark to the successor node (given by the negative constraint of the other branch)
-                if let Some(other_constraint) = &constraints[1 - i] {
+                let other_constraint = match conflict.window {
+                    Some(_) => None,
+                    None => constraints[1 - i].as_ref(),
+                };
+                if let Some(other_constraint) = other_constraint {
                     if !landmark_added && other_constraint.type_ == ConstraintType::Action {
                         // Transform action constraint in two landmarks
                         let from = Constraint::new_state_constraint(
@@ -390,104 +858,268 @@ where
         }
     }
 
-    /// Computes the successor nodes, the new constraints and the new solutions for the given conflict.
+    /// Returns the conflict with the highest priority. If a random number generator is configured,
+    /// ties between conflicts with the same priority are broken randomly.
+    fn select_conflict<'a>(
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        conflicts: &'a [Arc<Conflict<S, A, C, DC>>],
+    ) -> &'a Arc<Conflict<S, A, C, DC>> {
+        let conflict = conflicts.iter().min().unwrap();
+
+        #[cfg(feature = "rand")]
+        if let Some(rng) = &config.rng {
+            use rand::seq::IteratorRandom;
+
+            return conflicts
+                .iter()
+                .filter(|c| *c == conflict)
+                .choose(&mut *rng.lock())
+                .unwrap();
+        }
+
+        #[cfg(not(feature = "rand"))]
+        let _ = config;
+
+        conflict
+    }
+
+    /// Returns the conflict of the given node that maximizes the minimum increase of the lower bound
+    /// among its children. A missing child counts as an infinite increase, and ties are broken
+    /// by the priority of the conflicts.
+    fn select_conflict_by_increase<'a>(
+        shared: &Shared<TS, S, A, C, DC>,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        node: &'a CbsNode<S, A, C, DC>,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
+    ) -> &'a Arc<Conflict<S, A, C, DC>> {
+        node.conflicts
+            .iter()
+            .map(|conflict| {
+                let increases = conflict.increases.unwrap_or_else(|| {
+                    Self::probe_increases(shared, config, node, conflict, lsipp)
+                });
+                let increase = match (increases.0, increases.1) {
+                    (Some(first), Some(second)) => Some(first.min(second)),
+                    (first, second) => first.or(second),
+                };
+                ((increase.is_none(), increase), conflict)
+            })
+            .max_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(a.1)))
+            .unwrap()
+            .1
+    }
+
+    /// Returns how much the lower bound of each child of the given node would increase
+    /// if the given conflict were split on, or None for a child that has no solution.
+    /// Only the children that constrain the agents of the conflicting moves are considered.
+    fn probe_increases(
+        shared: &Shared<TS, S, A, C, DC>,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        node: &CbsNode<S, A, C, DC>,
+        conflict: &Conflict<S, A, C, DC>,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
+    ) -> A2<Option<DC>> {
+        let agents = T2(conflict.moves.0.agent, conflict.moves.1.agent);
+        let solutions = node.get_solutions(config.n_agents);
+
+        let (_, _, new_solutions, _) = Self::get_successors(shared, config, node, conflict, lsipp);
+        let increase = |i: usize| {
+            new_solutions[i].as_ref().map(|solution| {
+                (solution.cost - solutions[agents[i]].cost) * config.get_weight(agents[i])
+            })
+        };
+
+        T2(increase(0), increase(1))
+    }
+
+    /// Computes the successor nodes, the new constraints and the new solutions for the given conflict,
+    /// along with the agent constrained in each successor. The first two successors constrain the agents
+    /// of the conflicting moves, and a capacity conflict has one more successor for each other agent
+    /// that enters the edge within its window.
     fn get_successors(
         shared: &Shared<TS, S, A, C, DC>,
         config: &CbsConfig<TS, S, A, C, DC, H>,
         node: &CbsNode<S, A, C, DC>,
         conflict: &Conflict<S, A, C, DC>,
-        lsipp: &mut SafeIntervalPathPlanningWithLandmarks<
-            TS,
-            S,
-            A,
-            C,
-            DC,
-            ReverseResumableAStar<TS, S, A, C, DC, H>,
-        >,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
     ) -> (
+        Vec<usize>,
         Vec<Option<CbsNode<S, A, C, DC>>>,
-        Vec<Option<Solution<Arc<SippState<S, C>>, A, C, DC>>>,
-        A2<Option<Arc<Constraint<S, C>>>>,
+        Vec<Option<SippSolution<S, A, C, DC>>>,
+        Vec<Option<Arc<Constraint<S, C>>>>,
     ) {
-        // Get the agents involved in the conflict
-        let agents = T2(conflict.moves.0.agent, conflict.moves.1.agent);
-
-        // Check if the agents are already frozen
-        let frozen = T2(
-            config.frozen.contains_key(&agents[0]),
-            config.frozen.contains_key(&agents[1]),
-        );
-
         // Get one constraint for each agent from the transition system to avoid the conflict
-        let constraints = match (frozen[0], frozen[1]) {
-            (true, true) => T2(None, None),
-            (false, true) => T2(
-                Some(Arc::new(
-                    Self::get_constraints(shared, config, &conflict.moves).0,
-                )),
-                None,
-            ),
-            (true, false) => T2(
-                None,
-                Some(Arc::new(
-                    Self::get_constraints(shared, config, &conflict.moves).1,
-                )),
-            ),
-            (false, false) => {
-                let constraints = Self::get_constraints(shared, config, &conflict.moves);
-                T2(Some(Arc::new(constraints.0)), Some(Arc::new(constraints.1)))
-            }
+        let new_constraints = match conflict.window {
+            Some(window) => Self::get_capacity_constraints(config, node, &conflict.moves, window),
+            None => match &config.time_bases {
+                Some((time_bases, tolerance)) => {
+                    // The moves only collide on the common timeline, where the constraints are computed
+                    let to_common = |m: &Move<S, A, C>| {
+                        let time_base = &time_bases[m.agent];
+                        let mut m = m.clone();
+                        m.interval = Interval::new(
+                            time_base.to_common(m.interval.start),
+                            time_base.to_common(m.interval.end),
+                        );
+                        m
+                    };
+                    let moves = T2(to_common(&conflict.moves.0), to_common(&conflict.moves.1));
+                    let constraints = Self::get_constraints(shared, config, &moves);
+                    vec![constraints.0, constraints.1]
+                        .into_iter()
+                        .map(|constraint| {
+                            Self::to_native_constraint(time_bases, *tolerance, constraint)
+                        })
+                        .collect()
+                }
+                None => {
+                    let constraints = Self::get_constraints(shared, config, &conflict.moves);
+                    vec![constraints.0, constraints.1]
+                }
+            },
         };
 
+        // Frozen agents keep their paths, so they are not constrained
+        let agents = new_constraints
+            .iter()
+            .map(|constraint| constraint.agent)
+            .collect::<Vec<_>>();
+        let constraints = new_constraints
+            .into_iter()
+            .map(|constraint| {
+                (!config.frozen.contains_key(&constraint.agent)).then(|| Arc::new(constraint))
+            })
+            .collect::<Vec<_>>();
+
         // Get a minimal clone of the current node to allow retrieving the constraints in the successor nodes
         // without needing to store the current node in an Arc
         let minimal_clone = Arc::new(node.get_minimal_clone());
 
         // Create a successor nodes for each new constraint
-        let successors = vec![
-            constraints[0]
-                .as_ref()
-                .map(|c| CbsNode::new(minimal_clone.clone(), c.clone())),
-            constraints[1]
-                .as_ref()
-                .map(|c| CbsNode::new(minimal_clone, c.clone())),
-        ];
+        let successors = constraints
+            .iter()
+            .map(|constraint| {
+                constraint
+                    .as_ref()
+                    .map(|c| CbsNode::new(minimal_clone.clone(), c.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        // Compute a new path for each agent, taking into account all its constraints
+        let solutions = successors
+            .iter()
+            .zip(agents.iter())
+            .map(|(successor, &agent)| {
+                successor.as_ref().and_then(|succ| {
+                    let constraint_set = if config.memoize_constraints {
+                        succ.get_constraints_memoized(agent, config.get_reservations(agent))
+                    } else {
+                        succ.get_constraints_from(agent, config.get_reservations(agent))
+                    };
+                    Self::solve_low_level(shared, config, agent, constraint_set, lsipp)
+                })
+            })
+            .collect();
 
-        // Get all the constraints for each agent
-        let constraint_sets = (
-            successors[0]
-                .as_ref()
-                .map(|succ| succ.get_constraints(agents[0])),
-            successors[1]
-                .as_ref()
-                .map(|succ| succ.get_constraints(agents[1])),
+        (agents, successors, solutions, constraints)
+    }
+
+    /// Converts the given constraint on the common timeline into the time base of its agent,
+    /// extending it by the tolerance so that a conflict detected up to the tolerance is resolved.
+    fn to_native_constraint(
+        time_bases: &[Arc<dyn TimeBase<C, C> + Send + Sync>],
+        tolerance: DC,
+        mut constraint: Constraint<S, C>,
+    ) -> Constraint<S, C> {
+        let time_base = &time_bases[constraint.agent];
+        constraint.interval.start = time_base.to_native(constraint.interval.start);
+        if constraint.interval.end < C::max_value() {
+            constraint.interval.end = time_base.to_native(constraint.interval.end + tolerance);
+        }
+        constraint
+    }
+
+    /// Computes the path of the given agent under the given constraints and landmarks.
+    /// If enabled, the path is reused when the same subproblem has already been solved.
+    fn solve_low_level(
+        shared: &Shared<TS, S, A, C, DC>,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        agent: usize,
+        (constraints, landmarks): (Arc<ConstraintSet<S, C>>, LandmarkSet<S, C>),
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
+    ) -> Option<SippSolution<S, A, C, DC>> {
+        let key = config.cache_solutions.then(|| {
+            let landmarks = landmarks.iter().fold(0u64, |hash, landmark| {
+                hash.wrapping_add(landmark.fingerprint())
+            });
+            (agent, constraints.fingerprint(), landmarks)
+        });
+
+        if let Some(key) = &key {
+            let cached = shared.solution_cache.lock().get(key).and_then(|entries| {
+                entries
+                    .iter()
+                    .find(|(other_constraints, other_landmarks, _)| {
+                        constraints.is_identical(other_constraints)
+                            && identical_constraints(&landmarks, other_landmarks)
+                    })
+                    .map(|(_, _, solution)| solution.clone())
+            });
+            if let Some(solution) = cached {
+                shared.critical.lock().stats.cache_hits += 1;
+                return solution;
+            }
+        }
+
+        let subproblem = key
+            .as_ref()
+            .map(|_| (constraints.clone(), landmarks.clone()));
+        let lsipp_config = LSippConfig::new_with_pivots(
+            config.tasks[agent].clone(),
+            constraints,
+            landmarks,
+            config.pivots.clone(),
+            config.heuristic_to_pivots.clone(),
+            config.precision,
         );
+        let solution = Self::run_low_level(shared, agent, &lsipp_config, lsipp);
+
+        if let (Some(key), Some((constraints, landmarks))) = (key, subproblem) {
+            shared.solution_cache.lock().entry(key).or_default().push((
+                constraints,
+                landmarks,
+                solution.clone(),
+            ));
+        }
 
-        // Compute a new path for each agent, taking into account the new constraint
-        let solutions = vec![
-            constraint_sets.0.and_then(|cs| {
-                lsipp.solve(&LSippConfig::new_with_pivots(
-                    config.tasks[agents[0]].clone(),
-                    cs.0.clone(),
-                    cs.1,
-                    config.pivots.clone(),
-                    config.heuristic_to_pivots.clone(),
-                    config.precision,
-                ))
-            }),
-            constraint_sets.1.and_then(|cs| {
-                lsipp.solve(&LSippConfig::new_with_pivots(
-                    config.tasks[agents[1]].clone(),
-                    cs.0,
-                    cs.1,
-                    config.pivots.clone(),
-                    config.heuristic_to_pivots.clone(),
-                    config.precision,
-                ))
-            }),
-        ];
+        solution
+    }
+
+    /// Runs the low-level search of the given agent, and records its statistics for that agent.
+    fn run_low_level(
+        shared: &Shared<TS, S, A, C, DC>,
+        agent: usize,
+        config: &LowLevelConfig<TS, S, A, C, DC, H>,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
+    ) -> Option<SippSolution<S, A, C, DC>> {
+        let before = lsipp.get_stats();
+        let start = Instant::now();
+        let solution = lsipp.solve(config);
+        let elapsed = start.elapsed();
+        let after = lsipp.get_stats();
+
+        let mut critical = shared.critical.lock();
+        if critical.agent_stats.len() <= agent {
+            critical
+                .agent_stats
+                .resize(agent + 1, AgentStats::default());
+        }
+        let stats = &mut critical.agent_stats[agent];
+        stats.searches += after.searches - before.searches;
+        stats.expanded += after.sipp_stats.expanded - before.sipp_stats.expanded;
+        stats.time += elapsed;
 
-        (successors, solutions, constraints)
+        solution
     }
 
     /// Returns a constraint that ensures that the first move will not collide with the second move anymore, and vice-versa.
@@ -600,6 +1232,44 @@ where
         }
     }
 
+    /// Returns the constraints that resolve a capacity conflict on an edge, one for each agent that
+    /// enters the edge within the window of the first move, starting with the agents of the conflicting moves.
+    /// At least one of these agents must not enter the edge during that window, so each constraint
+    /// forbids one of them from doing so.
+    fn get_capacity_constraints(
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        node: &CbsNode<S, A, C, DC>,
+        moves: &A2<Move<S, A, C>>,
+        window: DC,
+    ) -> Vec<Constraint<S, C>> {
+        let (first, last) = (&moves[0], &moves[1]);
+        let interval = Interval::new(
+            first.interval.start - config.precision,
+            first.interval.start + window + config.precision,
+        );
+
+        let solutions = node.get_solutions(config.n_agents);
+        let edge = (first.from.clone(), first.to.clone());
+        let usages = Self::get_capacity_usages(config, &solutions);
+
+        let mut agents = vec![first.agent, last.agent];
+        for m in usages[&edge].iter() {
+            if m.interval.start > first.interval.start
+                && m.interval.start < last.interval.start
+                && !agents.contains(&m.agent)
+            {
+                agents.push(m.agent);
+            }
+        }
+
+        agents
+            .into_iter()
+            .map(|agent| {
+                Constraint::new_action_constraint(agent, edge.0.clone(), edge.1.clone(), interval)
+            })
+            .collect()
+    }
+
     fn earliest_non_colliding_time(
         shared: &Shared<TS, S, A, C, DC>,
         config: &CbsConfig<TS, S, A, C, DC, H>,
@@ -634,26 +1304,47 @@ where
         shared: &Shared<TS, S, A, C, DC>,
         config: &CbsConfig<TS, S, A, C, DC, H>,
         node: &mut CbsNode<S, A, C, DC>,
-        lsipp: &mut SafeIntervalPathPlanningWithLandmarks<
-            TS,
-            S,
-            A,
-            C,
-            DC,
-            ReverseResumableAStar<TS, S, A, C, DC, H>,
-        >,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
     ) -> bool {
+        // Only the agent constrained in the node has a new path
+        let parent_conflicts = node.parent.as_ref().map(|parent| {
+            (
+                parent.conflicts.as_slice(),
+                node.constraint.as_ref().unwrap().agent,
+            )
+        });
+
+        if let Some(conflicts) =
+            Self::detect_conflicts(shared, config, node, parent_conflicts, lsipp)
+        {
+            node.conflicts = conflicts;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Detects the conflicts between the solutions of the given node, or returns None if
+    /// any of them cannot be avoided. If the conflicts of the parent node are given, along with
+    /// the agent that was replanned, they are reused for the pairs of agents whose solutions
+    /// did not change, and only the pairs involving the replanned agent are recomputed.
+    fn detect_conflicts(
+        shared: &Shared<TS, S, A, C, DC>,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        node: &CbsNode<S, A, C, DC>,
+        parent_conflicts: Option<(&[Arc<Conflict<S, A, C, DC>>], usize)>,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
+    ) -> Option<Vec<Arc<Conflict<S, A, C, DC>>>> {
         let solutions = node.get_solutions(config.n_agents);
 
         let mut conflicts = vec![];
-        if let Some(parent) = &node.parent {
-            let agent = node.constraint.as_ref().unwrap().agent;
-
+        if let Some((parent_conflicts, agent)) = parent_conflicts {
             // Get conflicts from the parent node that do not involve the given agent
-            parent
-                .conflicts
+            parent_conflicts
                 .iter()
-                .filter(|c| c.moves.0.agent != agent && c.moves.1.agent != agent)
+                .filter(|c| {
+                    c.window.is_none() && c.moves.0.agent != agent && c.moves.1.agent != agent
+                })
                 .for_each(|c| {
                     conflicts.push(c.clone());
                 });
@@ -668,20 +1359,20 @@ where
                     Self::get_conflict(shared, config, node, &solutions, T2(agent, other), lsipp)
                 {
                     if !avoidable {
-                        return false;
+                        return None;
                     }
                     conflicts.push(Arc::new(conflict));
                 }
             }
         } else {
-            // Root node, compute conflicts between each pair of solutions
+            // Compute conflicts between each pair of solutions
             for i in 0..config.n_agents {
                 for j in i + 1..config.n_agents {
                     if let Some((conflict, avoidable)) =
                         Self::get_conflict(shared, config, node, &solutions, T2(i, j), lsipp)
                     {
                         if !avoidable {
-                            return false;
+                            return None;
                         }
                         conflicts.push(Arc::new(conflict));
                     }
@@ -689,9 +1380,14 @@ where
             }
         }
 
-        node.conflicts = conflicts;
+        // Capacity conflicts may involve any agent, so they are always recomputed
+        conflicts.extend(
+            Self::get_capacity_conflicts(config, &solutions)
+                .into_iter()
+                .map(Arc::new),
+        );
 
-        true
+        Some(conflicts)
     }
 
     /// Returns the first conflict between the given solutions, if any, and whether it can be avoided.
@@ -699,17 +1395,19 @@ where
         shared: &Shared<TS, S, A, C, DC>,
         config: &CbsConfig<TS, S, A, C, DC, H>,
         node: &CbsNode<S, A, C, DC>,
-        solutions: &[&Solution<Arc<SippState<S, C>>, A, C, DC>],
+        solutions: &[&SippSolution<S, A, C, DC>],
         agents: A2<usize>,
-        lsipp: &mut SafeIntervalPathPlanningWithLandmarks<
-            TS,
-            S,
-            A,
-            C,
-            DC,
-            ReverseResumableAStar<TS, S, A, C, DC, H>,
-        >,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
     ) -> Option<(Conflict<S, A, C, DC>, bool)> {
+        if let Some((time_bases, tolerance)) = &config.time_bases {
+            return Self::get_conflict_on_common_timeline(
+                shared, config, solutions, agents, time_bases, *tolerance,
+            )
+            .map(|conflict| {
+                Self::classify_conflict(shared, config, node, solutions, conflict, lsipp)
+            });
+        }
+
         let mut conflict = None;
 
         // Iterate through both solutions and find moves overlapping in C
@@ -726,8 +1424,13 @@ where
                 };
             }
 
+            // Transient agents no longer occupy their goal state after their arrival
+            let gone = (0..=1).any(|k| {
+                index[k] == solutions[agents[k]].actions.len() && config.tasks[agents[k]].transient
+            });
+
             // Check if the intervals overlap
-            if intervals[0].overlaps(&intervals[1]) {
+            if !gone && intervals[0].overlaps(&intervals[1]) {
                 // Check if the moves lead to a conflict
                 let moves = T2(
                     Move::new(
@@ -792,53 +1495,522 @@ where
             }
         }
 
-        if let Some(mut conflict) = conflict {
-            // Determine conflict type by trying to avoid it
-            let (_, new_solutions, _) =
-                Self::get_successors(shared, config, node, &conflict, lsipp);
+        conflict.map(|conflict| {
+            Self::classify_conflict(shared, config, node, solutions, conflict, lsipp)
+        })
+    }
 
-            if let (None, None) = (&new_solutions[0], &new_solutions[1]) {
-                return Some((conflict, false));
-            } else if let (Some(solution), None) = (&new_solutions[0], &new_solutions[1]) {
-                conflict.overcost = solution.cost - solutions[agents[0]].cost;
-                if config.frozen.contains_key(&agents[1]) {
-                    conflict.type_ = ConflictType::Frozen;
-                } else {
-                    conflict.type_ = ConflictType::Cardinal;
-                }
-            } else if let (None, Some(solution)) = (&new_solutions[0], &new_solutions[1]) {
-                conflict.overcost = solution.cost - solutions[agents[1]].cost;
-                if config.frozen.contains_key(&agents[0]) {
-                    conflict.type_ = ConflictType::Frozen;
-                } else {
-                    conflict.type_ = ConflictType::Cardinal;
-                }
-            } else if let (Some(solution1), Some(solution2)) =
-                (&new_solutions[0], &new_solutions[1])
-            {
-                let overcost1 = solution1.cost - solutions[agents[0]].cost;
-                let overcost2 = solution2.cost - solutions[agents[1]].cost;
-                if overcost1 > DC::default() && overcost2 > DC::default() {
-                    conflict.overcost = overcost1.min(overcost2);
-                    conflict.type_ = ConflictType::Cardinal;
-                } else if overcost1 > DC::default() || overcost2 > DC::default() {
-                    conflict.overcost = overcost1.max(overcost2);
-                    conflict.type_ = ConflictType::SemiCardinal;
-                } else {
-                    conflict.type_ = ConflictType::NonCardinal;
+    /// Returns the first conflict between the given solutions once converted into the common timeline,
+    /// whose moves are given in the time bases of the agents.
+    fn get_conflict_on_common_timeline(
+        shared: &Shared<TS, S, A, C, DC>,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        solutions: &[&SippSolution<S, A, C, DC>],
+        agents: A2<usize>,
+        time_bases: &[Arc<dyn TimeBase<C, C> + Send + Sync>],
+        tolerance: DC,
+    ) -> Option<Conflict<S, A, C, DC>> {
+        let get_moves = |agent: usize, time_base: &(dyn TimeBase<C, C> + Send + Sync)| {
+            let mut moves = solutions[agent].to_common_moves(agent, time_base);
+
+            // Transient agents no longer occupy their goal state after their arrival
+            if config.tasks[agent].transient {
+                moves.pop();
+            }
+            moves
+        };
+
+        let common = T2(
+            get_moves(agents[0], time_bases[agents[0]].as_ref()),
+            get_moves(agents[1], time_bases[agents[1]].as_ref()),
+        );
+        let (i, j) = find_conflict_indices(
+            shared.transition_system.as_ref(),
+            T2(&common.0, &common.1),
+            tolerance,
+        )?;
+
+        // Convert the conflicting moves back into the time bases of the agents
+        let native = |agent: usize, mut m: Move<S, A, C>| {
+            m.interval = Interval::new(
+                time_bases[agent].to_native(m.interval.start),
+                time_bases[agent].to_native(m.interval.end),
+            );
+            m
+        };
+
+        Some(Conflict::new(T2(
+            native(agents[0], common.0[i].clone()),
+            native(agents[1], common.1[j].clone()),
+        )))
+    }
+
+    /// Returns the first conflict on each edge whose capacity is exceeded by the given solutions,
+    /// between the first and the last of the moves that enter it within its window.
+    /// These conflicts are not classified, as they are only resolved once the other ones are.
+    fn get_capacity_conflicts(
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        solutions: &[&SippSolution<S, A, C, DC>],
+    ) -> Vec<Conflict<S, A, C, DC>> {
+        let mut conflicts = vec![];
+        for (edge, moves) in Self::get_capacity_usages(config, solutions) {
+            let (capacity, window) = config.edge_capacities[&edge];
+
+            // Find the first window that is entered by more moves than the capacity
+            let exceeded = (0..moves.len().saturating_sub(capacity)).find(|&i| {
+                moves[i].agent != moves[i + capacity].agent
+                    && moves[i].interval.start + window > moves[i + capacity].interval.start
+            });
+
+            if let Some(i) = exceeded {
+                conflicts.push(Conflict::new_capacity(
+                    T2(moves[i].clone(), moves[i + capacity].clone()),
+                    window,
+                ));
+            }
+        }
+
+        conflicts
+    }
+
+    /// Returns the moves of the given solutions through each edge with a limited capacity,
+    /// sorted by their start time.
+    fn get_capacity_usages(
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        solutions: &[&SippSolution<S, A, C, DC>],
+    ) -> FxHashMap<(S, S), Vec<Move<S, A, C>>> {
+        let mut usages: FxHashMap<(S, S), Vec<Move<S, A, C>>> = FxHashMap::default();
+        if config.edge_capacities.is_empty() {
+            return usages;
+        }
+
+        for (agent, solution) in solutions.iter().enumerate() {
+            for (i, action) in solution.actions.iter().enumerate() {
+                let edge = (
+                    solution.steps[i].0.internal_state.clone(),
+                    solution.steps[i + 1].0.internal_state.clone(),
+                );
+                if action.action.is_none() || !config.edge_capacities.contains_key(&edge) {
+                    continue;
                 }
+                usages.entry(edge.clone()).or_default().push(Move::new(
+                    agent,
+                    edge.0,
+                    edge.1,
+                    action.action,
+                    Interval::new(solution.steps[i].1, solution.steps[i + 1].1),
+                ));
+            }
+        }
+
+        for moves in usages.values_mut() {
+            moves.sort_by_key(|m| m.interval.start);
+        }
+
+        usages
+    }
+
+    /// Classifies the given conflict by trying to avoid it, and returns whether it can be avoided.
+    fn classify_conflict(
+        shared: &Shared<TS, S, A, C, DC>,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        node: &CbsNode<S, A, C, DC>,
+        solutions: &[&SippSolution<S, A, C, DC>],
+        mut conflict: Conflict<S, A, C, DC>,
+        lsipp: &mut LowLevelSearch<TS, S, A, C, DC, H>,
+    ) -> (Conflict<S, A, C, DC>, bool) {
+        let agents = T2(conflict.moves.0.agent, conflict.moves.1.agent);
+
+        // The diagrams describe the optimal paths of the agents that have not been constrained yet
+        if config.classify_with_mdds
+            && conflict.window.is_none()
+            && config.reservations.is_empty()
+            && config.reserved_actions.is_empty()
+            && (0..2).all(|k| {
+                !config.frozen.contains_key(&agents[k])
+                    && !config.tasks[agents[k]].transient
+                    && !node.is_constrained(agents[k])
+            })
+        {
+            let mdds = T2(
+                Self::get_mdd(shared, config, agents[0], solutions[agents[0]]),
+                Self::get_mdd(shared, config, agents[1], solutions[agents[1]]),
+            );
+            conflict.type_ = Mdd::classify((&mdds.0, &mdds.1), &conflict);
+            return (conflict, true);
+        }
+
+        // Determine conflict type by trying to avoid it
+        let (_, _, new_solutions, _) = Self::get_successors(shared, config, node, &conflict, lsipp);
+
+        // Record the increase of the lower bound of each child for the selection of the conflicts
+        let increase = |i: usize| {
+            new_solutions[i].as_ref().map(|solution| {
+                (solution.cost - solutions[agents[i]].cost) * config.get_weight(agents[i])
+            })
+        };
+        conflict.increases = Some(T2(increase(0), increase(1)));
+
+        if let (None, None) = (&new_solutions[0], &new_solutions[1]) {
+            return (conflict, false);
+        } else if let (Some(solution), None) = (&new_solutions[0], &new_solutions[1]) {
+            conflict.overcost = solution.cost - solutions[agents[0]].cost;
+            if config.frozen.contains_key(&agents[1]) {
+                conflict.type_ = ConflictType::Frozen;
+            } else {
+                conflict.type_ = ConflictType::Cardinal;
+            }
+        } else if let (None, Some(solution)) = (&new_solutions[0], &new_solutions[1]) {
+            conflict.overcost = solution.cost - solutions[agents[1]].cost;
+            if config.frozen.contains_key(&agents[0]) {
+                conflict.type_ = ConflictType::Frozen;
+            } else {
+                conflict.type_ = ConflictType::Cardinal;
             }
+        } else if let (Some(solution1), Some(solution2)) = (&new_solutions[0], &new_solutions[1]) {
+            let overcost1 = solution1.cost - solutions[agents[0]].cost;
+            let overcost2 = solution2.cost - solutions[agents[1]].cost;
+            if overcost1 > DC::default() && overcost2 > DC::default() {
+                conflict.overcost = overcost1.min(overcost2);
+                conflict.type_ = ConflictType::Cardinal;
+            } else if overcost1 > DC::default() || overcost2 > DC::default() {
+                conflict.overcost = overcost1.max(overcost2);
+                conflict.type_ = ConflictType::SemiCardinal;
+            } else {
+                conflict.type_ = ConflictType::NonCardinal;
+            }
+        }
+
+        // An agent that stays at its goal must vacate it to let the other agent pass,
+        // which takes precedence over the conflicts that delay a single agent
+        if conflict.is_goal_blocking() && conflict.type_ > ConflictType::GoalBlocking {
+            conflict.type_ = ConflictType::GoalBlocking;
+        }
+
+        (conflict, true)
+    }
+
+    /// Returns the diagram of the optimal paths of the given agent that reach its goal when its given
+    /// unconstrained solution does, which is built once per search.
+    fn get_mdd(
+        shared: &Shared<TS, S, A, C, DC>,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        agent: usize,
+        solution: &SippSolution<S, A, C, DC>,
+    ) -> Arc<Mdd<S, C>> {
+        shared
+            .mdds
+            .lock()
+            .entry(agent)
+            .or_insert_with(|| {
+                Arc::new(Mdd::new(
+                    shared.transition_system.as_ref(),
+                    &config.tasks[agent],
+                    solution.steps.last().unwrap().1,
+                ))
+            })
+            .clone()
+    }
+
+    /// Returns how much the lower bound of each child of the given node would increase
+    /// if the given conflict were split on, or None for a child that has no solution.
+    /// The increases recorded when the conflict was classified are reused.
+    pub fn get_lower_bound_increases(
+        &mut self,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        node: &CbsNode<S, A, C, DC>,
+        conflict: &Conflict<S, A, C, DC>,
+    ) -> A2<Option<DC>> {
+        if let Some(increases) = conflict.increases {
+            return increases;
+        }
+
+        let mut lsipp =
+            SafeIntervalPathPlanningWithLandmarks::new(self.shared.transition_system.clone());
+        Self::probe_increases(&self.shared, config, node, conflict, &mut lsipp)
+    }
 
-            return Some((conflict, true));
+    /// Returns the conflict of the given node that maximizes the minimum increase of the lower bound
+    /// among its children, if the node has any conflict.
+    pub fn select_conflict_by_lower_bound_increase<'a>(
+        &mut self,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        node: &'a CbsNode<S, A, C, DC>,
+    ) -> Option<&'a Arc<Conflict<S, A, C, DC>>> {
+        if node.conflicts.is_empty() {
+            return None;
         }
 
-        None
+        let mut lsipp =
+            SafeIntervalPathPlanningWithLandmarks::new(self.shared.transition_system.clone());
+        Some(Self::select_conflict_by_increase(
+            &self.shared,
+            config,
+            node,
+            &mut lsipp,
+        ))
+    }
+
+    /// Builds the diagram of the paths of the given agent that reach its goal at the given time,
+    /// which can be used to classify the conflicts without replanning.
+    pub fn build_mdd(
+        &self,
+        config: &CbsConfig<TS, S, A, C, DC, H>,
+        agent: usize,
+        cost: C,
+    ) -> Mdd<S, C> {
+        Mdd::new(
+            self.shared.transition_system.as_ref(),
+            &config.tasks[agent],
+            cost,
+        )
+    }
+
+    /// Sets the number of threads that expand the nodes of the search tree, all the available ones by default.
+    pub fn set_n_threads(&mut self, n_threads: usize) {
+        self.n_threads = n_threads.max(1);
     }
 
     /// Returns the statistics of the search algorithm.
     pub fn get_stats(&mut self) -> CbsStats {
         self.shared.critical.lock().stats
     }
+
+    /// Returns the statistics of the low-level searches of each agent.
+    pub fn get_agent_stats(&mut self) -> Vec<AgentStats> {
+        self.shared.critical.lock().agent_stats.clone()
+    }
+
+    /// Returns how each conflict was resolved along the branch of the last solution found,
+    /// from the root to the solution, or None if no solution has been found.
+    pub fn get_resolutions(&mut self) -> Option<Vec<Resolution<S, A, C, DC>>> {
+        let critical = self.shared.critical.lock();
+        critical.best.as_ref().map(|best| best.get_resolutions())
+    }
+
+    /// Extracts the state of a search paused between two calls to `solve_iter`,
+    /// i.e. the open list, the nodes leading to it, the best node found so far and the statistics.
+    pub fn checkpoint(&self) -> CbsCheckpoint<S, A, C, DC> {
+        let critical = self.shared.critical.lock();
+
+        let mut nodes = vec![];
+        let mut indices = FxHashMap::default();
+        let open = critical
+            .queue
+            .nodes
+            .iter()
+            .map(|(node, counter)| (Self::record_node(node, &mut indices, &mut nodes), *counter))
+            .collect();
+        let best = critical
+            .best
+            .as_ref()
+            .map(|best| Self::record_node(best, &mut indices, &mut nodes));
+
+        CbsCheckpoint {
+            nodes,
+            open,
+            counter: critical.queue.counter,
+            best,
+            stats: critical.stats,
+            agent_stats: critical.agent_stats.clone(),
+        }
+    }
+
+    /// Creates a solver that resumes the search saved in the given checkpoint with `solve_iter`.
+    /// The checkpoint must have been extracted from a search with the same transition system and configuration.
+    pub fn from_checkpoint(
+        transition_system: Arc<TS>,
+        checkpoint: CbsCheckpoint<S, A, C, DC>,
+    ) -> Self {
+        let solver = Self::new(transition_system);
+
+        let mut nodes: Vec<Arc<CbsNode<S, A, C, DC>>> = Vec::with_capacity(checkpoint.nodes.len());
+        for record in checkpoint.nodes {
+            let parent = record.parent.map(|parent| nodes[parent].clone());
+            nodes.push(Arc::new(Self::restore_node(record, parent)));
+        }
+
+        {
+            let mut critical = solver.shared.critical.lock();
+            for (index, counter) in checkpoint.open {
+                critical.queue.nodes.insert((nodes[index].clone(), counter));
+            }
+            critical.queue.counter = checkpoint.counter;
+            critical.best = checkpoint.best.map(|best| nodes[best].clone());
+            critical.stats = checkpoint.stats;
+            critical.agent_stats = checkpoint.agent_stats;
+        }
+
+        solver
+    }
+
+    /// Records the given node and its ancestors that have not been recorded yet, parents first,
+    /// and returns the index of the node.
+    fn record_node(
+        node: &Arc<CbsNode<S, A, C, DC>>,
+        indices: &mut FxHashMap<*const CbsNode<S, A, C, DC>, usize>,
+        nodes: &mut Vec<NodeRecord<S, A, C, DC>>,
+    ) -> usize {
+        // Walk up the tree until an ancestor that has already been recorded
+        let mut branch = vec![];
+        let mut current = Some(node);
+        while let Some(node) = current {
+            if indices.contains_key(&Arc::as_ptr(node)) {
+                break;
+            }
+            branch.push(node);
+            current = node.parent.as_ref();
+        }
+
+        for node in branch.into_iter().rev() {
+            let to_owned = |solution: &SippSolution<S, A, C, DC>| Solution {
+                cost: solution.cost,
+                steps: solution
+                    .steps
+                    .iter()
+                    .map(|(state, time)| ((**state).clone(), *time))
+                    .collect(),
+                actions: solution.actions.clone(),
+            };
+
+            indices.insert(Arc::as_ptr(node), nodes.len());
+            nodes.push(NodeRecord {
+                parent: node
+                    .parent
+                    .as_ref()
+                    .map(|parent| indices[&Arc::as_ptr(parent)]),
+                total_cost: node.total_cost,
+                solutions: node.solutions.iter().map(to_owned).collect(),
+                conflicts: node.conflicts.iter().map(|c| c.as_ref().into()).collect(),
+                constraint: node.constraint.as_ref().map(|c| (**c).clone()),
+                resolved: node.resolved.as_ref().map(|c| c.as_ref().into()),
+                landmark: node
+                    .landmark
+                    .as_ref()
+                    .map(|T2(from, to)| ((**from).clone(), (**to).clone())),
+                preferred: node.preferred,
+                depth: node.depth,
+            });
+        }
+
+        indices[&Arc::as_ptr(node)]
+    }
+
+    fn restore_node(
+        record: NodeRecord<S, A, C, DC>,
+        parent: Option<Arc<CbsNode<S, A, C, DC>>>,
+    ) -> CbsNode<S, A, C, DC> {
+        let to_shared = |solution: Solution<SippState<S, C>, A, C, DC>| Solution {
+            cost: solution.cost,
+            steps: solution
+                .steps
+                .into_iter()
+                .map(|(state, time)| (Arc::new(state), time))
+                .collect(),
+            actions: solution.actions,
+        };
+
+        CbsNode {
+            total_cost: record.total_cost,
+            parent,
+            solutions: record.solutions.into_iter().map(to_shared).collect(),
+            conflicts: record
+                .conflicts
+                .into_iter()
+                .map(|c| Arc::new(c.into()))
+                .collect(),
+            constraint: record.constraint.map(Arc::new),
+            resolved: record.resolved.map(|c| Arc::new(c.into())),
+            landmark: record
+                .landmark
+                .map(|(from, to)| T2(Arc::new(from), Arc::new(to))),
+            preferred: record.preferred,
+            cache: Default::default(),
+            depth: record.depth,
+        }
+    }
+}
+
+/// Conflict resolved in the search tree by the constraint imposed on one of its agents.
+#[derive(Debug, Clone)]
+pub struct Resolution<S, A, C, DC>
+where
+    C: Ord + LimitValues,
+    DC: Ord + Default,
+{
+    pub conflict: Arc<Conflict<S, A, C, DC>>,
+    pub constraint: Arc<Constraint<S, C>>,
+    /// The agent that is constrained.
+    pub agent: usize,
+}
+
+/// Result of the Conflict-Based Search algorithm.
+#[derive(Debug)]
+pub enum SolveOutcome<S, A, C, DC>
+where
+    S: Debug + Eq,
+    C: Ord + Default + LimitValues,
+{
+    /// The solutions of all the agents, which may be suboptimal if some nodes have been evicted
+    /// from the open list before they were found.
+    Solved {
+        solutions: Vec<SippSolution<S, A, C, DC>>,
+        maybe_suboptimal: bool,
+    },
+    /// The search tree has been exhausted without finding any solution.
+    Infeasible,
+    /// The time limit has been reached before finding a solution. Contains the solutions of the
+    /// most promising node of the open list, which may still have conflicts, if there was any.
+    TimedOut(Option<Vec<SippSolution<S, A, C, DC>>>),
+    /// No solution has been found but some nodes have been evicted from the open list,
+    /// so the problem may still be feasible with a larger open list.
+    Truncated,
+    /// The goal state of the given agent is not reachable from its initial state.
+    Disconnected(usize),
+}
+
+impl<S, A, C, DC> SolveOutcome<S, A, C, DC>
+where
+    S: Debug + Eq,
+    C: Ord + Default + LimitValues,
+{
+    /// Returns the solutions of all the agents, if they have been found.
+    pub fn solutions(self) -> Option<Vec<SippSolution<S, A, C, DC>>> {
+        match self {
+            SolveOutcome::Solved { solutions, .. } => Some(solutions),
+            _ => None,
+        }
+    }
+
+    /// Returns the solutions of all the agents, panics if they have not been found.
+    pub fn unwrap(self) -> Vec<SippSolution<S, A, C, DC>> {
+        self.solutions().expect("no solution found")
+    }
+}
+
+/// Change of a single agent after which it is reoptimized within a fixed plan.
+pub enum AgentUpdate<TS, S, C>
+where
+    S: State + Eq + Clone,
+    C: Copy,
+{
+    /// The agent has a new task, e.g. with a slightly different goal state.
+    Task(Arc<Task<S, C>>),
+    /// The costs of the moves have changed, which are given by the new transition system.
+    /// They may only increase, so that the heuristics of the configuration remain admissible.
+    Costs(Arc<TS>),
+}
+
+/// Current state of an agent during the execution of a plan.
+/// An agent partway along an edge is represented by the next state of the edge,
+/// along with the travel time remaining before it reaches that state.
+#[derive(Debug, Clone)]
+pub struct ExecutionState<S, C, DC> {
+    /// State that the agent comes from, which is its current state if it is not moving.
+    pub from: S,
+    pub state: S,
+    pub time: C,
+    pub residual: DC,
 }
 
 /// Input configuration for the Conflict-Based Search algorithm.
@@ -859,12 +2031,43 @@ where
 {
     pub n_agents: usize,
     pub tasks: Vec<Arc<Task<S, C>>>,
-    frozen: FxHashMap<usize, Solution<Arc<SippState<S, C>>, A, C, DC>>,
+    /// Weight of the cost of each agent in the sum-of-costs objective, 1.0 by default.
+    weights: FxHashMap<usize, f32>,
+    frozen: FxHashMap<usize, SippSolution<S, A, C, DC>>,
     /// A set of pivot states.
     pivots: Arc<Vec<S>>,
     /// A set of heuristics to those pivot states.
     heuristic_to_pivots: Arc<Vec<Arc<ReverseResumableAStar<TS, S, A, C, DC, H>>>>,
     precision: DC,
+    /// Whether to explore first the branch that constrains the agent arriving later at a conflict.
+    constrain_later_agent_first: bool,
+    /// Whether to split on the conflict that maximizes the minimum increase of the lower bound of its children.
+    select_by_lower_bound_increase: bool,
+    /// States that are occupied during given intervals, independently of the agents
+    /// except the one that holds the reservation, if any.
+    reservations: Vec<(S, Interval<C>, Option<usize>)>,
+    /// Edges reserved for the agents partway along them during a replanning, in both directions.
+    reserved_actions: Vec<(S, S, Interval<C>, usize)>,
+    /// Maximum number of agents that may enter each edge within a sliding window, if limited.
+    edge_capacities: FxHashMap<(S, S), (usize, DC)>,
+    /// Time base of each agent and tolerance up to which their times agree, if the conflicts
+    /// are detected on a common timeline.
+    time_bases: Option<(Vec<Arc<dyn TimeBase<C, C> + Send + Sync>>, DC)>,
+    /// Whether the constraints assembled for each node are cached to speed up their reconstruction.
+    memoize_constraints: bool,
+    /// Whether the low-level solutions are cached and reused for identical subproblems.
+    cache_solutions: bool,
+    /// Whether the conflicts between unconstrained agents are classified with their diagrams.
+    classify_with_mdds: bool,
+    /// Maximum number of nodes in the open list, the nodes with the highest costs are evicted beyond.
+    max_open_nodes: Option<usize>,
+    /// Whether to check that each agent can reach its goal state before searching.
+    check_connectivity: bool,
+    /// Maximum duration of the search.
+    time_limit: Option<Duration>,
+    /// Random number generator shared by all the randomized strategies.
+    #[cfg(feature = "rand")]
+    rng: Option<Mutex<StdRng>>,
     _phantom: PhantomData<(TS, A)>,
 }
 
@@ -891,29 +2094,222 @@ where
     ) -> Self {
         Self {
             n_agents: tasks.len(),
+            weights: FxHashMap::default(),
             tasks,
             frozen: FxHashMap::default(),
             pivots,
             heuristic_to_pivots,
             precision,
+            constrain_later_agent_first: false,
+            select_by_lower_bound_increase: false,
+            reservations: vec![],
+            reserved_actions: vec![],
+            edge_capacities: FxHashMap::default(),
+            time_bases: None,
+            memoize_constraints: false,
+            cache_solutions: false,
+            classify_with_mdds: false,
+            max_open_nodes: None,
+            check_connectivity: false,
+            time_limit: None,
+            #[cfg(feature = "rand")]
+            rng: None,
             _phantom: PhantomData,
         }
     }
 
-    pub fn add_frozen(&mut self, agent: usize, solution: Solution<Arc<SippState<S, C>>, A, C, DC>) {
+    pub fn add_frozen(&mut self, agent: usize, solution: SippSolution<S, A, C, DC>) {
         self.frozen.insert(agent, solution);
     }
 
     pub fn use_n_agents(&mut self, n_agents: usize) {
         for agent in n_agents..self.n_agents {
             self.frozen.remove(&agent);
+            self.weights.remove(&agent);
         }
         self.n_agents = n_agents;
         self.tasks.truncate(n_agents);
     }
+
+    /// Weights the cost of the given agent in the sum-of-costs objective, so that conflicts
+    /// tend to be resolved at the expense of the agents with lower weights.
+    pub fn set_agent_weight(&mut self, agent: usize, weight: f32) {
+        self.weights.insert(agent, weight);
+    }
+
+    fn get_weight(&self, agent: usize) -> f32 {
+        self.weights.get(&agent).copied().unwrap_or(1.0)
+    }
+
+    /// When resolving a conflict, explores first the branch that constrains the agent
+    /// arriving later, which tends to find cheap solutions sooner without affecting optimality.
+    pub fn set_constrain_later_agent_first(&mut self, constrain_later_agent_first: bool) {
+        self.constrain_later_agent_first = constrain_later_agent_first;
+    }
+
+    /// Splits each node on the conflict that maximizes the minimum increase of the lower bound
+    /// among its children, which is recorded when the conflicts are classified, instead of on the conflict
+    /// with the highest priority.
+    pub fn set_select_by_lower_bound_increase(&mut self, select_by_lower_bound_increase: bool) {
+        self.select_by_lower_bound_increase = select_by_lower_bound_increase;
+    }
+
+    /// Reserves the given state during the given interval, which is then forbidden to all agents.
+    pub fn add_reservation(&mut self, state: S, interval: Interval<C>) {
+        self.reservations.push((state, interval, None));
+    }
+
+    /// Limits the number of agents that may enter the edge between the given states within
+    /// any sliding window of the given duration, e.g. to model the throughput of a corridor.
+    /// A capacity of one with a window of the duration of the move forbids agents from following
+    /// each other on the edge.
+    pub fn set_edge_capacity(&mut self, from: S, to: S, capacity: usize, window: DC) {
+        self.edge_capacities.insert((from, to), (capacity, window));
+    }
+
+    /// Detects the conflicts on a common timeline, into which the times of each agent are converted
+    /// with its time base. Since these times only agree up to the given tolerance, agents that would
+    /// collide if one of them were delayed by the tolerance are in conflict, and the constraints
+    /// that resolve their conflicts are extended by the tolerance.
+    pub fn set_time_bases(
+        &mut self,
+        time_bases: Vec<Arc<dyn TimeBase<C, C> + Send + Sync>>,
+        tolerance: DC,
+    ) {
+        self.time_bases = Some((time_bases, tolerance));
+    }
+
+    /// Caches the constraints assembled for the nodes at regular depths, which avoids walking up
+    /// the whole tree to reconstruct them at the expense of memory.
+    pub fn set_memoize_constraints(&mut self, memoize_constraints: bool) {
+        self.memoize_constraints = memoize_constraints;
+    }
+
+    /// Caches the solution of each low-level search, which is reused when the same agent must be
+    /// replanned under the same constraints and landmarks in another node of the tree,
+    /// e.g. after the same splits have been made in a different order.
+    pub fn set_cache_solutions(&mut self, cache_solutions: bool) {
+        self.cache_solutions = cache_solutions;
+    }
+
+    /// Classifies the conflicts between agents that have not been constrained yet with the diagrams
+    /// of their optimal paths instead of replanning them, which is cheaper but leaves the overcost
+    /// of these conflicts unknown. Agents with reservations or transient tasks are always replanned.
+    pub fn set_classify_with_mdds(&mut self, classify_with_mdds: bool) {
+        self.classify_with_mdds = classify_with_mdds;
+    }
+
+    /// Bounds the number of nodes in the open list by evicting the nodes with the highest costs.
+    /// This bounds the memory usage but the solution returned is not guaranteed to be optimal
+    /// anymore if any node is evicted, which is reported in the outcome and the statistics.
+    pub fn set_max_open_nodes(&mut self, max_open_nodes: Option<usize>) {
+        self.max_open_nodes = max_open_nodes;
+    }
+
+    /// Checks that the goal state of each agent is reachable from its initial state before searching,
+    /// which fails fast instead of exploring the search tree in vain.
+    pub fn set_check_connectivity(&mut self, check_connectivity: bool) {
+        self.check_connectivity = check_connectivity;
+    }
+
+    /// Stops the search after the given duration, the most promising node found so far is then reported.
+    pub fn set_time_limit(&mut self, time_limit: Option<Duration>) {
+        self.time_limit = time_limit;
+    }
+
+    /// Seeds the random number generator used by the randomized strategies, so that a run is entirely
+    /// reproduced by its seed. The high-level search of a seeded configuration runs on a single thread.
+    #[cfg(feature = "rand")]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Some(Mutex::new(StdRng::seed_from_u64(seed)));
+    }
+
+    /// Suggests an order of priority of the agents from the geometry of their tasks, before any search.
+    /// The agents whose straight lines from their initial states to their goal states cross
+    /// the most others come first, ties being broken by decreasing length of these lines.
+    /// This is only a hint, e.g. for prioritized planning, which does not guarantee that
+    /// planning the agents in that order succeeds.
+    pub fn suggest_priority_order<F>(&self, position: F) -> Vec<usize>
+    where
+        F: Fn(&S) -> (f32, f32),
+    {
+        let segments = self
+            .tasks
+            .iter()
+            .map(|task| (position(&task.initial_state), position(&task.goal_state)))
+            .collect::<Vec<_>>();
+
+        let crossings = (0..segments.len())
+            .map(|i| {
+                (0..segments.len())
+                    .filter(|j| i != *j && segments_cross(segments[i], segments[*j]))
+                    .count()
+            })
+            .collect::<Vec<_>>();
+        let length = |((x1, y1), (x2, y2)): ((f32, f32), (f32, f32))| {
+            ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt()
+        };
+
+        let mut order = (0..segments.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            crossings[*b]
+                .cmp(&crossings[*a])
+                .then_with(|| length(segments[*b]).total_cmp(&length(segments[*a])))
+        });
+        order
+    }
+
+    /// Returns the reservations as a set of constraints for the given agent.
+    fn get_reservations(&self, agent: usize) -> ConstraintSet<S, C> {
+        let mut constraints = ConstraintSet::default();
+        for (state, interval, _) in self
+            .reservations
+            .iter()
+            .filter(|(_, _, holder)| *holder != Some(agent))
+        {
+            constraints.add(&Arc::new(Constraint::new_state_constraint(
+                agent,
+                state.clone(),
+                *interval,
+            )));
+        }
+        for (from, to, interval, _) in self
+            .reserved_actions
+            .iter()
+            .filter(|(_, _, _, holder)| *holder != agent)
+        {
+            constraints.add(&Arc::new(Constraint::new_action_constraint(
+                agent,
+                from.clone(),
+                to.clone(),
+                *interval,
+            )));
+        }
+        constraints
+    }
+}
+
+/// Returns true if both segments properly cross each other.
+fn segments_cross((a, b): ((f32, f32), (f32, f32)), (c, d): ((f32, f32), (f32, f32))) -> bool {
+    // The sign of the cross product tells on which side of the first segment the point lies
+    let side = |p: (f32, f32), q: (f32, f32), r: (f32, f32)| {
+        (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)
+    };
+    side(a, b, c) * side(a, b, d) < 0.0 && side(c, d, a) * side(c, d, b) < 0.0
 }
 
+/// Depth interval between the nodes that memoize their constraints, the other nodes walk up
+/// to the closest such ancestor. The memory used by the memoization then grows with the depth
+/// of the nodes divided by this interval instead of the depth itself, at the expense of walking
+/// up to this number of nodes to reconstruct the constraints of a node.
+const MEMOIZATION_INTERVAL: usize = 8;
+
+/// Constraints and landmarks assembled for each agent, shared by a node and its minimal clones.
+type ConstraintCache<S, C> =
+    Arc<Mutex<FxHashMap<usize, Arc<(ConstraintSet<S, C>, LandmarkSet<S, C>)>>>>;
+
 /// A node in the Conflict-Based Search tree.
+/// Each node only stores the constraint it adds with respect to its parent.
 #[derive(Debug)]
 pub struct CbsNode<S, A, C, DC>
 where
@@ -923,10 +2319,17 @@ where
 {
     pub total_cost: DC,
     parent: Option<Arc<Self>>,
-    solutions: Vec<Solution<Arc<SippState<S, C>>, A, C, DC>>,
+    solutions: Vec<SippSolution<S, A, C, DC>>,
     pub conflicts: Vec<Arc<Conflict<S, A, C, DC>>>,
     constraint: Option<Arc<Constraint<S, C>>>,
+    /// The conflict of the parent node that the constraint resolves.
+    resolved: Option<Arc<Conflict<S, A, C, DC>>>,
     landmark: Option<A2<Arc<Constraint<S, C>>>>,
+    /// Nodes with equal costs are expanded in priority if they are preferred.
+    preferred: bool,
+    cache: ConstraintCache<S, C>,
+    /// Number of constraints added from the root to this node.
+    pub depth: usize,
 }
 
 impl<S, A, C, DC> Default for CbsNode<S, A, C, DC>
@@ -942,7 +2345,11 @@ where
             solutions: vec![],
             conflicts: vec![],
             constraint: None,
+            resolved: None,
             landmark: None,
+            preferred: false,
+            cache: Default::default(),
+            depth: 0,
         }
     }
 }
@@ -956,11 +2363,15 @@ where
     pub fn new(parent: Arc<Self>, constraint: Arc<Constraint<S, C>>) -> Self {
         Self {
             total_cost: parent.total_cost,
+            depth: parent.depth + 1,
             parent: Some(parent),
             solutions: vec![],
             conflicts: vec![],
             constraint: Some(constraint),
+            resolved: None,
             landmark: None,
+            preferred: false,
+            cache: Default::default(),
         }
     }
 
@@ -971,12 +2382,45 @@ where
             solutions: vec![],
             conflicts: vec![],
             constraint: self.constraint.clone(),
+            resolved: self.resolved.clone(),
             landmark: self.landmark.clone(),
+            preferred: self.preferred,
+            cache: self.cache.clone(),
+            depth: self.depth,
         }
     }
 
+    /// Returns the conflicts resolved along the branch of the node, from the root to the node,
+    /// along with the constraints that resolved them.
+    pub fn get_resolutions(&self) -> Vec<Resolution<S, A, C, DC>> {
+        let mut resolutions = vec![];
+
+        let mut current = self;
+        while let Some(parent) = &current.parent {
+            if let (Some(conflict), Some(constraint)) = (&current.resolved, &current.constraint) {
+                resolutions.push(Resolution {
+                    conflict: conflict.clone(),
+                    constraint: constraint.clone(),
+                    agent: constraint.agent,
+                });
+            }
+            current = parent;
+        }
+
+        resolutions.reverse();
+        resolutions
+    }
+
     pub fn get_constraints(&self, agent: usize) -> (Arc<ConstraintSet<S, C>>, LandmarkSet<S, C>) {
-        let mut constraints = ConstraintSet::default();
+        self.get_constraints_from(agent, ConstraintSet::default())
+    }
+
+    /// Collects the constraints and landmarks of the given agent on top of the given constraints.
+    pub fn get_constraints_from(
+        &self,
+        agent: usize,
+        mut constraints: ConstraintSet<S, C>,
+    ) -> (Arc<ConstraintSet<S, C>>, LandmarkSet<S, C>) {
         let mut landmarks = LandmarkSet::default();
 
         let mut current = self;
@@ -1006,12 +2450,31 @@ where
         (Arc::new(constraints), landmarks)
     }
 
-    pub fn get_constraints_alt(
+    /// Same as get_constraints_from, but stops walking up the tree at the closest ancestor
+    /// whose depth is a multiple of the memoization interval, which caches its constraints for the given agent.
+    pub fn get_constraints_memoized(
         &self,
         agent: usize,
-    ) -> (ConstraintSet<S, C>, Vec<A2<Arc<Constraint<S, C>>>>) {
+        mut constraints: ConstraintSet<S, C>,
+    ) -> (Arc<ConstraintSet<S, C>>, LandmarkSet<S, C>) {
+        let cached = self.get_cached_constraints(agent);
+
+        constraints.merge(&cached.0);
+        constraints.unify();
+
+        (Arc::new(constraints), cached.1.clone())
+    }
+
+    fn get_cached_constraints(
+        &self,
+        agent: usize,
+    ) -> Arc<(ConstraintSet<S, C>, LandmarkSet<S, C>)> {
+        if let Some(cached) = self.cache.lock().get(&agent) {
+            return cached.clone();
+        }
+
         let mut constraints = ConstraintSet::default();
-        let mut landmarks = vec![];
+        let mut landmarks = LandmarkSet::default();
 
         let mut current = self;
         loop {
@@ -1022,25 +2485,68 @@ where
             }
             if let Some(T2(from, to)) = &current.landmark {
                 if from.agent == agent {
-                    landmarks.push(T2(from.clone(), to.clone()));
+                    landmarks.push(from.clone());
+                    landmarks.push(to.clone());
                 }
             }
 
-            if let Some(parent) = &current.parent {
-                current = parent;
-            } else {
-                break;
+            match &current.parent {
+                Some(parent) if parent.depth.is_multiple_of(MEMOIZATION_INTERVAL) => {
+                    let cached = parent.get_cached_constraints(agent);
+                    constraints.merge(&cached.0);
+                    landmarks.extend(cached.1.iter().cloned());
+                    break;
+                }
+                Some(parent) => current = parent,
+                None => break,
             }
         }
 
+        landmarks.sort_unstable();
         constraints.unify();
 
-        (constraints, landmarks)
+        let cached = Arc::new((constraints, landmarks));
+        if self.depth.is_multiple_of(MEMOIZATION_INTERVAL) {
+            self.cache.lock().insert(agent, cached.clone());
+        }
+        cached
     }
 
-    pub fn get_solutions(&self, n_agents: usize) -> Vec<&Solution<Arc<SippState<S, C>>, A, C, DC>> {
-        let mut found = 0;
-        let mut solutions = vec![None; n_agents];
+    pub fn get_constraints_alt(
+        &self,
+        agent: usize,
+    ) -> (ConstraintSet<S, C>, Vec<A2<Arc<Constraint<S, C>>>>) {
+        let mut constraints = ConstraintSet::default();
+        let mut landmarks = vec![];
+
+        let mut current = self;
+        loop {
+            if let Some(constraint) = &current.constraint {
+                if constraint.agent == agent {
+                    constraints.add(constraint);
+                }
+            }
+            if let Some(T2(from, to)) = &current.landmark {
+                if from.agent == agent {
+                    landmarks.push(T2(from.clone(), to.clone()));
+                }
+            }
+
+            if let Some(parent) = &current.parent {
+                current = parent;
+            } else {
+                break;
+            }
+        }
+
+        constraints.unify();
+
+        (constraints, landmarks)
+    }
+
+    pub fn get_solutions(&self, n_agents: usize) -> Vec<&SippSolution<S, A, C, DC>> {
+        let mut found = 0;
+        let mut solutions = vec![None; n_agents];
 
         let mut current = self;
         loop {
@@ -1073,6 +2579,39 @@ where
         solutions.into_iter().map(|s| s.unwrap()).collect()
     }
 
+    /// Returns an iterator over the conflicts between the solutions of the node.
+    /// The conflicts are detected when the node is enqueued, so they are yielded from the stored vector.
+    pub fn conflicts_iter(&self) -> impl Iterator<Item = Arc<Conflict<S, A, C, DC>>> + '_ {
+        self.conflicts.iter().cloned()
+    }
+
+    /// Returns true if a constraint or a landmark is imposed on the given agent along the branch of the node.
+    fn is_constrained(&self, agent: usize) -> bool {
+        let mut current = self;
+
+        loop {
+            if current
+                .constraint
+                .as_ref()
+                .is_some_and(|c| c.agent == agent)
+                || current
+                    .landmark
+                    .as_ref()
+                    .is_some_and(|l| l.0.agent == agent)
+            {
+                return true;
+            }
+
+            if let Some(parent) = current.parent.as_ref() {
+                current = parent;
+            } else {
+                break;
+            }
+        }
+
+        false
+    }
+
     fn contains_landmark(&self, landmark: T2<&Constraint<S, C>, &Constraint<S, C>>) -> bool {
         let mut current = self;
 
@@ -1140,7 +2679,7 @@ where
     DC: PartialEq + Eq + PartialOrd + Ord + Default + Copy,
 {
     fn eq(&self, other: &Self) -> bool {
-        self.total_cost == other.total_cost
+        self.total_cost == other.total_cost && self.preferred == other.preferred
     }
 }
 
@@ -1170,7 +2709,9 @@ where
     DC: PartialEq + Eq + PartialOrd + Ord + Default + Copy,
 {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.total_cost.cmp(&other.total_cost)
+        self.total_cost
+            .cmp(&other.total_cost)
+            .then_with(|| other.preferred.cmp(&self.preferred))
     }
 }
 
@@ -1185,58 +2726,124 @@ where
     WorkItem { node: Arc<CbsNode<S, A, C, DC>> },
 }
 
+/// Statistics of the low-level searches of a single agent.
+#[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AgentStats {
+    pub searches: usize,
+    pub expanded: usize,
+    pub time: Duration,
+}
+
 /// Statistics of the Conflict-Based Search algorithm.
 #[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CbsStats {
     pub expanded: usize,
+    /// Number of nodes evicted from the open list, the solution may be suboptimal if non-zero.
+    pub evicted: usize,
+    /// Maximum number of nodes in the open list.
+    pub max_open: usize,
+    /// Number of nodes generated and added to the open list, including the root node.
+    pub generated: usize,
+    /// Maximum depth of the generated nodes.
+    pub max_depth: usize,
+    /// Number of low-level searches avoided by reusing a cached solution.
+    pub cache_hits: usize,
     pub lsipp_stats: LSippStats,
     pub rra_stats: RraStats,
 }
 
+impl CbsStats {
+    /// Returns the effective branching factor of the search, i.e. the value `b` such that
+    /// `b^max_depth` equals the number of generated nodes, or None if no node has been branched on.
+    pub fn effective_branching_factor(&self) -> Option<f64> {
+        if self.max_depth == 0 {
+            return None;
+        }
+        Some((self.generated as f64).powf(1.0 / self.max_depth as f64))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{sync::Arc, time::Duration};
 
     use ordered_float::OrderedFloat;
+    use tuple::T2;
 
     use crate::{
-        Graph, GraphNodeId, ReverseResumableAStar, SimpleEdgeData, SimpleHeuristic, SimpleNodeData,
-        SimpleState, SimpleWorld, Task,
+        find_conflict_on_common_timeline, simple_graph, Conflict, ConflictType, Constraint,
+        ConstraintSet, Graph, GraphEdgeId, GraphNodeId, Interval, LimitValues, Mdd, MyTime,
+        ReverseResumableAStar, SafeIntervalPathPlanningWithLandmarks, ScaledTimeBase,
+        SimpleHeuristic, SimpleState, SimpleWorld, Solution, Task, TransitionSystem,
     };
 
-    use super::{CbsConfig, ConflictBasedSearch};
+    use super::{
+        AgentUpdate, CbsConfig, ConflictBasedSearch, ExecutionState, SolveOutcome,
+        MEMOIZATION_INTERVAL,
+    };
 
-    fn simple_graph(size: usize) -> Arc<Graph<SimpleNodeData, SimpleEdgeData>> {
-        let mut graph = Graph::new();
-        for x in 0..size {
-            for y in 0..size {
-                graph.add_node((x as f32, y as f32));
-            }
-        }
-        for x in 0..size {
-            for y in 0..size {
-                let node_id = GraphNodeId(x + y * size);
-                if x > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x - 1 + y * size), 1.0);
-                }
-                if y > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y - 1) * size), 1.0);
-                }
-                if x < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + 1 + y * size), 1.0);
-                }
-                if y < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y + 1) * size), 1.0);
-                }
-            }
-        }
-        Arc::new(graph)
+    /// Returns the conflicts in a form that does not depend on the order of detection.
+    fn normalize(
+        conflicts: &[Arc<Conflict<SimpleState, GraphEdgeId, MyTime, MyTime>>],
+    ) -> Vec<(usize, usize, ConflictType, MyTime, MyTime)> {
+        let mut conflicts = conflicts
+            .iter()
+            .map(|c| {
+                let agents = (c.moves.0.agent, c.moves.1.agent);
+                (
+                    agents.0.min(agents.1),
+                    agents.0.max(agents.1),
+                    c.type_,
+                    c.overcost,
+                    c.moves.0.interval.start.min(c.moves.1.interval.start),
+                )
+            })
+            .collect::<Vec<_>>();
+        conflicts.sort();
+        conflicts
+    }
+
+    fn get_config(
+        transition_system: &Arc<SimpleWorld>,
+        tasks: Vec<Arc<Task<SimpleState, MyTime>>>,
+    ) -> CbsConfig<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime, SimpleHeuristic> {
+        let pivots = Arc::new(tasks.iter().map(|t| t.goal_state.clone()).collect());
+        let heuristic_to_pivots = Arc::new(
+            tasks
+                .iter()
+                .map(|t| {
+                    Arc::new(ReverseResumableAStar::new(
+                        transition_system.clone(),
+                        t.clone(),
+                        SimpleHeuristic::new(transition_system.clone(), Arc::new(t.reverse())),
+                    ))
+                })
+                .collect(),
+        );
+
+        CbsConfig::new(tasks, pivots, heuristic_to_pivots, OrderedFloat(1e-6))
+    }
+
+    /// Returns the task of an agent that goes from one node to another, starting at time zero.
+    fn task(from: usize, to: usize) -> Arc<Task<SimpleState, MyTime>> {
+        Arc::new(Task::new(
+            SimpleState(GraphNodeId(from)),
+            SimpleState(GraphNodeId(to)),
+            OrderedFloat(0.0),
+        ))
+    }
+
+    /// Returns the tasks of four agents whose shortest paths conflict in a grid of size 5.
+    fn get_crossing_tasks() -> Vec<Arc<Task<SimpleState, MyTime>>> {
+        vec![task(16, 9), task(17, 8), task(22, 4), task(11, 13)]
     }
 
     #[test]
     fn test_simple() {
         let size = 10;
-        let graph = simple_graph(size);
+        let graph = Arc::new(simple_graph(size));
         let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
 
         let tasks = vec![
@@ -1284,7 +2891,7 @@ mod tests {
     #[test]
     fn test_frozen() {
         let size = 10;
-        let graph = simple_graph(size);
+        let graph = Arc::new(simple_graph(size));
         let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
 
         let mut tasks = vec![Arc::new(Task::new(
@@ -1340,4 +2947,1457 @@ mod tests {
 
         assert_eq!(solutions[0].steps, config.frozen[&0].steps);
     }
+
+    #[test]
+    fn test_reoptimize_agent() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let tasks = get_crossing_tasks();
+        let config = get_config(&transition_system, tasks.clone());
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let plan = solver.solve(&config).unwrap();
+
+        // The other agents are untouched and the new plan is conflict-free
+        let check = |new_plan: &[Solution<_, _, _, _>], agent: usize, new_task| {
+            for other in (0..tasks.len()).filter(|other| *other != agent) {
+                assert_eq!(new_plan[other].steps, plan[other].steps);
+            }
+            let mut tasks = tasks.clone();
+            tasks[agent] = new_task;
+            let mut config = get_config(&transition_system, tasks);
+            for (agent, solution) in new_plan.iter().enumerate() {
+                config.add_frozen(agent, solution.clone());
+            }
+            let mut solver = ConflictBasedSearch::new(transition_system.clone());
+            assert!(solver.solve_iter(&config).unwrap().conflicts.is_empty());
+        };
+
+        // The second agent has already moved one node closer to its goal
+        let new_plan = solver
+            .reoptimize_agent(&config, &plan, 1, AgentUpdate::Task(task(12, 8)))
+            .unwrap();
+        check(&new_plan, 1, task(12, 8));
+        assert_eq!(
+            new_plan[1].steps.first().unwrap().0.internal_state,
+            SimpleState(GraphNodeId(12))
+        );
+        assert!(new_plan[1].cost < plan[1].cost);
+
+        // The goal of the first agent moves slightly, to a neighbor that it passes through
+        let new_plan = solver
+            .reoptimize_agent(&config, &plan, 0, AgentUpdate::Task(task(16, 14)))
+            .unwrap();
+        check(&new_plan, 0, task(16, 14));
+        assert_eq!(
+            new_plan[0].steps.last().unwrap().0.internal_state,
+            SimpleState(GraphNodeId(14))
+        );
+        assert!(new_plan[0].cost < plan[0].cost);
+
+        // Unchanged costs lead to a solution of the same cost
+        let new_plan = solver
+            .reoptimize_agent(
+                &config,
+                &plan,
+                1,
+                AgentUpdate::Costs(transition_system.clone()),
+            )
+            .unwrap();
+        check(&new_plan, 1, task(17, 8));
+        assert_eq!(new_plan[1].cost, plan[1].cost);
+
+        // A farther goal cannot be reached at the cost of the current solution
+        assert!(solver
+            .reoptimize_agent(&config, &plan, 1, AgentUpdate::Task(task(17, 0)))
+            .is_none());
+    }
+
+    #[test]
+    fn test_incremental_conflicts() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let config = get_config(
+            &transition_system,
+            vec![
+                task(0, size - 1),
+                task(size - 1, 0),
+                task(2, 2 + size * (size - 1)),
+                task(2 + size * (size - 1), 2),
+            ],
+        );
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let mut lsipp = SafeIntervalPathPlanningWithLandmarks::new(transition_system.clone());
+
+        let mut checked = 0;
+        for _ in 0..20 {
+            let node = match solver.solve_iter(&config) {
+                Some(node) => node,
+                None => break,
+            };
+            if let Some(parent) = &node.parent {
+                let agent = node.constraint.as_ref().unwrap().agent;
+
+                let full = ConflictBasedSearch::detect_conflicts(
+                    &solver.shared,
+                    &config,
+                    &node,
+                    None,
+                    &mut lsipp,
+                )
+                .unwrap();
+                let incremental = ConflictBasedSearch::detect_conflicts(
+                    &solver.shared,
+                    &config,
+                    &node,
+                    Some((&parent.conflicts, agent)),
+                    &mut lsipp,
+                )
+                .unwrap();
+
+                assert_eq!(normalize(&full), normalize(&incremental));
+                assert_eq!(normalize(&full), normalize(&node.conflicts));
+                checked += 1;
+            }
+            if node.conflicts.is_empty() {
+                break;
+            }
+        }
+
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn test_constrain_later_agent_first() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let tasks = vec![task(3, 1), task(24, 12), task(5, 13), task(11, 9)];
+
+        let solve = |constrain_later_agent_first| {
+            let mut config = get_config(&transition_system, tasks.clone());
+            config.set_constrain_later_agent_first(constrain_later_agent_first);
+            let mut solver = ConflictBasedSearch::new(transition_system.clone());
+            solver.n_threads = 1;
+            let solutions = solver.solve(&config).unwrap();
+            (
+                solutions.iter().map(|sol| sol.cost).sum::<MyTime>(),
+                solver.get_stats().expanded,
+            )
+        };
+
+        let (cost, expanded) = solve(false);
+        let (later_cost, later_expanded) = solve(true);
+
+        assert_eq!(cost, later_cost);
+        assert!(later_expanded < expanded);
+    }
+
+    #[test]
+    fn test_reservations() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let center = SimpleState(GraphNodeId(size * size / 2));
+        let window = Interval::new(OrderedFloat(0.0), OrderedFloat(5.0));
+
+        let mut config = get_config(
+            &transition_system,
+            vec![
+                task(2 * size, 3 * size - 1),
+                task(2, 2 + size * (size - 1)),
+                task(0, size * size - 1),
+            ],
+        );
+        config.add_reservation(center.clone(), window);
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+
+        for solution in solutions.iter() {
+            for (i, (state, time)) in solution.steps.iter().enumerate() {
+                if state.internal_state == center {
+                    let end = solution
+                        .steps
+                        .get(i + 1)
+                        .map_or(MyTime::max_value(), |(_, time)| *time);
+                    assert!(!Interval::new(*time, end).overlaps(&window));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_memoized_constraints() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut config = get_config(&transition_system, get_crossing_tasks());
+        config.set_memoize_constraints(true);
+
+        let intervals =
+            |constraints: &ConstraintSet<SimpleState, MyTime>| {
+                let mut intervals =
+                    constraints
+                        .state_constraints
+                        .iter()
+                        .flat_map(|(s, c)| c.iter().map(|c| (s.0 .0, s.0 .0, c.interval)))
+                        .chain(constraints.action_constraints.iter().flat_map(|(s, c)| {
+                            c.iter().map(|c| (s.0 .0 .0, s.1 .0 .0, c.interval))
+                        }))
+                        .collect::<Vec<_>>();
+                intervals.sort();
+                intervals
+            };
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let mut checked = 0;
+        for _ in 0..50 {
+            let node = match solver.solve_iter(&config) {
+                Some(node) => node,
+                None => break,
+            };
+
+            // Each node only stores its own constraint, the rest is inherited from its parent
+            let mut depth = 0;
+            let mut current = node.as_ref();
+            while let Some(parent) = &current.parent {
+                assert!(current.constraint.is_some());
+                assert_eq!(current.solutions.len(), 1);
+                depth += 1;
+                current = parent;
+            }
+            assert_eq!(current.solutions.len(), config.n_agents);
+
+            for agent in 0..config.n_agents {
+                let full = node.get_constraints(agent);
+                let memoized = node.get_constraints_memoized(agent, ConstraintSet::default());
+                assert_eq!(intervals(&full.0), intervals(&memoized.0));
+                assert_eq!(full.1, memoized.1);
+                assert!(intervals(&full.0).len() <= depth);
+            }
+            checked += depth;
+
+            // Only the nodes at regular depths memoize their constraints, so the memory used
+            // along the branch grows with its depth divided by the interval
+            let mut memoized = 0;
+            let mut current = node.as_ref();
+            loop {
+                let cached = current.cache.lock().len();
+                if !current.depth.is_multiple_of(MEMOIZATION_INTERVAL) {
+                    assert_eq!(cached, 0);
+                }
+                memoized += cached;
+                match &current.parent {
+                    Some(parent) => current = parent,
+                    None => break,
+                }
+            }
+            assert!(memoized > 0);
+            assert!(memoized <= config.n_agents * (depth / MEMOIZATION_INTERVAL + 1));
+
+            if node.conflicts.is_empty() {
+                break;
+            }
+        }
+
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn test_mdd_classification() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let tasks = vec![
+            task(2 * size, 3 * size - 1),
+            task(2, 2 + size * (size - 1)),
+            task(0, size * size - 1),
+            task(size - 1, size * (size - 1)),
+        ];
+        let config = get_config(&transition_system, tasks);
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let root = solver.solve_iter(&config).unwrap();
+        let solutions = root.get_solutions(config.n_agents);
+
+        let mdds = (0..config.n_agents)
+            .map(|agent| solver.build_mdd(&config, agent, solutions[agent].cost))
+            .collect::<Vec<_>>();
+        assert!(mdds.iter().all(|mdd| !mdd.is_empty()));
+
+        assert!(!root.conflicts.is_empty());
+        for conflict in root.conflicts.iter() {
+            let agents = (conflict.moves.0.agent, conflict.moves.1.agent);
+            assert_eq!(
+                Mdd::classify((&mdds[agents.0], &mdds[agents.1]), conflict),
+                conflict.type_
+            );
+        }
+    }
+
+    #[test]
+    fn test_classify_with_mdds() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let tasks = vec![
+            task(2 * size, 3 * size - 1),
+            task(2, 2 + size * (size - 1)),
+            task(0, size * size - 1),
+            task(size - 1, size * (size - 1)),
+        ];
+
+        let get_types = |classify_with_mdds| {
+            let mut config = get_config(&transition_system, tasks.clone());
+            config.set_classify_with_mdds(classify_with_mdds);
+            let mut solver = ConflictBasedSearch::new(transition_system.clone());
+            let root = solver.solve_iter(&config).unwrap();
+            root.conflicts
+                .iter()
+                .map(|conflict| {
+                    (
+                        conflict.moves.0.agent,
+                        conflict.moves.1.agent,
+                        conflict.moves.0.interval,
+                        conflict.moves.1.interval,
+                        conflict.type_,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The diagrams of the unconstrained agents agree with replanning at the root
+        let types = get_types(true);
+        assert!(!types.is_empty());
+        assert_eq!(types, get_types(false));
+
+        // Corridor with a parking spot next to its middle, whose goal is blocked by the first agent
+        let mut graph = Graph::new();
+        for x in 0..5 {
+            graph.add_node((x as f32, 0.0));
+        }
+        graph.add_node((2.0, 1.0));
+        for x in 0..4 {
+            graph.add_edge(GraphNodeId(x), GraphNodeId(x + 1), 1.0);
+            graph.add_edge(GraphNodeId(x + 1), GraphNodeId(x), 1.0);
+        }
+        graph.add_edge(GraphNodeId(2), GraphNodeId(5), 1.0);
+        graph.add_edge(GraphNodeId(5), GraphNodeId(2), 1.0);
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        let mut config = get_config(&transition_system, vec![task(1, 2), task(0, 4)]);
+        config.set_classify_with_mdds(true);
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let root = solver.solve_iter(&config).unwrap();
+        assert_eq!(root.conflicts.len(), 1);
+        assert!(root.conflicts[0].is_goal_blocking());
+
+        // The first agent is forced to stay at its goal forever
+        assert_eq!(root.conflicts[0].type_, ConflictType::Cardinal);
+
+        // Constrained agents are replanned, and the search still finds the solution
+        let solutions = solver.solve(&config).unwrap();
+        assert_eq!(
+            solutions[0].steps.last().unwrap().0.internal_state,
+            SimpleState(GraphNodeId(2))
+        );
+        assert_eq!(
+            solutions[1].steps.last().unwrap().0.internal_state,
+            SimpleState(GraphNodeId(4))
+        );
+    }
+
+    #[test]
+    fn test_max_open_nodes() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let tasks = get_crossing_tasks();
+
+        let solve = |max_open_nodes| {
+            let mut config = get_config(&transition_system, tasks.clone());
+            config.set_max_open_nodes(max_open_nodes);
+            let mut solver = ConflictBasedSearch::new(transition_system.clone());
+            solver.n_threads = 1;
+            match solver.solve(&config) {
+                SolveOutcome::Solved {
+                    solutions,
+                    maybe_suboptimal,
+                } => (
+                    solutions.iter().map(|sol| sol.cost).sum::<MyTime>(),
+                    maybe_suboptimal,
+                    solver.get_stats(),
+                ),
+                outcome => panic!("unexpected outcome {:?}", outcome),
+            }
+        };
+
+        let (cost, maybe_suboptimal, stats) = solve(None);
+        assert_eq!(stats.evicted, 0);
+        assert!(!maybe_suboptimal);
+
+        let max_open_nodes = 3;
+        let (bounded_cost, maybe_suboptimal, bounded_stats) = solve(Some(max_open_nodes));
+        assert!(bounded_stats.evicted > 0);
+        assert!(maybe_suboptimal);
+        assert!(bounded_stats.max_open <= max_open_nodes);
+        assert!(bounded_stats.max_open < stats.max_open);
+        assert!(bounded_cost >= cost);
+    }
+
+    #[test]
+    fn test_goal_blocking() {
+        // Corridor with a parking spot next to its middle
+        let mut graph = Graph::new();
+        for x in 0..5 {
+            graph.add_node((x as f32, 0.0));
+        }
+        graph.add_node((2.0, 1.0));
+        for x in 0..4 {
+            graph.add_edge(GraphNodeId(x), GraphNodeId(x + 1), 1.0);
+            graph.add_edge(GraphNodeId(x + 1), GraphNodeId(x), 1.0);
+        }
+        graph.add_edge(GraphNodeId(2), GraphNodeId(5), 1.0);
+        graph.add_edge(GraphNodeId(5), GraphNodeId(2), 1.0);
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        // The first agent stops in the middle of the corridor that the second agent goes through
+        let config = get_config(&transition_system, vec![task(1, 2), task(0, 4)]);
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let root = solver.solve_iter(&config).unwrap();
+        assert_eq!(root.conflicts.len(), 1);
+        assert!(root.conflicts[0].is_goal_blocking());
+
+        // Both agents are delayed, the first one by vacating its goal, the second one by waiting for it
+        assert_eq!(root.conflicts[0].type_, ConflictType::Cardinal);
+
+        let solutions = solver.solve(&config).unwrap();
+
+        // The first agent reaches its goal, vacates it by going to the parking spot, and comes back
+        let states = solutions[0]
+            .steps
+            .iter()
+            .map(|(state, _)| state.internal_state.0 .0)
+            .collect::<Vec<_>>();
+        let reached = states.iter().position(|&node| node == 2).unwrap();
+        let parked = states.iter().position(|&node| node == 5).unwrap();
+        assert!(reached < parked);
+        assert_eq!(states.last(), Some(&2));
+        assert_eq!(solutions[0].steps[parked].1, OrderedFloat(2.0));
+
+        // The second agent passes through the goal of the first one while it is parked
+        let passed = solutions[1]
+            .steps
+            .iter()
+            .find(|(state, _)| state.internal_state == SimpleState(GraphNodeId(2)))
+            .unwrap()
+            .1;
+        assert!(passed > solutions[0].steps[parked].1);
+        assert!(passed < solutions[0].steps[parked + 1].1);
+    }
+
+    #[test]
+    fn test_goal_blocking_type() {
+        // Diamond whose top is the goal of the first agent, the second agent may go around it
+        // either through the top or through the bottom at the same cost
+        let mut graph = Graph::new();
+        for position in [
+            (0.0, 0.0),
+            (1.0, 1.0),
+            (1.0, -1.0),
+            (2.0, 0.0),
+            (1.0, 2.0),
+            (-1.0, 0.0),
+        ] {
+            graph.add_node(position);
+        }
+        for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 3), (4, 1), (5, 0)] {
+            graph.add_edge(GraphNodeId(from), GraphNodeId(to), 1.0);
+            graph.add_edge(GraphNodeId(to), GraphNodeId(from), 1.0);
+        }
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        let config = get_config(&transition_system, vec![task(4, 1), task(5, 3)]);
+
+        // Only the first agent would be delayed, by vacating its goal
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let root = solver.solve_iter(&config).unwrap();
+        assert_eq!(root.conflicts.len(), 1);
+        assert!(root.conflicts[0].is_goal_blocking());
+        assert_eq!(root.conflicts[0].type_, ConflictType::GoalBlocking);
+
+        // Goal-blocking conflicts come after the cardinal ones and before the semi-cardinal ones
+        assert!(ConflictType::Cardinal < ConflictType::GoalBlocking);
+        assert!(ConflictType::GoalBlocking < ConflictType::SemiCardinal);
+
+        // The second agent goes around through the bottom instead
+        let solutions = solver.solve(&config).unwrap();
+        assert!((solutions[0].cost.0 - 1.0).abs() < 1e-3);
+        assert!((solutions[1].cost.0 - (1.0 + 2.0 * 2.0f32.sqrt())).abs() < 1e-3);
+        assert!(solutions[1]
+            .steps
+            .iter()
+            .any(|(state, _)| state.internal_state == SimpleState(GraphNodeId(2))));
+    }
+
+    #[test]
+    fn test_transient() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let state = |node| SimpleState(GraphNodeId(node));
+
+        // Both agents want to end at the same node, but the first one leaves upon arrival
+        let tasks = vec![
+            Arc::new(Task::new_transient(state(0), state(1), OrderedFloat(0.0))),
+            Arc::new(Task::new(state(3), state(1), OrderedFloat(0.0))),
+        ];
+        let config = get_config(&transition_system, tasks);
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+
+        let arrivals = solutions
+            .iter()
+            .map(|sol| sol.steps.last().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(arrivals[0].0.internal_state, state(1));
+        assert_eq!(arrivals[1].0.internal_state, state(1));
+        assert!(arrivals[0].1 < arrivals[1].1);
+        assert_eq!(solutions[0].cost, OrderedFloat(1.0));
+        assert_eq!(solutions[1].cost, OrderedFloat(2.0));
+    }
+
+    #[test]
+    fn test_no_initial_wait() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let state = |node| SimpleState(GraphNodeId(node));
+
+        // The second agent leaves the state right in front of the first one
+        let mut costs = vec![];
+        for no_initial_wait in [false, true] {
+            let first = if no_initial_wait {
+                Task::new_without_initial_wait(state(0), state(2), OrderedFloat(0.0))
+            } else {
+                Task::new(state(0), state(2), OrderedFloat(0.0))
+            };
+            let tasks = vec![
+                Arc::new(first),
+                Arc::new(Task::new(state(1), state(2 * size + 1), OrderedFloat(0.0))),
+            ];
+            let config = get_config(&transition_system, tasks);
+            let mut solver = ConflictBasedSearch::new(transition_system.clone());
+            let solutions = solver.solve(&config).unwrap();
+
+            // Both plans are conflict-free
+            let moves = T2(solutions[0].to_moves(0), solutions[1].to_moves(1));
+            for first in &moves.0 {
+                for second in &moves.1 {
+                    assert!(
+                        !first.interval.overlaps(&second.interval)
+                            || !transition_system.conflict(T2(first, second))
+                    );
+                }
+            }
+
+            // The first agent departs immediately if it cannot wait
+            let first = &solutions[0];
+            assert_eq!(
+                first.steps[1].0.internal_state == state(0),
+                !no_initial_wait
+            );
+            costs.push(first.cost);
+        }
+
+        // Without waiting, the first agent must take a detour
+        assert!(costs[0] < OrderedFloat(3.0));
+        assert!(costs[1] > OrderedFloat(4.0));
+    }
+
+    #[test]
+    fn test_solve_anytime() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let config = get_config(&transition_system, get_crossing_tasks());
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        solver.n_threads = 1;
+        let optimal = solver
+            .solve(&config)
+            .unwrap()
+            .iter()
+            .map(|sol| sol.cost)
+            .sum::<MyTime>();
+
+        let results = solver.solve_anytime(&config, 2.0, &[1.5, 1.2, 1.0]);
+        assert!(!results.is_empty());
+
+        let costs = results
+            .iter()
+            .map(|(solutions, _)| solutions.iter().map(|sol| sol.cost).sum::<MyTime>())
+            .collect::<Vec<_>>();
+        for (i, (cost, (_, weight))) in costs.iter().zip(results.iter()).enumerate() {
+            assert!(*cost <= optimal * *weight + 1e-4);
+            if i > 0 {
+                assert!(*cost <= costs[i - 1] + 1e-4);
+            }
+        }
+
+        // The last solution has been proven optimal
+        let (_, weight) = results.last().unwrap();
+        assert_eq!(*weight, 1.0);
+        assert!((costs.last().unwrap() - optimal).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_conflicts_iter() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let config = get_config(&transition_system, get_crossing_tasks());
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        solver.n_threads = 1;
+        while let Some(node) = solver.solve_iter(&config) {
+            let conflicts = node.conflicts_iter().collect::<Vec<_>>();
+            assert_eq!(conflicts.len(), node.conflicts.len());
+            for (conflict, stored) in conflicts.iter().zip(node.conflicts.iter()) {
+                assert!(Arc::ptr_eq(conflict, stored));
+            }
+            if node.conflicts.is_empty() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_effective_branching_factor() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let config = get_config(&transition_system, get_crossing_tasks());
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        solver.n_threads = 1;
+        assert_eq!(solver.get_stats().effective_branching_factor(), None);
+        solver.solve(&config).unwrap();
+
+        let stats = solver.get_stats();
+        assert!(stats.max_depth > 0);
+        assert!(stats.generated > stats.max_depth);
+        let branching_factor = stats.effective_branching_factor().unwrap();
+        assert!(branching_factor.is_finite());
+        assert!(branching_factor > 1.0);
+    }
+
+    #[test]
+    fn test_deadlines() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let task = |from, to, deadline| {
+            Arc::new(Task::new_with_deadline(
+                SimpleState(GraphNodeId(from)),
+                SimpleState(GraphNodeId(to)),
+                OrderedFloat(0.0),
+                OrderedFloat(deadline),
+            ))
+        };
+
+        // Both agents cross the center of the grid at the same time,
+        // the agent with the tight deadline must go first
+        for tight in 0..2 {
+            let deadlines = if tight == 0 { (4.0, 10.0) } else { (10.0, 4.0) };
+            let config = get_config(
+                &transition_system,
+                vec![task(10, 14, deadlines.0), task(2, 22, deadlines.1)],
+            );
+            let mut solver = ConflictBasedSearch::new(transition_system.clone());
+            let solutions = solver.solve(&config).unwrap();
+
+            assert_eq!(solutions[tight].cost, OrderedFloat(4.0));
+            assert!(solutions[1 - tight].cost > OrderedFloat(4.0));
+            assert!(solutions[1 - tight].cost <= OrderedFloat(10.0));
+        }
+
+        // Both deadlines cannot be met
+        let config = get_config(
+            &transition_system,
+            vec![task(10, 14, 4.0), task(2, 22, 4.0)],
+        );
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        match solver.solve(&config) {
+            SolveOutcome::Infeasible => {}
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn test_solve_outcome() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let task = |from, to, deadline| {
+            Arc::new(Task::new_with_deadline(
+                SimpleState(GraphNodeId(from)),
+                SimpleState(GraphNodeId(to)),
+                OrderedFloat(0.0),
+                OrderedFloat(deadline),
+            ))
+        };
+        let get_outcome = |deadlines: (f32, f32), max_open_nodes, time_limit| {
+            let mut config = get_config(
+                &transition_system,
+                vec![task(10, 14, deadlines.0), task(2, 22, deadlines.1)],
+            );
+            config.set_max_open_nodes(max_open_nodes);
+            config.set_time_limit(time_limit);
+            let mut solver = ConflictBasedSearch::new(transition_system.clone());
+            solver.n_threads = 1;
+            solver.solve(&config)
+        };
+
+        match get_outcome((4.0, 10.0), None, None) {
+            SolveOutcome::Solved {
+                solutions,
+                maybe_suboptimal,
+            } => {
+                assert_eq!(solutions.len(), 2);
+                assert!(!maybe_suboptimal);
+            }
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+        match get_outcome((4.0, 4.0), None, None) {
+            SolveOutcome::Infeasible => {}
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+        match get_outcome((4.0, 10.0), None, Some(Duration::ZERO)) {
+            // The root node has been computed but the conflict has not been resolved
+            SolveOutcome::TimedOut(Some(solutions)) => {
+                assert_eq!(solutions[0].cost, OrderedFloat(4.0));
+                assert_eq!(solutions[1].cost, OrderedFloat(4.0));
+            }
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+        // Infeasible as well, but this is not proven after evicting a node
+        match get_outcome((5.0, 5.0), None, None) {
+            SolveOutcome::Infeasible => {}
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+        match get_outcome((5.0, 5.0), Some(1), None) {
+            SolveOutcome::Truncated => {}
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn test_disconnected() {
+        // Two separate grids side by side
+        let size = 3;
+        let mut graph = Graph::new();
+        for offset in [0.0, 10.0] {
+            for y in 0..size {
+                for x in 0..size {
+                    graph.add_node((x as f32 + offset, y as f32));
+                }
+            }
+        }
+        for grid in 0..2 {
+            for y in 0..size {
+                for x in 0..size {
+                    let node_id = grid * size * size + x + y * size;
+                    if x < size - 1 {
+                        graph.add_edge(GraphNodeId(node_id), GraphNodeId(node_id + 1), 1.0);
+                        graph.add_edge(GraphNodeId(node_id + 1), GraphNodeId(node_id), 1.0);
+                    }
+                    if y < size - 1 {
+                        graph.add_edge(GraphNodeId(node_id), GraphNodeId(node_id + size), 1.0);
+                        graph.add_edge(GraphNodeId(node_id + size), GraphNodeId(node_id), 1.0);
+                    }
+                }
+            }
+        }
+        assert!(graph.connected(GraphNodeId(0), GraphNodeId(size * size - 1)));
+        assert!(!graph.connected(GraphNodeId(0), GraphNodeId(size * size)));
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        let mut config = get_config(
+            &transition_system,
+            vec![task(0, 8), task(1, 12), task(9, 17)],
+        );
+        config.set_check_connectivity(true);
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        match solver.solve(&config) {
+            SolveOutcome::Disconnected(agent) => assert_eq!(agent, 1),
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+
+        // No search has been performed
+        let stats = solver.get_stats();
+        assert_eq!(stats.expanded, 0);
+        assert_eq!(stats.lsipp_stats.searches, 0);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_seed() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let tasks = get_crossing_tasks();
+
+        let run = |seed| {
+            let mut config = get_config(&transition_system, tasks.clone());
+            config.set_seed(seed);
+            let mut solver = ConflictBasedSearch::new(transition_system.clone());
+            let mut sequence = vec![];
+            while let Some(node) = solver.solve_iter(&config) {
+                let constraint = node
+                    .constraint
+                    .as_ref()
+                    .map(|c| (c.agent, c.state.clone(), c.interval));
+                sequence.push((node.total_cost, constraint, normalize(&node.conflicts)));
+                if node.conflicts.is_empty() {
+                    break;
+                }
+            }
+            sequence
+        };
+
+        assert_eq!(run(0), run(0));
+        assert_eq!(run(42), run(42));
+
+        // The ties between conflicts are broken differently by different seeds
+        let sequences = (0..10).map(run).collect::<Vec<_>>();
+        assert!(sequences.iter().any(|sequence| *sequence != sequences[0]));
+
+        // A whole search on all the available threads is reproduced as well
+        let solve = |seed| {
+            let mut config = get_config(&transition_system, tasks.clone());
+            config.set_seed(seed);
+            let mut solver = ConflictBasedSearch::new(transition_system.clone());
+            let solutions = solver.solve(&config).unwrap();
+            let stats = solver.get_stats();
+            (
+                solutions
+                    .iter()
+                    .map(|s| {
+                        s.steps
+                            .iter()
+                            .map(|(s, t)| (s.internal_state.clone(), *t))
+                            .collect()
+                    })
+                    .collect::<Vec<Vec<_>>>(),
+                stats.expanded,
+                stats.generated,
+            )
+        };
+        for seed in 0..10 {
+            assert_eq!(solve(seed), solve(seed));
+        }
+    }
+
+    #[test]
+    fn test_replan_from() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        // Both agents cross the center of the grid
+        let config = get_config(
+            &transition_system,
+            vec![task(2 * size, 3 * size - 1), task(2, 2 + size * (size - 1))],
+        );
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+
+        // Execution is interrupted while the agents may be partway along their edges
+        let now = OrderedFloat(1.5);
+        let current = solutions
+            .iter()
+            .map(|solution| {
+                let i = solution.steps.iter().rposition(|(_, t)| *t <= now).unwrap();
+                match solution.steps.get(i + 1) {
+                    Some((next, arrival)) if solution.actions[i].action.is_some() => {
+                        ExecutionState {
+                            from: solution.steps[i].0.internal_state.clone(),
+                            state: next.internal_state.clone(),
+                            time: now,
+                            residual: *arrival - now,
+                        }
+                    }
+                    _ => ExecutionState {
+                        from: solution.steps[i].0.internal_state.clone(),
+                        state: solution.steps[i].0.internal_state.clone(),
+                        time: now,
+                        residual: OrderedFloat(0.0),
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+        assert!(current.iter().any(|c| c.residual > OrderedFloat(0.0)));
+
+        // The first agent now heads to the initial state of the second one
+        let mut config = get_config(
+            &transition_system,
+            vec![task(2 * size, 2), task(2, 2 + size * (size - 1))],
+        );
+        let solutions = solver.replan_from(&current, &mut config).unwrap();
+
+        for (solution, current) in solutions.iter().zip(current.iter()) {
+            assert_eq!(solution.steps[0].0.internal_state, current.state);
+            assert_eq!(solution.steps[0].1, current.time + current.residual);
+        }
+        assert_eq!(
+            solutions[0].steps.last().unwrap().0.internal_state,
+            SimpleState(GraphNodeId(2))
+        );
+
+        let time_base = ScaledTimeBase::new(1.0);
+        let moves = T2(
+            solutions[0].to_common_moves(0, &time_base),
+            solutions[1].to_common_moves(1, &time_base),
+        );
+        assert!(find_conflict_on_common_timeline(
+            &*transition_system,
+            T2(&moves.0, &moves.1),
+            OrderedFloat(0.0)
+        )
+        .is_none());
+
+        // The configuration is left unchanged, so that it is solved again from the initial states
+        assert!(config.reservations.is_empty());
+        assert!(config.reserved_actions.is_empty());
+        let solutions = solver.solve(&config).unwrap();
+        assert_eq!(
+            solutions[0].steps[0].0.internal_state,
+            SimpleState(GraphNodeId(2 * size))
+        );
+        assert_eq!(solutions[0].steps[0].1, OrderedFloat(0.0));
+    }
+
+    #[test]
+    fn test_replan_from_edge() {
+        // Corridor with a long edge in its middle
+        let mut graph = Graph::new();
+        for x in [0.0, 1.0, 4.0, 5.0] {
+            graph.add_node((x, 0.0));
+        }
+        for x in 1..4 {
+            graph.add_edge(GraphNodeId(x - 1), GraphNodeId(x), 1.0);
+            graph.add_edge(GraphNodeId(x), GraphNodeId(x - 1), 1.0);
+        }
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        let mut config = get_config(&transition_system, vec![task(1, 3), task(1, 2)]);
+
+        // The first agent is one unit away from the end of the long edge, the second one waits at its start
+        let current = vec![
+            ExecutionState {
+                from: SimpleState(GraphNodeId(1)),
+                state: SimpleState(GraphNodeId(2)),
+                time: OrderedFloat(0.0),
+                residual: OrderedFloat(1.0),
+            },
+            ExecutionState {
+                from: SimpleState(GraphNodeId(1)),
+                state: SimpleState(GraphNodeId(1)),
+                time: OrderedFloat(0.0),
+                residual: OrderedFloat(0.0),
+            },
+        ];
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.replan_from(&current, &mut config).unwrap();
+
+        // The second agent only enters the edge once the first one has left it
+        let entry = solutions[1]
+            .steps
+            .windows(2)
+            .find(|w| w[1].0.internal_state == SimpleState(GraphNodeId(2)))
+            .unwrap()[0]
+            .1;
+        assert!(entry >= OrderedFloat(1.0));
+        assert!(config.reserved_actions.is_empty());
+    }
+
+    #[test]
+    fn test_time_bases() {
+        // Horizontal line crossed in its middle by a much longer vertical line
+        let mut graph = Graph::new();
+        for x in 0..5 {
+            graph.add_node((x as f32, 0.0));
+        }
+        let mut vertical = vec![];
+        for y in -20..=1 {
+            if y == 0 {
+                vertical.push(GraphNodeId(2));
+            } else {
+                vertical.push(graph.add_node((2.0, y as f32)));
+            }
+        }
+        for x in 1..5 {
+            graph.add_edge(GraphNodeId(x - 1), GraphNodeId(x), 1.0);
+            graph.add_edge(GraphNodeId(x), GraphNodeId(x - 1), 1.0);
+        }
+        for w in vertical.windows(2) {
+            graph.add_edge(w[0], w[1], 1.0);
+            graph.add_edge(w[1], w[0], 1.0);
+        }
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        let tasks = vec![task(0, 4), task(vertical[0].0, vertical.last().unwrap().0)];
+
+        // The first agent plans in seconds and the second one in ticks of a tenth of a second,
+        // whose times are only compared up to half a tick
+        let seconds = ScaledTimeBase::new(1.0);
+        let ticks = ScaledTimeBase::new(0.1);
+        let tolerance = OrderedFloat(0.05);
+
+        let find_conflict = |solutions: &[Solution<_, _, _, _>]| {
+            let moves = T2(
+                solutions[0].to_common_moves(0, &seconds),
+                solutions[1].to_common_moves(1, &ticks),
+            );
+            find_conflict_on_common_timeline(&*transition_system, T2(&moves.0, &moves.1), tolerance)
+        };
+
+        // Read in the same unit, the second agent crosses long after the first one has passed,
+        // but both reach the crossing after two seconds on the common timeline
+        let config = get_config(&transition_system, tasks.clone());
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+        assert!((solutions[0].cost.0 - 4.0).abs() < 1e-3);
+        assert!((solutions[1].cost.0 - 21.0).abs() < 1e-3);
+        let conflict = find_conflict(&solutions).unwrap();
+        assert_eq!(conflict.1.to, SimpleState(GraphNodeId(2)));
+        assert!((conflict.1.interval.start.0 - 1.9).abs() < 1e-4);
+
+        // Detecting the conflicts on the common timeline delays one of the agents
+        let mut config = get_config(&transition_system, tasks);
+        config.set_time_bases(vec![Arc::new(seconds), Arc::new(ticks)], tolerance);
+        let solutions = solver.solve(&config).unwrap();
+        assert!(find_conflict(&solutions).is_none());
+        assert!(solutions.iter().map(|s| s.cost).sum::<MyTime>() > OrderedFloat(25.0));
+
+        // The constraints on the common timeline are converted into the time base of each agent
+        // and extended by the tolerance
+        let time_bases = config.time_bases.as_ref().unwrap().0.clone();
+        let convert = |agent, end| {
+            let constraint = Constraint::new_state_constraint(
+                agent,
+                SimpleState(GraphNodeId(2)),
+                Interval::new(OrderedFloat(1.5), end),
+            );
+            ConflictBasedSearch::<SimpleWorld, _, GraphEdgeId, _, _, SimpleHeuristic>::to_native_constraint(
+                &time_bases,
+                tolerance,
+                constraint,
+            )
+            .interval
+        };
+        let interval = convert(0, OrderedFloat(2.3));
+        assert!((interval.start.0 - 1.5).abs() < 1e-4);
+        assert!((interval.end.0 - 2.35).abs() < 1e-4);
+        let interval = convert(1, OrderedFloat(2.3));
+        assert!((interval.start.0 - 15.0).abs() < 1e-4);
+        assert!((interval.end.0 - 23.5).abs() < 1e-4);
+        assert_eq!(convert(1, MyTime::max_value()).end, MyTime::max_value());
+    }
+
+    #[test]
+    fn test_agent_weights() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        // Both agents must cross the center of the grid at the same time along their shortest paths
+        let tasks = vec![task(2 * size, 3 * size - 1), task(2, 2 + size * (size - 1))];
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let unweighted = solver
+            .solve(&get_config(&transition_system, tasks.clone()))
+            .unwrap();
+        let optimal = unweighted[0].cost + unweighted[1].cost;
+
+        for heavy in 0..2 {
+            let mut config = get_config(&transition_system, tasks.clone());
+            config.set_agent_weight(heavy, 10.0);
+            let solutions = solver.solve(&config).unwrap();
+
+            // The heavy agent goes straight while the light one gives way
+            assert_eq!(solutions[heavy].cost, OrderedFloat(4.0));
+            assert!(solutions[1 - heavy].cost > OrderedFloat(4.0));
+            assert!((solutions[0].cost + solutions[1].cost - optimal).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_cache_solutions() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let tasks = get_crossing_tasks();
+
+        let solve = |cache_solutions: bool| {
+            let mut config = get_config(&transition_system, tasks.clone());
+            config.set_cache_solutions(cache_solutions);
+            let mut solver = ConflictBasedSearch::new(transition_system.clone());
+            solver.n_threads = 1;
+            let solutions = solver.solve(&config).unwrap();
+            (solutions, solver.get_stats())
+        };
+
+        let (cached, cached_stats) = solve(true);
+        let (uncached, uncached_stats) = solve(false);
+        // The same splits are made in different orders along different branches
+        assert!(cached_stats.cache_hits > 0);
+        assert_eq!(
+            cached_stats.lsipp_stats.searches + cached_stats.cache_hits,
+            uncached_stats.lsipp_stats.searches
+        );
+        assert_eq!(cached_stats.expanded, uncached_stats.expanded);
+        for (a, b) in cached.iter().zip(uncached.iter()) {
+            assert!(a.structurally_eq(b, OrderedFloat(1e-6)));
+        }
+    }
+
+    #[test]
+    fn test_resolutions() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let config = get_config(
+            &transition_system,
+            vec![
+                task(2 * size, 3 * size - 1),
+                task(2, 2 + size * (size - 1)),
+                task(0, size * size - 1),
+            ],
+        );
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        assert!(solver.get_resolutions().is_none());
+        solver.solve(&config).unwrap();
+        let resolutions = solver.get_resolutions().unwrap();
+
+        let best = solver.shared.critical.lock().best.clone().unwrap();
+        assert!(!resolutions.is_empty());
+        assert_eq!(resolutions.len(), best.depth);
+
+        // Each resolution is made by a constraint of the branch, in order from the root,
+        // on one of the agents of the conflict that was detected in the parent node
+        let mut branch = vec![];
+        let mut current = best.as_ref();
+        while let Some(parent) = &current.parent {
+            branch.push((
+                current.constraint.clone().unwrap(),
+                parent.conflicts.clone(),
+            ));
+            current = parent;
+        }
+        branch.reverse();
+
+        for (resolution, (constraint, conflicts)) in resolutions.iter().zip(branch.iter()) {
+            assert!(Arc::ptr_eq(&resolution.constraint, constraint));
+            assert_eq!(resolution.agent, constraint.agent);
+            assert!(conflicts
+                .iter()
+                .any(|conflict| Arc::ptr_eq(conflict, &resolution.conflict)));
+            assert!(
+                resolution.conflict.moves.0.agent == resolution.agent
+                    || resolution.conflict.moves.1.agent == resolution.agent
+            );
+        }
+    }
+
+    #[test]
+    fn test_edge_capacity() {
+        // Single corridor along which three agents follow each other
+        let length = 8;
+        let mut graph = Graph::new();
+        for x in 0..length {
+            graph.add_node((x as f32, 0.0));
+        }
+        for x in 1..length {
+            graph.add_edge(GraphNodeId(x - 1), GraphNodeId(x), 1.0);
+            graph.add_edge(GraphNodeId(x), GraphNodeId(x - 1), 1.0);
+        }
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        let mut config = get_config(&transition_system, vec![task(0, 5), task(1, 6), task(2, 7)]);
+
+        // Without capacity, the agents follow each other closely
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+        assert!(solutions.iter().all(|s| s.cost == OrderedFloat(5.0)));
+
+        // Only two agents may enter the middle edge within three time units
+        let edge = (SimpleState(GraphNodeId(3)), SimpleState(GraphNodeId(4)));
+        config.set_edge_capacity(edge.0.clone(), edge.1.clone(), 2, OrderedFloat(3.0));
+        let solutions = solver.solve(&config).unwrap();
+
+        // One agent is staggered until the window of the first one is over
+        let delayed = solutions
+            .iter()
+            .filter(|s| s.cost > OrderedFloat(5.0))
+            .collect::<Vec<_>>();
+        assert_eq!(delayed.len(), 1);
+        assert!((delayed[0].cost.0 - 6.0).abs() < 1e-3);
+
+        let mut entries = solutions
+            .iter()
+            .flat_map(|s| {
+                s.steps.windows(2).filter_map(|w| {
+                    (w[0].0.internal_state == edge.0 && w[1].0.internal_state == edge.1)
+                        .then_some(w[0].1)
+                })
+            })
+            .collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(entries.len(), 3);
+        assert!(entries[2] >= entries[0] + OrderedFloat(3.0));
+    }
+
+    #[test]
+    fn test_edge_capacity_middle_agent() {
+        // Corridor with a longer bypass from the third cell to a cell next to its middle
+        let length = 9;
+        let mut graph = Graph::new();
+        for x in 0..length {
+            graph.add_node((x as f32, 0.0));
+        }
+        graph.add_node((2.0, -2.0));
+        graph.add_node((4.0, -1.0));
+        for x in 1..length {
+            graph.add_edge(GraphNodeId(x - 1), GraphNodeId(x), 1.0);
+            graph.add_edge(GraphNodeId(x), GraphNodeId(x - 1), 1.0);
+        }
+        for (from, to) in [(2, 9), (9, 10), (4, 10)] {
+            graph.add_edge(GraphNodeId(from), GraphNodeId(to), 1.0);
+            graph.add_edge(GraphNodeId(to), GraphNodeId(from), 1.0);
+        }
+        let transition_system = Arc::new(SimpleWorld::new(Arc::new(graph), 0.4));
+
+        let mut config = get_config(
+            &transition_system,
+            vec![task(3, 8), task(2, 10), task(0, 7)],
+        );
+
+        // Only two agents may enter the middle edge within five time units
+        let edge = (SimpleState(GraphNodeId(3)), SimpleState(GraphNodeId(4)));
+        config.set_edge_capacity(edge.0.clone(), edge.1.clone(), 2, OrderedFloat(5.0));
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+
+        // The agent in the middle of the queue takes the bypass, which delays it less
+        // than the last agent would be delayed by waiting for the window to be over
+        assert!((solutions[0].cost.0 - 5.0).abs() < 1e-3);
+        assert!((solutions[1].cost.0 - (2.0 + 5.0f32.sqrt())).abs() < 1e-3);
+        assert!((solutions[2].cost.0 - 7.0).abs() < 1e-3);
+        assert!(solutions[1]
+            .steps
+            .iter()
+            .any(|(state, _)| state.internal_state == SimpleState(GraphNodeId(9))));
+    }
+
+    #[test]
+    fn test_suggest_priority_order() {
+        let size = 7;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph.clone(), 0.4));
+
+        // The first agent crosses the grid and the lines of all the other agents,
+        // which only cross the line of the first one
+        let config = get_config(
+            &transition_system,
+            vec![
+                task(3, 3 + 6 * size),
+                task(1 + size, 4 + size),
+                task(2 + 3 * size, 6 + 3 * size),
+                task(5 * size, 5 + 5 * size),
+                task(6 + 6 * size, 6 + 5 * size),
+            ],
+        );
+
+        let order = config.suggest_priority_order(|state| graph.get_node(state.0).data);
+        assert_eq!(order.len(), 5);
+        assert_eq!(order[0], 0);
+
+        // The agents that cross the first one come next, by decreasing length, then the last one
+        assert_eq!(order[1..], [3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn test_agent_stats() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        // The last agent crosses the grid and the paths of the other ones
+        let config = get_config(
+            &transition_system,
+            vec![
+                task(4 * size, 4 * size + 2),
+                task(6 * size + 3, 6 * size + 1),
+                task(0, size * size - 1),
+            ],
+        );
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        solver.solve(&config).unwrap();
+        let stats = solver.get_stats();
+        let agents = solver.get_agent_stats();
+
+        assert_eq!(agents.len(), 3);
+        assert_eq!(
+            agents.iter().map(|a| a.searches).sum::<usize>(),
+            stats.lsipp_stats.searches
+        );
+        assert_eq!(
+            agents.iter().map(|a| a.expanded).sum::<usize>(),
+            stats.lsipp_stats.sipp_stats.expanded
+        );
+        assert!(agents.iter().all(|a| a.searches > 0));
+
+        let bottleneck = (0..3).max_by_key(|a| agents[*a].expanded).unwrap();
+        assert_eq!(bottleneck, 2);
+    }
+
+    #[test]
+    fn test_explain_infeasibility() {
+        let size = 3;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let task = |from, to| {
+            Arc::new(Task::new_with_deadline(
+                SimpleState(GraphNodeId(from)),
+                SimpleState(GraphNodeId(to)),
+                OrderedFloat(0.0),
+                OrderedFloat(10.0),
+            ))
+        };
+
+        // Both agents swap along a corridor, all the other cells are reserved
+        let mut config = get_config(&transition_system, vec![task(0, 2), task(2, 0)]);
+        for node in 3..size * size {
+            config.add_reservation(SimpleState(GraphNodeId(node)), Interval::default());
+        }
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        match solver.solve(&config) {
+            SolveOutcome::Infeasible => {}
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+
+        // Freeing the cell next to the middle of the corridor is enough to let the agents pass
+        let certificate = solver.explain_infeasibility(&mut config).unwrap();
+        assert_eq!(
+            certificate,
+            vec![(SimpleState(GraphNodeId(4)), Interval::default())]
+        );
+        assert_eq!(config.reservations.len(), size * size - 3);
+
+        config
+            .reservations
+            .retain(|(state, interval, _)| !certificate.contains(&(state.clone(), *interval)));
+        assert!(solver.solve(&config).solutions().is_some());
+        assert!(solver.explain_infeasibility(&mut config).is_none());
+    }
+
+    #[test]
+    fn test_lower_bound_increase() {
+        let size = 7;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let mut config = get_config(
+            &transition_system,
+            vec![task(2, 30), task(14, 18), task(19, 47), task(37, 41)],
+        );
+        config.set_agent_weight(2, 3.0);
+        config.set_agent_weight(3, 3.0);
+
+        // Both pairs of agents cross in the middle of their straight paths, so that one agent of each pair
+        // must wait until the other one has cleared the crossing, the second pair costs three times more
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let root = solver.solve_iter(&config).unwrap();
+        assert_eq!(root.conflicts.len(), 2);
+
+        let increases = root
+            .conflicts
+            .iter()
+            .map(|conflict| {
+                // The increases are recorded when the conflicts are classified
+                let increases = conflict.increases.unwrap();
+                assert_eq!(
+                    solver.get_lower_bound_increases(&config, &root, conflict),
+                    increases
+                );
+                increases.0.unwrap().min(increases.1.unwrap()).0
+            })
+            .collect::<Vec<_>>();
+        let expected = |conflict: &Conflict<_, _, _, _>| {
+            if conflict.moves.0.agent < 2 {
+                0.8
+            } else {
+                2.4
+            }
+        };
+        for (conflict, increase) in root.conflicts.iter().zip(increases) {
+            assert!((increase - expected(conflict)).abs() < 1e-4);
+        }
+
+        // The priority of the conflicts favors the earlier crossing
+        let default = root.conflicts.iter().min().unwrap();
+        assert_eq!((default.moves.0.agent, default.moves.1.agent), (0, 1));
+        let selected = solver
+            .select_conflict_by_lower_bound_increase(&config, &root)
+            .unwrap();
+        assert_eq!((selected.moves.0.agent, selected.moves.1.agent), (2, 3));
+
+        // The selection of the conflicts does not affect the cost of the solution
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let cost = solver
+            .solve(&config)
+            .unwrap()
+            .iter()
+            .map(|s| s.cost)
+            .sum::<MyTime>();
+        config.set_select_by_lower_bound_increase(true);
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let selected_cost = solver
+            .solve(&config)
+            .unwrap()
+            .iter()
+            .map(|s| s.cost)
+            .sum::<MyTime>();
+        assert!((cost.0 - selected_cost.0).abs() < 1e-4);
+    }
 }
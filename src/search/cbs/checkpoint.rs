@@ -0,0 +1,232 @@
+use std::fmt::Debug;
+
+use tuple::T2;
+
+use crate::{
+    AgentStats, CbsStats, Conflict, ConflictType, Constraint, LimitValues, Move, SippState,
+    Solution,
+};
+
+/// Resumable state of a Conflict-Based Search, extracted with `ConflictBasedSearch::checkpoint`
+/// and restored with `ConflictBasedSearch::from_checkpoint`, e.g. to continue a search after a restart.
+/// The search tree is flattened into a list of nodes that refer to their parent by index,
+/// so that the ancestors shared by many open nodes are only stored once.
+/// The checkpoint can be persisted with the `serde` feature.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CbsCheckpoint<S, A, C, DC>
+where
+    S: Debug + Eq,
+    C: Ord + Default + LimitValues,
+    DC: Ord + Default,
+{
+    /// Nodes that are open or that lead to an open node or to the best node, each one after its parent.
+    pub(crate) nodes: Vec<NodeRecord<S, A, C, DC>>,
+    /// Index of each open node, along with its insertion counter that breaks ties in the open list.
+    pub(crate) open: Vec<(usize, usize)>,
+    pub(crate) counter: usize,
+    pub(crate) best: Option<usize>,
+    pub(crate) stats: CbsStats,
+    pub(crate) agent_stats: Vec<AgentStats>,
+}
+
+impl<S, A, C, DC> CbsCheckpoint<S, A, C, DC>
+where
+    S: Debug + Eq,
+    C: Ord + Default + LimitValues,
+    DC: Ord + Default,
+{
+    /// Returns the number of open nodes left to explore.
+    pub fn n_open(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Returns the statistics of the search up to the checkpoint.
+    pub fn get_stats(&self) -> &CbsStats {
+        &self.stats
+    }
+}
+
+/// Node of the search tree whose parent is given by its index in the checkpoint.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct NodeRecord<S, A, C, DC>
+where
+    S: Debug + Eq,
+    C: Ord + Default + LimitValues,
+    DC: Ord + Default,
+{
+    pub(crate) parent: Option<usize>,
+    pub(crate) total_cost: DC,
+    pub(crate) solutions: Vec<Solution<SippState<S, C>, A, C, DC>>,
+    pub(crate) conflicts: Vec<ConflictRecord<S, A, C, DC>>,
+    pub(crate) constraint: Option<Constraint<S, C>>,
+    pub(crate) resolved: Option<ConflictRecord<S, A, C, DC>>,
+    pub(crate) landmark: Option<(Constraint<S, C>, Constraint<S, C>)>,
+    pub(crate) preferred: bool,
+    pub(crate) depth: usize,
+}
+
+/// Conflict of a node of the search tree, whose moves are stored as a plain pair.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct ConflictRecord<S, A, C, DC>
+where
+    C: Ord + LimitValues,
+{
+    pub(crate) moves: (Move<S, A, C>, Move<S, A, C>),
+    pub(crate) type_: ConflictType,
+    pub(crate) overcost: DC,
+    pub(crate) window: Option<DC>,
+    pub(crate) increases: Option<(Option<DC>, Option<DC>)>,
+}
+
+impl<S, A, C, DC> From<&Conflict<S, A, C, DC>> for ConflictRecord<S, A, C, DC>
+where
+    S: Clone,
+    A: Clone,
+    C: Ord + LimitValues + Copy,
+    DC: Ord + Default + Copy,
+{
+    fn from(conflict: &Conflict<S, A, C, DC>) -> Self {
+        Self {
+            moves: (conflict.moves.0.clone(), conflict.moves.1.clone()),
+            type_: conflict.type_,
+            overcost: conflict.overcost,
+            window: conflict.window,
+            increases: conflict
+                .increases
+                .map(|increases| (increases.0, increases.1)),
+        }
+    }
+}
+
+impl<S, A, C, DC> From<ConflictRecord<S, A, C, DC>> for Conflict<S, A, C, DC>
+where
+    C: Ord + LimitValues,
+    DC: Ord + Default,
+{
+    fn from(record: ConflictRecord<S, A, C, DC>) -> Self {
+        Self {
+            moves: T2(record.moves.0, record.moves.1),
+            type_: record.type_,
+            overcost: record.overcost,
+            window: record.window,
+            increases: record
+                .increases
+                .map(|increases| T2(increases.0, increases.1)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, CbsConfig, CbsNode, ConflictBasedSearch, GraphEdgeId, GraphNodeId, MyTime,
+        ReverseResumableAStar, SimpleHeuristic, SimpleState, SimpleWorld, Task,
+    };
+
+    type Plan = Vec<(MyTime, Vec<(SimpleState, MyTime)>, Vec<Option<GraphEdgeId>>)>;
+
+    fn get_plan(node: &CbsNode<SimpleState, GraphEdgeId, MyTime, MyTime>, n_agents: usize) -> Plan {
+        node.get_solutions(n_agents)
+            .iter()
+            .map(|solution| {
+                (
+                    solution.cost,
+                    solution
+                        .steps
+                        .iter()
+                        .map(|(state, time)| (state.internal_state.clone(), *time))
+                        .collect(),
+                    solution
+                        .actions
+                        .iter()
+                        .map(|action| action.action)
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let get_config = || {
+            let tasks = [(16, 9), (17, 8), (22, 4), (11, 13)]
+                .iter()
+                .map(|(from, to)| {
+                    Arc::new(Task::new(
+                        SimpleState(GraphNodeId(*from)),
+                        SimpleState(GraphNodeId(*to)),
+                        OrderedFloat(0.0),
+                    ))
+                })
+                .collect::<Vec<_>>();
+            let pivots = Arc::new(tasks.iter().map(|t| t.goal_state.clone()).collect());
+            let heuristic_to_pivots = Arc::new(
+                tasks
+                    .iter()
+                    .map(|t| {
+                        Arc::new(ReverseResumableAStar::new(
+                            transition_system.clone(),
+                            t.clone(),
+                            SimpleHeuristic::new(transition_system.clone(), Arc::new(t.reverse())),
+                        ))
+                    })
+                    .collect(),
+            );
+            CbsConfig::new(tasks, pivots, heuristic_to_pivots, OrderedFloat(1e-6))
+        };
+
+        // Uninterrupted run
+        let config = get_config();
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let mut iterations = 0;
+        let expected = loop {
+            let node = solver.solve_iter(&config).unwrap();
+            iterations += 1;
+            if node.conflicts.is_empty() {
+                break get_plan(&node, config.n_agents);
+            }
+        };
+        assert!(iterations > 2);
+
+        // Run interrupted halfway and resumed from a checkpoint, persisted with the serde feature as after a restart
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        for _ in 0..iterations / 2 {
+            assert!(!solver.solve_iter(&config).unwrap().conflicts.is_empty());
+        }
+        let checkpoint = solver.checkpoint();
+        assert!(checkpoint.n_open() > 0);
+        assert_eq!(checkpoint.get_stats().expanded, iterations / 2);
+
+        #[cfg(feature = "serde")]
+        let checkpoint: crate::CbsCheckpoint<SimpleState, GraphEdgeId, MyTime, MyTime> = {
+            let json = serde_json::to_string(&checkpoint).unwrap();
+            serde_json::from_str(&json).unwrap()
+        };
+
+        let config = get_config();
+        let mut solver =
+            ConflictBasedSearch::from_checkpoint(transition_system.clone(), checkpoint);
+        let mut resumed = iterations / 2;
+        let solution = loop {
+            let node = solver.solve_iter(&config).unwrap();
+            resumed += 1;
+            if node.conflicts.is_empty() {
+                break get_plan(&node, config.n_agents);
+            }
+        };
+
+        assert_eq!(resumed, iterations);
+        assert_eq!(solution, expected);
+    }
+}
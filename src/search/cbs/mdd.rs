@@ -0,0 +1,141 @@
+use std::{fmt::Debug, hash::Hash, ops::Add};
+
+use fxhash::{FxHashMap, FxHashSet};
+
+use crate::{Conflict, ConflictType, LimitValues, Move, State, Task, TransitionSystem};
+
+/// Multi-valued decision diagram that compactly represents all the paths of an agent
+/// that reach its goal at a given time without waiting, which are all its optimal paths
+/// when the agent is not constrained. The nodes are given by a state and the time it is reached.
+#[derive(Debug)]
+pub struct Mdd<S, C>
+where
+    S: Eq + Hash,
+    C: Eq + Hash,
+{
+    successors: FxHashMap<(S, C), Vec<(S, C)>>,
+    goals: Vec<(S, C)>,
+}
+
+impl<S, C> Mdd<S, C>
+where
+    S: Debug + State + Eq + Hash + Clone,
+    C: Debug + Hash + Eq + Ord + Copy + LimitValues,
+{
+    /// Builds the diagram of the paths that solve the given task and reach the goal at the given time.
+    pub fn new<TS, A, DC>(transition_system: &TS, task: &Task<S, C>, cost: C) -> Self
+    where
+        TS: TransitionSystem<S, A, C, DC>,
+        C: Add<DC, Output = C>,
+    {
+        let mut successors: FxHashMap<(S, C), Vec<(S, C)>> = FxHashMap::default();
+        let mut predecessors: FxHashMap<(S, C), Vec<(S, C)>> = FxHashMap::default();
+        let mut goals = vec![];
+
+        // Generate all the nodes that can be reached before the given time
+        let root = (task.initial_state.clone(), task.initial_cost);
+        let mut stack = vec![root.clone()];
+        successors.insert(root, vec![]);
+        while let Some((state, time)) = stack.pop() {
+            if time == cost && task.is_goal_state(&state) {
+                goals.push((state.clone(), time));
+            }
+
+            for action in transition_system.actions_from(&state) {
+                let next = (
+                    transition_system.transition(&state, action),
+                    time + transition_system.transition_cost(&state, action),
+                );
+                if next.1 > cost {
+                    continue;
+                }
+
+                predecessors
+                    .entry(next.clone())
+                    .or_default()
+                    .push((state.clone(), time));
+                if !successors.contains_key(&next) {
+                    successors.insert(next.clone(), vec![]);
+                    stack.push(next);
+                }
+            }
+        }
+
+        // Keep only the nodes that lead to a goal node
+        let mut useful = FxHashSet::default();
+        let mut stack = goals.clone();
+        useful.extend(goals.iter().cloned());
+        while let Some(node) = stack.pop() {
+            for parent in predecessors.get(&node).into_iter().flatten() {
+                successors.get_mut(parent).unwrap().push(node.clone());
+                if useful.insert(parent.clone()) {
+                    stack.push(parent.clone());
+                }
+            }
+        }
+        successors.retain(|node, _| useful.contains(node));
+
+        Self { successors, goals }
+    }
+
+    /// Returns true if the diagram does not contain any path.
+    pub fn is_empty(&self) -> bool {
+        self.goals.is_empty()
+    }
+
+    /// Returns the states that the agent can occupy at exactly the given time.
+    pub fn get_level(&self, time: C) -> Vec<&S> {
+        self.successors
+            .keys()
+            .filter(|(_, t)| *t == time)
+            .map(|(s, _)| s)
+            .collect()
+    }
+
+    /// Returns true if all the paths visit the given state at the given time,
+    /// i.e. the level is of width one and no path is in transit at that time.
+    pub fn is_singleton(&self, state: &S, time: C) -> bool {
+        let level = self.get_level(time);
+        level.len() == 1
+            && level[0] == state
+            && self.successors.iter().all(|((_, start), successors)| {
+                *start >= time || successors.iter().all(|(_, end)| *end <= time)
+            })
+    }
+
+    /// Returns true if all the paths perform the given move.
+    pub fn is_forced<A>(&self, m: &Move<S, A, C>) -> bool {
+        if m.interval.end == C::max_value() {
+            // The agent stays at its goal
+            return self.goals.len() == 1
+                && self.goals[0].0 == m.from
+                && self.goals[0].1 <= m.interval.start;
+        }
+
+        let from = (m.from.clone(), m.interval.start);
+        let to = (m.to.clone(), m.interval.end);
+        self.is_singleton(&from.0, from.1)
+            && self.is_singleton(&to.0, to.1)
+            && self.successors.get(&from) == Some(&vec![to])
+    }
+
+    /// Classifies a conflict between two agents whose paths are optimal and unconstrained,
+    /// using only their diagrams: a conflict is cardinal if both agents are forced to perform
+    /// the conflicting moves, and semi-cardinal if only one of them is.
+    /// An agent staying at its goal is forced to stay there if all its paths reach the goal by then,
+    /// and a conflict with such an agent that is not cardinal is goal-blocking.
+    pub fn classify<A, DC>(mdds: (&Self, &Self), conflict: &Conflict<S, A, C, DC>) -> ConflictType
+    where
+        DC: Ord + Default,
+    {
+        match (
+            mdds.0.is_forced(&conflict.moves.0),
+            mdds.1.is_forced(&conflict.moves.1),
+        ) {
+            (true, true) => ConflictType::Cardinal,
+            _ if conflict.is_goal_blocking() => ConflictType::GoalBlocking,
+            (true, false) | (false, true) => ConflictType::SemiCardinal,
+            (false, false) => ConflictType::NonCardinal,
+        }
+    }
+}
@@ -1,6 +1,12 @@
 mod cbs;
+mod checkpoint;
+mod mdd;
+mod snapshot;
 
 pub use cbs::*;
+pub use checkpoint::*;
+pub use mdd::*;
+pub use snapshot::*;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,230 @@
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+};
+
+use crate::{CbsNode, Constraint, Interval, LimitValues, Move, State};
+
+/// Read-only view of a node of the Conflict-Based Search tree, e.g. to log the progress of the search.
+/// The states and times are formatted with their `Debug` representation, so that the snapshot
+/// can be displayed, and serialized with the `serde` feature, whatever the types of the transition system.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DebugSnapshot {
+    pub cost: String,
+    pub depth: usize,
+    pub agents: Vec<AgentSnapshot>,
+    pub conflicts: Vec<ConflictSnapshot>,
+}
+
+/// Constraints and landmarks imposed on an agent in a node of the search tree.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AgentSnapshot {
+    pub agent: usize,
+    pub constraints: Vec<String>,
+    pub landmarks: Vec<String>,
+}
+
+/// Conflict remaining between two agents in a node of the search tree.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConflictSnapshot {
+    pub agents: (usize, usize),
+    pub type_: String,
+    pub moves: (String, String),
+}
+
+impl<S, A, C, DC> CbsNode<S, A, C, DC>
+where
+    S: Debug + State + Eq + Hash + Clone,
+    C: Debug + Ord + Default + LimitValues + Copy,
+    DC: Debug + PartialEq + Eq + PartialOrd + Ord + Default + Copy,
+{
+    /// Returns a snapshot of the cost of the node, of the constraints and landmarks
+    /// of each of the given number of agents, and of the remaining conflicts.
+    pub fn get_debug_snapshot(&self, n_agents: usize) -> DebugSnapshot {
+        let agents = (0..n_agents)
+            .map(|agent| {
+                let (constraints, landmarks) = self.get_constraints(agent);
+
+                let mut constraints = constraints
+                    .state_constraints
+                    .values()
+                    .chain(constraints.action_constraints.values())
+                    .flatten()
+                    .map(format_constraint)
+                    .collect::<Vec<_>>();
+                constraints.sort_unstable();
+
+                AgentSnapshot {
+                    agent,
+                    constraints,
+                    landmarks: landmarks.iter().map(|l| format_constraint(l)).collect(),
+                }
+            })
+            .collect();
+
+        let conflicts = self
+            .conflicts
+            .iter()
+            .map(|conflict| ConflictSnapshot {
+                agents: (conflict.moves.0.agent, conflict.moves.1.agent),
+                type_: format!("{:?}", conflict.type_),
+                moves: (
+                    format_move(&conflict.moves.0),
+                    format_move(&conflict.moves.1),
+                ),
+            })
+            .collect();
+
+        DebugSnapshot {
+            cost: format!("{:?}", self.total_cost),
+            depth: self.depth,
+            agents,
+            conflicts,
+        }
+    }
+}
+
+fn format_interval<C>(interval: &Interval<C>) -> String
+where
+    C: Debug + Ord + LimitValues,
+{
+    format!("[{:?}, {:?}]", interval.start, interval.end)
+}
+
+fn format_constraint<S, C>(constraint: &Constraint<S, C>) -> String
+where
+    S: Debug,
+    C: Debug + Ord + LimitValues,
+{
+    match &constraint.next {
+        Some(next) => format!(
+            "{:?} -> {:?} during {}",
+            constraint.state,
+            next,
+            format_interval(&constraint.interval)
+        ),
+        None => format!(
+            "{:?} during {}",
+            constraint.state,
+            format_interval(&constraint.interval)
+        ),
+    }
+}
+
+fn format_move<S, A, C>(m: &Move<S, A, C>) -> String
+where
+    S: Debug,
+    C: Debug + Ord + LimitValues,
+{
+    format!(
+        "{:?} -> {:?} during {}",
+        m.from,
+        m.to,
+        format_interval(&m.interval)
+    )
+}
+
+impl Display for DebugSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Node at depth {} with cost {}", self.depth, self.cost)?;
+        for agent in &self.agents {
+            writeln!(
+                f,
+                "  Agent {}: {} constraints, {} landmarks",
+                agent.agent,
+                agent.constraints.len(),
+                agent.landmarks.len()
+            )?;
+            for constraint in &agent.constraints {
+                writeln!(f, "    - {}", constraint)?;
+            }
+            for landmark in &agent.landmarks {
+                writeln!(f, "    + {}", landmark)?;
+            }
+        }
+        writeln!(f, "  Conflicts: {}", self.conflicts.len())?;
+        for conflict in &self.conflicts {
+            writeln!(
+                f,
+                "    - {} between agents {} and {}: {} / {}",
+                conflict.type_,
+                conflict.agents.0,
+                conflict.agents.1,
+                conflict.moves.0,
+                conflict.moves.1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, CbsConfig, ConflictBasedSearch, GraphNodeId, ReverseResumableAStar,
+        SimpleHeuristic, SimpleState, SimpleWorld, Task,
+    };
+
+    #[test]
+    fn test_debug_snapshot() {
+        let size = 5;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let tasks = [(16, 9), (17, 8), (22, 4), (11, 13)]
+            .iter()
+            .map(|(from, to)| {
+                Arc::new(Task::new(
+                    SimpleState(GraphNodeId(*from)),
+                    SimpleState(GraphNodeId(*to)),
+                    OrderedFloat(0.0),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let pivots = Arc::new(tasks.iter().map(|t| t.goal_state.clone()).collect());
+        let heuristic_to_pivots = Arc::new(
+            tasks
+                .iter()
+                .map(|t| {
+                    Arc::new(ReverseResumableAStar::new(
+                        transition_system.clone(),
+                        t.clone(),
+                        SimpleHeuristic::new(transition_system.clone(), Arc::new(t.reverse())),
+                    ))
+                })
+                .collect(),
+        );
+        let config = CbsConfig::new(tasks, pivots, heuristic_to_pivots, OrderedFloat(1e-6));
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let mut constrained = false;
+        while let Some(node) = solver.solve_iter(&config) {
+            let snapshot = node.get_debug_snapshot(config.n_agents);
+            assert_eq!(snapshot.conflicts.len(), node.conflicts.len());
+            assert_eq!(snapshot.agents.len(), config.n_agents);
+            constrained |= snapshot.agents.iter().any(|a| !a.constraints.is_empty());
+
+            #[cfg(feature = "serde")]
+            {
+                let json = serde_json::to_string(&snapshot).unwrap();
+                let parsed: crate::DebugSnapshot = serde_json::from_str(&json).unwrap();
+                assert_eq!(parsed, snapshot);
+            }
+            assert!(snapshot
+                .to_string()
+                .contains(&format!("Conflicts: {}", node.conflicts.len())));
+
+            if node.conflicts.is_empty() {
+                break;
+            }
+        }
+        assert!(constrained);
+    }
+}
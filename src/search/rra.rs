@@ -16,7 +16,7 @@ use std::{
 use fxhash::{FxHashMap, FxHashSet};
 use parking_lot::Mutex;
 
-use crate::{abstraction::TransitionSystem, Heuristic, Task};
+use crate::{abstraction::TransitionSystem, Heuristic, MinimalHeuristic, Task};
 use crate::{LimitValues, SearchNode, State};
 
 /// Implementation of the Reverse Resumable A* algorithm
@@ -114,6 +114,8 @@ where
     /// Computes the shortest path between the given state and the goal state,
     /// or returns directly if it has already been computed.
     fn find_path(&self, state: &S) -> Option<DC> {
+        let state = state.canonical();
+        let state = state.as_ref();
         let mut data = self.data.lock();
 
         if data.closed.contains(state) {
@@ -190,6 +192,46 @@ where
     }
 }
 
+/// Builds the heuristics to the goal states of the given tasks at once.
+/// The tasks that share the same goal state also share a single reverse search,
+/// whose distances are computed once and reused by all of them.
+pub fn build_heuristics<TS, S, A, C, DC, H>(
+    transition_system: &Arc<TS>,
+    tasks: &[Arc<Task<S, C>>],
+) -> Vec<Arc<ReverseResumableAStar<TS, S, A, C, DC, H>>>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: Debug + State + Hash + Eq + Clone,
+    C: Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues,
+    DC: Copy,
+    H: Heuristic<TS, S, A, C, DC> + MinimalHeuristic<TS, S, A, C, DC>,
+{
+    let mut heuristics: FxHashMap<S, Arc<ReverseResumableAStar<TS, S, A, C, DC, H>>> =
+        FxHashMap::default();
+    tasks
+        .iter()
+        .map(|task| {
+            heuristics
+                .entry(task.goal_state.clone())
+                .or_insert_with(|| {
+                    Arc::new(ReverseResumableAStar::new(
+                        transition_system.clone(),
+                        task.clone(),
+                        H::build(transition_system.clone(), Arc::new(task.reverse())),
+                    ))
+                })
+                .clone()
+        })
+        .collect()
+}
+
 /// Protected data used by the Reverse Resumable A* algorithm.
 pub struct RraData<S, C, DC>
 where
@@ -219,6 +261,7 @@ where
 
 /// Statistics of the Reverse Resumable A* algorithm.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RraStats {
     pub new_query: usize,
     pub cached_query: usize,
@@ -242,41 +285,14 @@ mod tests {
     use ordered_float::OrderedFloat;
 
     use crate::{
-        Graph, GraphNodeId, Heuristic, ReverseResumableAStar, RraStats, SimpleEdgeData,
-        SimpleHeuristic, SimpleNodeData, SimpleState, SimpleWorld, Task,
+        build_heuristics, simple_graph, GraphNodeId, Heuristic, ReverseResumableAStar, RraStats,
+        SimpleHeuristic, SimpleState, SimpleWorld, Task,
     };
 
-    fn simple_graph(size: usize) -> Arc<Graph<SimpleNodeData, SimpleEdgeData>> {
-        let mut graph = Graph::new();
-        for x in 0..size {
-            for y in 0..size {
-                graph.add_node((x as f32, y as f32));
-            }
-        }
-        for x in 0..size {
-            for y in 0..size {
-                let node_id = GraphNodeId(x + y * size);
-                if x > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x - 1 + y * size), 1.0);
-                }
-                if y > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y - 1) * size), 1.0);
-                }
-                if x < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + 1 + y * size), 1.0);
-                }
-                if y < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y + 1) * size), 1.0);
-                }
-            }
-        }
-        Arc::new(graph)
-    }
-
     #[test]
     fn test_simple() {
         let size = 10;
-        let graph = simple_graph(size);
+        let graph = Arc::new(simple_graph(size));
         let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
         let task = Arc::new(Task::new(
             SimpleState(GraphNodeId(0)),
@@ -304,7 +320,7 @@ mod tests {
     #[test]
     fn test_caching() {
         let size = 10;
-        let graph = simple_graph(size);
+        let graph = Arc::new(simple_graph(size));
         let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
         let task = Arc::new(Task::new(
             SimpleState(GraphNodeId(0)),
@@ -335,4 +351,44 @@ mod tests {
         assert_eq!(after_same_query.cached_query, 1);
         assert_eq!(after_same_query.expanded, after_one_query.expanded);
     }
+
+    #[test]
+    fn test_build_heuristics() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        // The first and last tasks share the same goal state
+        let tasks: Vec<_> = [(0, size * size - 1), (size - 1, 0), (size, size * size - 1)]
+            .iter()
+            .map(|(from, to)| {
+                Arc::new(Task::new(
+                    SimpleState(GraphNodeId(*from)),
+                    SimpleState(GraphNodeId(*to)),
+                    OrderedFloat(0.0),
+                ))
+            })
+            .collect();
+
+        let heuristics: Vec<Arc<ReverseResumableAStar<_, _, _, _, _, SimpleHeuristic>>> =
+            build_heuristics(&transition_system, &tasks);
+        assert_eq!(heuristics.len(), tasks.len());
+        assert!(Arc::ptr_eq(&heuristics[0], &heuristics[2]));
+        assert!(!Arc::ptr_eq(&heuristics[0], &heuristics[1]));
+
+        for (task, heuristic) in tasks.iter().zip(heuristics.iter()) {
+            let individual = ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            );
+            for node in 0..size * size {
+                let state = SimpleState(GraphNodeId(node));
+                assert_eq!(
+                    heuristic.get_heuristic(&state),
+                    individual.get_heuristic(&state)
+                );
+            }
+        }
+    }
 }
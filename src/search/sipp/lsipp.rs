@@ -2,16 +2,16 @@ use std::{
     fmt::Debug,
     hash::Hash,
     marker::PhantomData,
-    ops::{Add, AddAssign, Sub},
+    ops::{Add, AddAssign, Mul, Sub},
     sync::Arc,
 };
 
 use fxhash::FxHashMap;
 
 use crate::{
-    ConstraintSet, DifferentialHeuristic, GeneralizedSippConfig, Heuristic, Interval, LandmarkSet,
-    LimitValues, SafeIntervalPathPlanning, SippConfig, SippState, SippStats, SippTask, Solution,
-    State, Task, TransitionSystem,
+    Constraint, ConstraintSet, DifferentialHeuristic, GeneralizedSippConfig, Heuristic, Interval,
+    LandmarkSet, LimitValues, SafeIntervalPathPlanning, SippConfig, SippSolution, SippState,
+    SippStats, SippTask, Solution, State, Task, TransitionSystem,
 };
 
 /// Implementation of Safe Interval Path Planning algorithm that supports landmarks
@@ -32,13 +32,12 @@ where
         + Copy
         + Default
         + LimitValues,
-    DC: Debug + Ord + Sub<DC, Output = DC> + Copy + Default,
+    DC: Debug + Ord + Sub<DC, Output = DC> + Mul<f32, Output = DC> + Copy + Default,
     H: Heuristic<TS, S, A, C, DC>,
 {
     sipp: SafeIntervalPathPlanning<TS, S, A, C, DC, DifferentialHeuristic<TS, S, A, C, DC, H>>,
-    solutions: Vec<Solution<Arc<SippState<S, C>>, A, C, DC>>,
-    solution_parts:
-        FxHashMap<((Arc<SippState<S, C>>, C), usize), Solution<Arc<SippState<S, C>>, A, C, DC>>,
+    solutions: Vec<SippSolution<S, A, C, DC>>,
+    solution_parts: FxHashMap<((Arc<SippState<S, C>>, C), usize), SippSolution<S, A, C, DC>>,
     landmark_states: Vec<Arc<SippState<S, C>>>,
     landmark_times: Vec<C>,
     stats: LSippStats,
@@ -60,7 +59,7 @@ where
         + Copy
         + Default
         + LimitValues,
-    DC: Debug + Ord + Sub<DC, Output = DC> + Copy + Default,
+    DC: Debug + Ord + Sub<DC, Output = DC> + Mul<f32, Output = DC> + Copy + Default,
     H: Heuristic<TS, S, A, C, DC>,
 {
     /// Creates a new instance of the Safe Interval Path Planning algorithm with landmarks.
@@ -76,41 +75,74 @@ where
     }
 
     fn init(&mut self) {
+        self.clear();
+
+        self.stats.searches += 1;
+    }
+
+    fn clear(&mut self) {
         self.solutions.clear();
         self.solution_parts.clear();
         self.landmark_states.clear();
         self.landmark_times.clear();
-
-        self.stats.searches += 1;
     }
 
     /// Attempts to solve the given configuration, and returns the solution if any.
     pub fn solve(
         &mut self,
         config: &LSippConfig<TS, S, A, C, DC, H>,
-    ) -> Option<Solution<Arc<SippState<S, C>>, A, C, DC>> {
+    ) -> Option<SippSolution<S, A, C, DC>> {
         self.init();
 
-        let solution = if config.landmarks.is_empty() {
+        match &config.precedences {
+            Some(precedences) if !config.landmarks.is_empty() => {
+                // Try all orderings of the landmarks that satisfy the precedences
+                let mut best = None;
+                self.explore_orderings(
+                    config,
+                    precedences,
+                    &mut vec![],
+                    config.task.initial_cost,
+                    &mut best,
+                );
+                best
+            }
+            _ => self.solve_sequence(config, &config.landmarks),
+        }
+    }
+
+    /// Attempts to solve the given configuration by visiting the landmarks in the given order.
+    fn solve_sequence(
+        &mut self,
+        config: &LSippConfig<TS, S, A, C, DC, H>,
+        landmarks: &[Arc<Constraint<S, C>>],
+    ) -> Option<SippSolution<S, A, C, DC>> {
+        self.clear();
+
+        let solution = if landmarks.is_empty() {
             // No landmarks, just solve the task with SIPP
-            self.sipp.solve(&SippConfig::new(
+            let mut sipp_config = SippConfig::new(
                 config.task.clone(),
                 Default::default(),
                 config.constraints.clone(),
                 self.get_heuristic(config, config.task.clone()),
                 config.precision,
-            ))
+            );
+            sipp_config.set_horizon(config.horizon);
+            self.sipp.solve(&sipp_config)
         } else {
             // Solve the task with landmarks
-            self.to_first_landmark(config);
-            self.between_landmarks(config);
-            self.to_goal(config);
-            self.get_solution(config)
+            self.to_first_landmark(config, landmarks);
+            self.between_landmarks(config, landmarks);
+            self.to_goal(config, landmarks);
+            self.get_solution(landmarks)
         };
 
         solution.and_then(|sol| {
-            // Last move must be valid until the end of the horizon
-            if sol.steps.last().unwrap().0.safe_interval.end != C::max_value() {
+            // Last move must be valid until the end of the horizon, unless the agent leaves at its goal
+            if !config.task.transient
+                && sol.steps.last().unwrap().0.safe_interval.end < config.horizon
+            {
                 None
             } else {
                 Some(sol)
@@ -118,16 +150,86 @@ where
         })
     }
 
+    /// Recursively builds the orderings of the landmarks that satisfy the precedences,
+    /// and keeps the best solution found. Partial orderings whose lower bound is not
+    /// better than the best solution are pruned.
+    fn explore_orderings(
+        &mut self,
+        config: &LSippConfig<TS, S, A, C, DC, H>,
+        precedences: &[(usize, usize)],
+        order: &mut Vec<usize>,
+        lower_bound: C,
+        best: &mut Option<SippSolution<S, A, C, DC>>,
+    ) {
+        if best.as_ref().is_some_and(|best| lower_bound >= best.cost) {
+            return;
+        }
+
+        if order.len() == config.landmarks.len() {
+            let landmarks: LandmarkSet<S, C> = order
+                .iter()
+                .map(|landmark| config.landmarks[*landmark].clone())
+                .collect();
+            if let Some(solution) = self.solve_sequence(config, &landmarks) {
+                if best.as_ref().is_none_or(|best| solution.cost < best.cost) {
+                    *best = Some(solution);
+                }
+            }
+            return;
+        }
+
+        let from = order
+            .last()
+            .map_or(&config.task.initial_state, |landmark| {
+                &config.landmarks[*landmark].state
+            })
+            .clone();
+
+        for next in 0..config.landmarks.len() {
+            if order.contains(&next)
+                || precedences
+                    .iter()
+                    .any(|(before, after)| *after == next && !order.contains(before))
+            {
+                continue;
+            }
+
+            let to = &config.landmarks[next].state;
+            let mut lower_bound = match self.get_distance(config, &from, to) {
+                Some(distance) => lower_bound + distance,
+                None => continue,
+            };
+            if order.len() + 1 == config.landmarks.len() {
+                // Last landmark, add the distance to the goal state
+                lower_bound = match self.get_distance(config, to, &config.task.goal_state) {
+                    Some(distance) => lower_bound + distance,
+                    None => continue,
+                };
+            }
+
+            order.push(next);
+            self.explore_orderings(config, precedences, order, lower_bound, best);
+            order.pop();
+        }
+    }
+
     // Go from the initial state to the first landmark
-    fn to_first_landmark(&mut self, config: &LSippConfig<TS, S, A, C, DC, H>) {
-        let task = Arc::new(Task::new(
-            config.task.initial_state.clone(),
-            config.landmarks[0].state.clone(),
-            config.task.initial_cost,
-        ));
+    fn to_first_landmark(
+        &mut self,
+        config: &LSippConfig<TS, S, A, C, DC, H>,
+        landmarks: &[Arc<Constraint<S, C>>],
+    ) {
+        let task = Arc::new(Task {
+            no_initial_wait: config.task.no_initial_wait,
+            ..Task::new(
+                config.task.initial_state.clone(),
+                landmarks[0].state.clone(),
+                config.task.initial_cost,
+            )
+        });
         let config = self.sipp.to_generalized(&SippConfig::new(
             task.clone(),
-            config.landmarks[0].interval,
+            landmarks[0].interval,
             config.constraints.clone(),
             self.get_heuristic(config, task),
             config.precision,
@@ -145,10 +247,14 @@ where
     }
 
     // Connect all landmarks sequentially
-    fn between_landmarks(&mut self, config: &LSippConfig<TS, S, A, C, DC, H>) {
-        for (i, landmark) in config.landmarks.iter().enumerate().skip(1) {
+    fn between_landmarks(
+        &mut self,
+        config: &LSippConfig<TS, S, A, C, DC, H>,
+        landmarks: &[Arc<Constraint<S, C>>],
+    ) {
+        for (i, landmark) in landmarks.iter().enumerate().skip(1) {
             let task = Arc::new(Task::new(
-                config.landmarks[i - 1].state.clone(),
+                landmarks[i - 1].state.clone(),
                 landmark.state.clone(),
                 config.task.initial_cost,
             ));
@@ -172,13 +278,21 @@ where
     }
 
     // Go from the last landmark to the goal state
-    fn to_goal(&mut self, config: &LSippConfig<TS, S, A, C, DC, H>) {
-        let task = Arc::new(Task::new(
-            config.landmarks[config.landmarks.len() - 1].state.clone(),
-            config.task.goal_state.clone(),
-            config.task.initial_cost,
-        ));
-        let config = GeneralizedSippConfig::new(
+    fn to_goal(
+        &mut self,
+        config: &LSippConfig<TS, S, A, C, DC, H>,
+        landmarks: &[Arc<Constraint<S, C>>],
+    ) {
+        let task = Arc::new(Task {
+            deadline: config.task.deadline,
+            goal_predicate: config.task.goal_predicate.clone(),
+            ..Task::new(
+                landmarks[landmarks.len() - 1].state.clone(),
+                config.task.goal_state.clone(),
+                config.task.initial_cost,
+            )
+        });
+        let mut generalized = GeneralizedSippConfig::new(
             SippTask::new(
                 self.landmark_times.drain(..).collect(),
                 self.landmark_states.drain(..).collect(),
@@ -190,8 +304,9 @@ where
             self.get_heuristic(config, task),
             config.precision,
         );
+        generalized.set_horizon(config.horizon);
 
-        self.solutions = self.sipp.solve_generalized(&config);
+        self.solutions = self.sipp.solve_generalized(&generalized);
     }
 
     /// Stores the last solutions as solution parts
@@ -208,8 +323,8 @@ where
     /// Returns the solution to the given task, if any.
     fn get_solution(
         &mut self,
-        config: &LSippConfig<TS, S, A, C, DC, H>,
-    ) -> Option<Solution<Arc<SippState<S, C>>, A, C, DC>> {
+        landmarks: &[Arc<Constraint<S, C>>],
+    ) -> Option<SippSolution<S, A, C, DC>> {
         if self.solutions.is_empty() {
             return None;
         }
@@ -218,7 +333,7 @@ where
         solution.cost = self.solutions[0].cost;
 
         let mut current_part = self.solutions.swap_remove(0);
-        for landmark in (0..(config.landmarks.len() + 1)).rev() {
+        for landmark in (0..(landmarks.len() + 1)).rev() {
             current_part
                 .steps
                 .drain(..)
@@ -257,6 +372,24 @@ where
         ))
     }
 
+    /// Returns a lower bound on the cost to go from one state to another.
+    fn get_distance(
+        &self,
+        config: &LSippConfig<TS, S, A, C, DC, H>,
+        from: &S,
+        to: &S,
+    ) -> Option<DC> {
+        self.get_heuristic(
+            config,
+            Arc::new(Task::new(
+                from.clone(),
+                to.clone(),
+                config.task.initial_cost,
+            )),
+        )
+        .get_heuristic(from)
+    }
+
     /// Returns the statistics of the search algorithm.
     pub fn get_stats(&mut self) -> LSippStats {
         self.stats.sipp_stats = self.sipp.get_stats();
@@ -284,11 +417,16 @@ where
     task: Arc<Task<S, C>>,
     constraints: Arc<ConstraintSet<S, C>>,
     landmarks: LandmarkSet<S, C>,
+    /// Pairs of landmarks (before, after) that must be visited in that order.
+    /// If not set, the landmarks are visited in the given order.
+    precedences: Option<Vec<(usize, usize)>>,
     /// A set of pivot states.
     pivots: Arc<Vec<S>>,
     /// A set of heuristics to those pivot states.
     heuristic_to_pivots: Arc<Vec<Arc<H>>>,
     precision: DC,
+    /// Time until which the agent must be able to stay at its goal state.
+    horizon: C,
     _phantom: PhantomData<(TS, A)>,
 }
 
@@ -319,9 +457,11 @@ where
             task: task.clone(),
             constraints,
             landmarks,
+            precedences: None,
             pivots: Arc::new(vec![task.goal_state.clone()]),
             heuristic_to_pivots: Arc::new(vec![heuristic]),
             precision,
+            horizon: C::max_value(),
             _phantom: PhantomData,
         }
     }
@@ -338,16 +478,32 @@ where
             task,
             constraints,
             landmarks,
+            precedences: None,
             pivots,
             heuristic_to_pivots,
             precision,
+            horizon: C::max_value(),
             _phantom: PhantomData,
         }
     }
+
+    /// Only requires the landmarks to be visited in an order compatible with the given
+    /// precedences (pairs of landmark indices), instead of the order of the landmark set.
+    pub fn set_precedences(&mut self, precedences: Vec<(usize, usize)>) {
+        self.precedences = Some(precedences);
+    }
+
+    /// Only requires the agent to stay safely at its goal state until the given horizon,
+    /// e.g. the end of a finite mission, instead of forever. Constraints beyond the horizon
+    /// then no longer make the task infeasible.
+    pub fn set_horizon(&mut self, horizon: C) {
+        self.horizon = horizon;
+    }
 }
 
 /// Statistics of the Safe Interval Path Planning algorithm with landmarks.
 #[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LSippStats {
     pub searches: usize,
     pub sipp_stats: SippStats,
@@ -367,42 +523,16 @@ mod tests {
     use ordered_float::OrderedFloat;
 
     use crate::{
-        Constraint, Graph, GraphNodeId, Interval, LSippConfig, ReverseResumableAStar,
-        SafeIntervalPathPlanningWithLandmarks, SimpleEdgeData, SimpleHeuristic, SimpleNodeData,
-        SimpleState, SimpleWorld, Task,
+        simple_graph, Constraint, ConstraintSet, GraphNodeId, Interval, LSippConfig, LimitValues,
+        MyTime, ReverseResumableAStar, SafeIntervalPathPlanning,
+        SafeIntervalPathPlanningWithLandmarks, SimpleHeuristic, SimpleState, SimpleWorld,
+        SippConfig, Task,
     };
 
-    fn simple_graph(size: usize) -> Arc<Graph<SimpleNodeData, SimpleEdgeData>> {
-        let mut graph = Graph::new();
-        for x in 0..size {
-            for y in 0..size {
-                graph.add_node((x as f32, y as f32));
-            }
-        }
-        for x in 0..size {
-            for y in 0..size {
-                let node_id = GraphNodeId(x + y * size);
-                if x > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x - 1 + y * size), 1.0);
-                }
-                if y > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y - 1) * size), 1.0);
-                }
-                if x < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + 1 + y * size), 1.0);
-                }
-                if y < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y + 1) * size), 1.0);
-                }
-            }
-        }
-        Arc::new(graph)
-    }
-
     #[test]
     fn test_simple() {
         let size = 10;
-        let graph = simple_graph(size);
+        let graph = Arc::new(simple_graph(size));
         let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
         let mut solver = SafeIntervalPathPlanningWithLandmarks::new(transition_system.clone());
 
@@ -432,7 +562,11 @@ mod tests {
                     OrderedFloat(((size - x - 1) + (size - y - 1)) as f32)
                 );
                 assert_eq!(after.searches, before.searches + 1);
-                assert_eq!(after.sipp_stats.searches, before.sipp_stats.searches + 1);
+                // No search is needed when the agent starts at its goal
+                assert_eq!(
+                    after.sipp_stats.searches,
+                    before.sipp_stats.searches + usize::from(!solution.actions.is_empty())
+                );
             }
         }
     }
@@ -440,7 +574,7 @@ mod tests {
     #[test]
     fn test_with_landmarks() {
         let size = 10;
-        let graph = simple_graph(size);
+        let graph = Arc::new(simple_graph(size));
         let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
         let mut solver = SafeIntervalPathPlanningWithLandmarks::new(transition_system.clone());
 
@@ -478,4 +612,133 @@ mod tests {
         assert_eq!(after.searches, before.searches + 1);
         assert_eq!(after.sipp_stats.searches, before.sipp_stats.searches + 3);
     }
+
+    #[test]
+    fn test_with_partial_order_landmarks() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanningWithLandmarks::new(transition_system.clone());
+
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(size * size - 1)),
+            OrderedFloat(0.0),
+        ));
+        let landmarks = vec![
+            Arc::new(Constraint::new_state_constraint(
+                0,
+                SimpleState(GraphNodeId(8)),
+                Interval::default(),
+            )),
+            Arc::new(Constraint::new_state_constraint(
+                0,
+                SimpleState(GraphNodeId(2)),
+                Interval::default(),
+            )),
+            Arc::new(Constraint::new_state_constraint(
+                0,
+                SimpleState(GraphNodeId(8 + 8 * size)),
+                Interval::default(),
+            )),
+        ];
+        let mut config = LSippConfig::new(
+            task.clone(),
+            Default::default(),
+            landmarks,
+            Arc::new(ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            )),
+            1e-6.into(),
+        );
+
+        // Visiting the landmarks in the given order is expensive
+        let solution = solver.solve(&config).unwrap();
+        assert_eq!(solution.cost, OrderedFloat(30.0));
+
+        // The first two landmarks can be visited in any order before the third one
+        config.set_precedences(vec![(0, 2), (1, 2)]);
+        let solution = solver.solve(&config).unwrap();
+        assert_eq!(solution.cost, OrderedFloat(18.0));
+
+        let position = |node| {
+            solution
+                .steps
+                .iter()
+                .position(|(state, _)| state.internal_state == SimpleState(GraphNodeId(node)))
+                .unwrap()
+        };
+        assert!(position(2) < position(8));
+        assert!(position(8) < position(8 + 8 * size));
+    }
+
+    #[test]
+    fn test_finite_horizon() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanningWithLandmarks::new(transition_system.clone());
+
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(3)),
+            OrderedFloat(0.0),
+        ));
+
+        // The goal state becomes unavailable forever after the end of the mission
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(GraphNodeId(3)),
+            Interval::new(OrderedFloat(20.0), MyTime::max_value()),
+        )));
+        let constraints = Arc::new(constraints);
+
+        for landmarks in [
+            vec![],
+            vec![Arc::new(Constraint::new_state_constraint(
+                0,
+                SimpleState(GraphNodeId(1)),
+                Interval::default(),
+            ))],
+        ] {
+            let mut config = LSippConfig::new(
+                task.clone(),
+                constraints.clone(),
+                landmarks,
+                Arc::new(ReverseResumableAStar::new(
+                    transition_system.clone(),
+                    task.clone(),
+                    SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+                )),
+                1e-6.into(),
+            );
+            assert!(solver.solve(&config).is_none());
+
+            config.set_horizon(OrderedFloat(10.0));
+            let solution = solver.solve(&config).unwrap();
+            assert_eq!(solution.cost, OrderedFloat(3.0));
+        }
+
+        // The plain search does not return a goal state that the agent cannot hold until the horizon
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+        let mut config = SippConfig::new(
+            task.clone(),
+            Default::default(),
+            constraints,
+            Arc::new(ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            )),
+            1e-6.into(),
+        );
+        assert!(solver.solve(&config).is_none());
+        assert!(solver.solve_cost(&config).is_none());
+
+        config.set_horizon(OrderedFloat(10.0));
+        assert_eq!(solver.solve_cost(&config), Some(OrderedFloat(3.0)));
+    }
 }
@@ -1,5 +1,7 @@
 mod lsipp;
+mod multi;
 mod sipp;
 
 pub use lsipp::*;
+pub use multi::*;
 pub use sipp::*;
@@ -0,0 +1,184 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, Mul, Sub},
+    sync::Arc,
+};
+
+use crate::{
+    ConstraintSet, Heuristic, LimitValues, SafeIntervalPathPlanning, SippState, SippStats, State,
+    TransitionSystem,
+};
+
+/// Safe Interval Path Planning algorithm that answers many queries from a fixed initial state
+/// under a fixed set of constraints, e.g. to fill a cost table. A single search without heuristic
+/// is continued until each requested goal state is settled, so that its work is shared by all the queries.
+pub struct MultiQuerySipp<TS, S, A, C, DC, H>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: State + Debug + Hash + Eq + Clone,
+    A: Copy,
+    C: Debug
+        + Hash
+        + Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues,
+    DC: Debug + PartialOrd + Mul<f32, Output = DC> + Copy + Default,
+    H: Heuristic<TS, S, A, C, DC>,
+{
+    sipp: SafeIntervalPathPlanning<TS, S, A, C, DC, H>,
+    constraints: Arc<ConstraintSet<S, C>>,
+    precision: DC,
+    /// Whether the initial state is safe at the initial time.
+    initialized: bool,
+}
+
+impl<TS, S, A, C, DC, H> MultiQuerySipp<TS, S, A, C, DC, H>
+where
+    TS: TransitionSystem<S, A, C, DC>,
+    S: State + Debug + Hash + Eq + Clone,
+    A: Copy,
+    C: Debug
+        + Hash
+        + Eq
+        + PartialOrd
+        + Ord
+        + Add<DC, Output = C>
+        + Sub<DC, Output = C>
+        + Sub<C, Output = DC>
+        + Copy
+        + Default
+        + LimitValues,
+    DC: Debug + PartialOrd + Mul<f32, Output = DC> + Copy + Default,
+    H: Heuristic<TS, S, A, C, DC>,
+{
+    /// Creates a new instance that answers the queries from the given state and initial time.
+    pub fn new(
+        transition_system: Arc<TS>,
+        start: &S,
+        initial_time: C,
+        constraints: Arc<ConstraintSet<S, C>>,
+        precision: DC,
+    ) -> Self {
+        let mut sipp = SafeIntervalPathPlanning::new(transition_system);
+        let initialized = sipp.init_uninformed(start, initial_time, &constraints, precision);
+
+        Self {
+            sipp,
+            constraints,
+            precision,
+            initialized,
+        }
+    }
+
+    /// Returns the cost of the optimal solution that reaches the given goal state and stays there forever,
+    /// as `SafeIntervalPathPlanning::solve` would, if any. The search is only continued if the goal state
+    /// has not been settled by a previous query.
+    pub fn cost_to(&mut self, goal: &S) -> Option<C> {
+        if !self.initialized {
+            return None;
+        }
+
+        // The agent must be able to stay at its goal state forever
+        let safe_interval = SafeIntervalPathPlanning::<TS, S, A, C, DC, H>::safe_intervals_for(
+            goal,
+            &self.constraints,
+            self.precision,
+        )
+        .pop()
+        .filter(|safe_interval| safe_interval.end == C::max_value())?;
+
+        self.sipp.search_until(
+            Some(&SippState {
+                safe_interval,
+                internal_state: goal.clone(),
+            }),
+            &self.constraints,
+            self.precision,
+        )
+    }
+
+    /// Returns the statistics of the underlying search.
+    pub fn get_stats(&self) -> SippStats {
+        self.sipp.get_stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, Constraint, ConstraintSet, GraphNodeId, Interval, MultiQuerySipp,
+        ReverseResumableAStar, SafeIntervalPathPlanning, SimpleHeuristic, SimpleState, SimpleWorld,
+        SippConfig, Task,
+    };
+
+    #[test]
+    fn test_multi_query() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        let start = SimpleState(GraphNodeId(0));
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(GraphNodeId(1)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(5.0)),
+        )));
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(GraphNodeId(size)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(5.0)),
+        )));
+        let constraints = Arc::new(constraints);
+
+        let goals = (0..size)
+            .map(|i| SimpleState(GraphNodeId(i * size + size - 1)))
+            .collect::<Vec<_>>();
+
+        let mut queries = MultiQuerySipp::<_, _, _, _, _, SimpleHeuristic>::new(
+            transition_system.clone(),
+            &start,
+            OrderedFloat(0.0),
+            constraints.clone(),
+            1e-6.into(),
+        );
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        for goal in &goals {
+            let task = Arc::new(Task::new(start.clone(), goal.clone(), OrderedFloat(0.0)));
+            let config = SippConfig::new(
+                task.clone(),
+                Default::default(),
+                constraints.clone(),
+                Arc::new(ReverseResumableAStar::new(
+                    transition_system.clone(),
+                    task.clone(),
+                    SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+                )),
+                1e-6.into(),
+            );
+            let solution = solver.solve(&config).unwrap();
+
+            assert_eq!(queries.cost_to(goal), Some(solution.cost));
+        }
+
+        // Settled goals are answered without any further expansion
+        let expanded = queries.get_stats().expanded;
+        assert_eq!(queries.cost_to(&goals[0]), Some(OrderedFloat(13.0)));
+        assert_eq!(queries.get_stats().expanded, expanded);
+
+        assert_eq!(queries.get_stats().searches, 1);
+        assert!(expanded < solver.get_stats().expanded);
+    }
+}
@@ -1,13 +1,10 @@
 use std::{
-    cmp::Reverse,
-    collections::{
-        hash_map::Entry::{Occupied, Vacant},
-        BTreeSet, BinaryHeap,
-    },
+    cmp::{Ordering, Reverse},
+    collections::{BTreeSet, BinaryHeap},
     fmt::Debug,
     hash::Hash,
     marker::PhantomData,
-    ops::{Add, AddAssign, Sub},
+    ops::{Add, AddAssign, Mul, Sub},
     sync::Arc,
     vec,
 };
@@ -15,8 +12,8 @@ use std::{
 use fxhash::{FxHashMap, FxHashSet};
 
 use crate::{
-    Action, ConstraintSet, Heuristic, Interval, LimitValues, SearchNode, Solution, State, Task,
-    TransitionSystem,
+    Action, Constraint, ConstraintSet, ConstraintType, Heuristic, Interval, LimitValues,
+    SearchNode, Solution, State, Task, TransitionSystem,
 };
 
 /// Implementation of the Safe Interval Path Planning algorithm that computes
@@ -38,17 +35,22 @@ where
         + Copy
         + Default
         + LimitValues,
-    DC: Debug + PartialOrd + Copy + Default,
+    DC: Debug + PartialOrd + Mul<f32, Output = DC> + Copy + Default,
     H: Heuristic<TS, S, A, C, DC>,
 {
     transition_system: Arc<TS>,
-    queue: BinaryHeap<Reverse<SearchNode<SippState<S, C>, C, DC>>>,
+    queue: BinaryHeap<Reverse<QueueNode<S, C, DC>>>,
     distance: FxHashMap<Arc<SippState<S, C>>, C>,
+    /// Weighted margin around the constraints along the best path to each state, if bounded.
+    margin: FxHashMap<Arc<SippState<S, C>>, Option<DC>>,
     closed: FxHashSet<Arc<SippState<S, C>>>,
     parent: FxHashMap<Arc<SippState<S, C>>, (Action<A, DC>, Arc<SippState<S, C>>)>,
     goal_intervals: BTreeSet<Interval<C>>,
     goal_horizon: C,
     safe_intervals: Vec<Interval<C>>,
+    /// Threshold above the value of a node beyond which its successors are only generated
+    /// when it is popped again, if partial expansion is enabled.
+    partial_expansion: Option<DC>,
     stats: SippStats,
     _phantom: PhantomData<(A, H)>,
 }
@@ -69,7 +71,7 @@ where
         + Copy
         + Default
         + LimitValues,
-    DC: Debug + PartialOrd + Copy + Default,
+    DC: Debug + PartialOrd + Mul<f32, Output = DC> + Copy + Default,
     H: Heuristic<TS, S, A, C, DC>,
 {
     /// Creates a new instance of the Safe Interval Path Planning algorithm.
@@ -78,16 +80,27 @@ where
             transition_system,
             queue: BinaryHeap::new(),
             distance: FxHashMap::default(),
+            margin: FxHashMap::default(),
             closed: FxHashSet::default(),
             parent: FxHashMap::default(),
             goal_intervals: BTreeSet::default(),
             goal_horizon: C::max_value(),
             safe_intervals: vec![],
+            partial_expansion: None,
             stats: SippStats::default(),
             _phantom: PhantomData,
         }
     }
 
+    /// Enables partial expansion with the given threshold: when a node is expanded, only its successors
+    /// whose (cost + heuristic) value exceeds the one of the node by at most the threshold are generated,
+    /// and the node is enqueued again with the smallest value of the other ones to generate them later.
+    /// This reduces the size of the queue on graphs with a large branching factor, at the expense of
+    /// expanding some nodes several times, without affecting the cost of the solutions.
+    pub fn set_partial_expansion(&mut self, threshold: Option<DC>) {
+        self.partial_expansion = threshold;
+    }
+
     /// Transforms the configuration into a generalized configuration, if any
     /// safe intervals exist for the initial state.
     pub fn to_generalized(
@@ -122,27 +135,202 @@ where
             config.task.clone(),
         );
 
-        Some(GeneralizedSippConfig::new(
+        let mut generalized = GeneralizedSippConfig::new(
             sipp_task,
             config.constraints.clone(),
             config.heuristic.clone(),
             config.precision,
-        ))
+        );
+        generalized.set_horizon(config.horizon);
+
+        Some(generalized)
     }
 
     /// Attempts to solve the given configuration, and returns the optimal solution if any.
+    /// The agent must be able to stay at its goal state until the horizon of the configuration,
+    /// unless its task is transient in which case the earliest arrival at the goal state is returned.
     pub fn solve(
         &mut self,
         config: &SippConfig<TS, S, A, C, DC, H>,
-    ) -> Option<Solution<Arc<SippState<S, C>>, A, C, DC>> {
-        self.to_generalized(config)
-            .and_then(|config| self.solve_generalized(&config).pop())
+    ) -> Option<SippSolution<S, A, C, DC>> {
+        let generalized = self.to_generalized(config)?;
+        if let Some(solution) = Self::get_trivial_solution(config, &generalized) {
+            return Some(solution);
+        }
+
+        self.find_goal(config, &generalized)
+            .map(|goal| self.get_solution(&generalized, &goal))
+    }
+
+    /// Attempts to solve the given configuration, and returns the cost of the optimal solution if any.
+    /// The solution is not reconstructed, which saves work when only the cost is needed,
+    /// e.g. to build a cost matrix.
+    pub fn solve_cost(&mut self, config: &SippConfig<TS, S, A, C, DC, H>) -> Option<C> {
+        let generalized = self.to_generalized(config)?;
+        if let Some(solution) = Self::get_trivial_solution(config, &generalized) {
+            return Some(solution.cost);
+        }
+
+        self.find_goal(config, &generalized)
+            .map(|goal| goal.cost.max(generalized.task.goal_interval.start))
+    }
+
+    /// Searches the given generalized configuration, and returns the goal node of the optimal solution
+    /// of the given configuration if any.
+    fn find_goal(
+        &mut self,
+        config: &SippConfig<TS, S, A, C, DC, H>,
+        generalized: &GeneralizedSippConfig<TS, S, A, C, DC, H>,
+    ) -> Option<SearchNode<SippState<S, C>, C, DC>> {
+        if !self.init(generalized) {
+            return None;
+        }
+
+        let mut goals = self.find_paths(generalized);
+        if config.task.transient {
+            goals.into_iter().next()
+        } else {
+            // Earliest arrival from which the agent can stay at its goal state until the horizon
+            goals
+                .iter()
+                .position(|goal| goal.state.safe_interval.end >= generalized.horizon)
+                .map(|index| goals.swap_remove(index))
+        }
+    }
+
+    /// Returns the solution without any move if the agent already stands at its goal state
+    /// and can stay there, or leave it if its task is transient, without running a search.
+    fn get_trivial_solution(
+        config: &SippConfig<TS, S, A, C, DC, H>,
+        generalized: &GeneralizedSippConfig<TS, S, A, C, DC, H>,
+    ) -> Option<SippSolution<S, A, C, DC>> {
+        let task = &config.task;
+        let initial_state = &generalized.task.initial_states[0];
+        let initial_time = Interval::new(task.initial_cost, task.initial_cost);
+
+        if task.is_goal_state(&task.initial_state)
+            && (task.transient || initial_state.safe_interval.end >= generalized.horizon)
+            && config.interval.contains(&initial_time)
+            && task
+                .deadline
+                .is_none_or(|deadline| task.initial_cost <= deadline)
+        {
+            Some(Solution {
+                cost: task.initial_cost,
+                steps: vec![(initial_state.clone(), task.initial_cost)],
+                actions: vec![],
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns up to k solutions of the given configuration with distinct sequences of states,
+    /// sorted by increasing cost. The solutions are obtained with Yen's algorithm: each new solution
+    /// deviates from a previous one at some step, by forbidding the actions taken there by the
+    /// solutions that share the same prefix.
+    pub fn solve_k_best(
+        &mut self,
+        config: &SippConfig<TS, S, A, C, DC, H>,
+        k: usize,
+    ) -> Vec<SippSolution<S, A, C, DC>> {
+        let mut solutions = vec![];
+        let mut candidates: Vec<SippSolution<S, A, C, DC>> = vec![];
+
+        match self.solve(config) {
+            Some(solution) => solutions.push(solution),
+            None => return solutions,
+        }
+
+        while solutions.len() < k {
+            let last = solutions.last().unwrap();
+
+            for i in 0..last.actions.len() {
+                let (spur, spur_time) = &last.steps[i];
+                if last.actions[i].action.is_none() {
+                    // Deviations are only made by taking a different action
+                    continue;
+                }
+
+                // Forbid the next action of all the solutions that share the same prefix
+                let mut constraints = ConstraintSet::default();
+                constraints.merge(&config.constraints);
+                for solution in solutions.iter() {
+                    if solution.actions.len() > i
+                        && solution.actions[i].action.is_some()
+                        && solution.steps[..=i]
+                            .iter()
+                            .zip(last.steps[..=i].iter())
+                            .all(|(a, b)| a.0.internal_state == b.0.internal_state && a.1 == b.1)
+                    {
+                        constraints.add(&Arc::new(Constraint::new_action_constraint(
+                            0,
+                            spur.internal_state.clone(),
+                            solution.steps[i + 1].0.internal_state.clone(),
+                            Interval::new(*spur_time, C::max_value()),
+                        )));
+                    }
+                }
+                constraints.unify();
+
+                let spur_config = SippConfig::new(
+                    Arc::new(Task::new(
+                        spur.internal_state.clone(),
+                        config.task.goal_state.clone(),
+                        *spur_time,
+                    )),
+                    config.interval,
+                    Arc::new(constraints),
+                    config.heuristic.clone(),
+                    config.precision,
+                );
+
+                if let Some(spur_solution) = self.solve(&spur_config) {
+                    let mut candidate = Solution {
+                        cost: spur_solution.cost,
+                        steps: last.steps[..i].to_vec(),
+                        actions: last.actions[..i].to_vec(),
+                    };
+                    candidate.steps.extend(spur_solution.steps);
+                    candidate.actions.extend(spur_solution.actions);
+
+                    let states = Self::get_state_sequence(&candidate);
+                    if solutions
+                        .iter()
+                        .chain(candidates.iter())
+                        .all(|s| Self::get_state_sequence(s) != states)
+                    {
+                        candidates.push(candidate);
+                    }
+                }
+            }
+
+            // Keep the cheapest candidate
+            match (0..candidates.len()).min_by_key(|j| candidates[*j].cost) {
+                Some(best) => solutions.push(candidates.swap_remove(best)),
+                None => break,
+            }
+        }
+
+        solutions
     }
+
+    /// Returns the sequence of states visited by the given solution, ignoring the wait actions.
+    fn get_state_sequence(solution: &SippSolution<S, A, C, DC>) -> Vec<&S> {
+        let mut states: Vec<&S> = solution
+            .steps
+            .iter()
+            .map(|(state, _)| &state.internal_state)
+            .collect();
+        states.dedup();
+        states
+    }
+
     /// Attempts to solve the given generalized configuration, and returns the optimal solution if any.
     pub fn solve_generalized(
         &mut self,
         config: &GeneralizedSippConfig<TS, S, A, C, DC, H>,
-    ) -> Vec<Solution<Arc<SippState<S, C>>, A, C, DC>> {
+    ) -> Vec<SippSolution<S, A, C, DC>> {
         if !self.init(config) {
             return vec![];
         }
@@ -158,6 +346,7 @@ where
     fn init(&mut self, config: &GeneralizedSippConfig<TS, S, A, C, DC, H>) -> bool {
         self.queue.clear();
         self.distance.clear();
+        self.margin.clear();
         self.closed.clear();
         self.parent.clear();
         self.goal_intervals.clear();
@@ -178,7 +367,17 @@ where
 
             self.distance
                 .insert(initial_node.state.clone(), initial_node.cost);
-            self.queue.push(Reverse(initial_node));
+            self.margin.insert(initial_node.state.clone(), None);
+            self.queue.push(Reverse(QueueNode::new(initial_node, None)));
+        }
+
+        let deadline = config.task.internal_task.deadline;
+        if config.task.has_goal_predicate() {
+            // The goal states are only known when they are reached
+            self.goal_horizon =
+                deadline.map_or(C::max_value(), |deadline| deadline + config.precision);
+            self.stats.searches += 1;
+            return true;
         }
 
         // Find the safe intervals at the goal state
@@ -189,19 +388,31 @@ where
             config.precision,
             &mut self.safe_intervals,
         );
-        if self.safe_intervals.is_empty() {
+        self.goal_intervals.extend(
+            self.safe_intervals
+                .drain(..)
+                .filter(|i| deadline.is_none_or(|deadline| i.start <= deadline)),
+        );
+        if self.goal_intervals.is_empty() {
             return false;
         }
-        self.safe_intervals.drain(..).for_each(|i| {
-            self.goal_horizon = self.goal_horizon.max(i.end);
-            self.goal_intervals.insert(i);
-        });
+        self.goal_horizon = self.get_goal_horizon(config);
 
         self.stats.searches += 1;
 
         true
     }
 
+    /// Returns the time after which the remaining safe intervals at the goal state cannot be reached,
+    /// which is bounded by the deadline of the task, if any.
+    fn get_goal_horizon(&self, config: &GeneralizedSippConfig<TS, S, A, C, DC, H>) -> C {
+        let horizon = self.goal_intervals.last().unwrap().end;
+        match config.task.internal_task.deadline {
+            Some(deadline) => horizon.min(deadline + config.precision),
+            None => horizon,
+        }
+    }
+
     /// Finds all shortest paths from the initial states to any reachable safe interval
     /// at the goal state.
     fn find_paths(
@@ -210,7 +421,7 @@ where
     ) -> Vec<SearchNode<SippState<S, C>, C, DC>> {
         let mut goals = vec![];
 
-        while let Some(Reverse(current)) = self.queue.pop() {
+        while let Some(Reverse(QueueNode { node: current, .. })) = self.queue.pop() {
             if current.cost > self.distance[current.state.as_ref()] {
                 // A better path has already been found
                 continue;
@@ -221,7 +432,15 @@ where
                 continue;
             }
 
-            if config.task.is_goal(&current)
+            if config.task.is_goal(&current) && config.task.has_goal_predicate() {
+                // Any goal state will do, provided that the agent can stay there or leave
+                if config.task.internal_task.transient
+                    || current.state.safe_interval.end >= config.horizon
+                {
+                    goals.push(current.clone());
+                    break;
+                }
+            } else if config.task.is_goal(&current)
                 && self.goal_intervals.remove(&current.state.safe_interval)
             {
                 // A path to the goal has been found
@@ -229,11 +448,22 @@ where
                 if self.goal_intervals.is_empty() {
                     break;
                 }
-                self.goal_horizon = self.goal_intervals.last().unwrap().end;
+                self.goal_horizon = self.get_goal_horizon(config);
             }
 
+            // The initial states have no parent, the agent may have to leave them immediately
+            let latest_departure = (config.task.internal_task.no_initial_wait
+                && !self.parent.contains_key(&current.state))
+            .then_some(current.cost);
+
             // Expand the current state and enqueue its successors
-            self.expand(config, &current);
+            self.expand(
+                &config.constraints,
+                Some(config.heuristic.as_ref()),
+                config.precision,
+                &current,
+                latest_departure,
+            );
 
             self.closed.insert(current.state.clone()); // Mark the state as closed because it has been expanded
             self.stats.expanded += 1;
@@ -242,12 +472,136 @@ where
         goals
     }
 
+    /// Returns the minimum arrival time at each state that can be reached from the given state
+    /// no later than the given budget after the initial time, while satisfying the given constraints.
+    pub fn reachable_within(
+        &mut self,
+        start: &S,
+        initial_time: C,
+        budget: DC,
+        constraints: &Arc<ConstraintSet<S, C>>,
+        precision: DC,
+    ) -> FxHashMap<S, C> {
+        let mut reachable = FxHashMap::default();
+
+        if !self.init_uninformed(start, initial_time, constraints, precision) {
+            return reachable;
+        }
+
+        // Successors arriving after the deadline are not generated
+        let deadline = initial_time + budget;
+        self.goal_horizon = deadline + precision;
+
+        self.search_until(None, constraints, precision);
+
+        for (state, time) in self.distance.iter() {
+            if *time <= deadline {
+                reachable
+                    .entry(state.internal_state.clone())
+                    .and_modify(|t: &mut C| *t = (*t).min(*time))
+                    .or_insert(*time);
+            }
+        }
+
+        reachable
+    }
+
+    /// Initializes a search without heuristic from the given state, whose nodes are then settled
+    /// on demand by `search_until`. Returns false if the given state is not safe at the initial time.
+    pub(crate) fn init_uninformed(
+        &mut self,
+        start: &S,
+        initial_time: C,
+        constraints: &Arc<ConstraintSet<S, C>>,
+        precision: DC,
+    ) -> bool {
+        self.queue.clear();
+        self.distance.clear();
+        self.margin.clear();
+        self.closed.clear();
+        self.parent.clear();
+        self.goal_horizon = C::max_value();
+
+        // Find the safe interval in which the initial time is contained
+        Self::get_safe_intervals(
+            constraints,
+            start,
+            &Interval::new(initial_time, initial_time),
+            precision,
+            &mut self.safe_intervals,
+        );
+        let initial_state = match self.safe_intervals.pop() {
+            Some(safe_interval) => Arc::new(SippState {
+                safe_interval,
+                internal_state: start.clone(),
+            }),
+            None => return false,
+        };
+        self.safe_intervals.clear();
+
+        self.distance.insert(initial_state.clone(), initial_time);
+        self.margin.insert(initial_state.clone(), None);
+        self.queue.push(Reverse(QueueNode::new(
+            SearchNode {
+                state: initial_state,
+                cost: initial_time,
+                heuristic: DC::default(),
+            },
+            None,
+        )));
+
+        self.stats.searches += 1;
+
+        true
+    }
+
+    /// Continues the search without heuristic until the given state is expanded, or until the queue
+    /// is exhausted if no state is given. Returns the minimum arrival time at the given state, if reachable.
+    pub(crate) fn search_until(
+        &mut self,
+        target: Option<&SippState<S, C>>,
+        constraints: &Arc<ConstraintSet<S, C>>,
+        precision: DC,
+    ) -> Option<C> {
+        if let Some(target) = target.filter(|target| self.closed.contains(*target)) {
+            // The given state has already been settled by a previous call
+            return Some(self.distance[target]);
+        }
+
+        while let Some(Reverse(QueueNode { node: current, .. })) = self.queue.pop() {
+            if current.cost > self.distance[current.state.as_ref()] {
+                // A better path has already been found
+                continue;
+            }
+
+            self.expand(constraints, None, precision, &current, None);
+
+            self.closed.insert(current.state.clone());
+            self.stats.expanded += 1;
+
+            if target.is_some_and(|target| current.state.as_ref() == target) {
+                return Some(current.cost);
+            }
+        }
+
+        None
+    }
+
     /// Generates the reachable successors of the given search node.
+    /// Without heuristic, all the successors reached before the goal horizon are generated.
+    /// Successors that require to depart after the given latest departure time, if any, are skipped.
     fn expand(
         &mut self,
-        config: &GeneralizedSippConfig<TS, S, A, C, DC, H>,
+        constraints: &Arc<ConstraintSet<S, C>>,
+        heuristic: Option<&H>,
+        precision: DC,
         current: &SearchNode<SippState<S, C>, C, DC>,
+        latest_departure: Option<C>,
     ) {
+        // Partial expansion requires a heuristic to order the successors
+        let partial_expansion = heuristic.and(self.partial_expansion);
+        let mut deferred: Option<C> = None;
+
         for action in self
             .transition_system
             .actions_from(&current.state.internal_state)
@@ -259,34 +613,42 @@ where
                 .transition_system
                 .transition_cost(&current.state.internal_state, action);
 
-            let heuristic = config.heuristic.get_heuristic(&successor_state);
-            if heuristic.is_none() {
-                continue; // Goal state is not reachable from this state
+            // Cheap rejection of the successors that cannot reach the goal in time
+            if let Some(bound) = heuristic.and_then(|h| h.lower_bound(&successor_state)) {
+                if current.cost + transition_cost + bound >= self.goal_horizon {
+                    continue;
+                }
             }
-            let heuristic = heuristic.unwrap();
+
+            let heuristic = match heuristic {
+                Some(heuristic) => match heuristic.get_heuristic(&successor_state) {
+                    Some(heuristic) => heuristic,
+                    None => continue, // Goal state is not reachable from this state
+                },
+                None => DC::default(),
+            };
 
             if current.cost + transition_cost + heuristic >= self.goal_horizon {
                 // The remaining safe intervals at the goal state are not reachable in time
                 continue;
             }
 
-            let action_constraints = config
-                .constraints
-                .get_action_constraints(&current.state.internal_state, &successor_state);
+            let action_constraints =
+                constraints.get_action_constraints(&current.state.internal_state, &successor_state);
 
             // Try to reach any of the safe intervals of the destination state
             // and add the corresponding successors to the queue if a better path has been found
             Self::get_safe_intervals(
-                &config.constraints,
+                constraints,
                 &successor_state,
                 &Interval::new(current.cost + transition_cost, C::max_value()),
-                config.precision,
+                precision,
                 &mut self.safe_intervals,
             );
             for safe_interval in self.safe_intervals.drain(..) {
                 let mut successor_cost = current.cost + transition_cost;
 
-                if successor_cost + config.precision > safe_interval.end {
+                if successor_cost + precision > safe_interval.end {
                     // Cannot reach this safe interval in time
                     continue;
                 }
@@ -301,7 +663,7 @@ where
                         continue;
                     }
                     successor_cost = safe_interval.start; // Try to depart later to arrive at the right time
-                    if successor_cost - transition_cost + config.precision
+                    if successor_cost - transition_cost + precision
                         > current.state.safe_interval.end
                     {
                         // Cannot depart that late from the current safe interval
@@ -312,13 +674,11 @@ where
                 // Check collision along the action
                 if let Some(collision_interval) = action_constraints.and_then(|col| {
                     col.get(col.partition_point(|c| {
-                        c.interval.end + config.precision < successor_cost - transition_cost
+                        c.interval.end + precision < successor_cost - transition_cost
                     }))
                     .map(|c| c.interval)
                 }) {
-                    if successor_cost - transition_cost + config.precision
-                        > collision_interval.start
-                    {
+                    if successor_cost - transition_cost + precision > collision_interval.start {
                         // Collision detected
                         if !self
                             .transition_system
@@ -329,15 +689,22 @@ where
                         }
                         successor_cost = collision_interval.end + transition_cost; // Try to depart later
 
-                        if successor_cost - transition_cost + config.precision
+                        if successor_cost - transition_cost + precision
                             > current.state.safe_interval.end
-                            || successor_cost + config.precision > safe_interval.end
+                            || successor_cost + precision > safe_interval.end
                         {
                             continue;
                         }
                     }
                 }
 
+                if latest_departure
+                    .is_some_and(|latest| successor_cost - transition_cost > latest + precision)
+                {
+                    // Cannot wait before departing
+                    continue;
+                }
+
                 if successor_cost + heuristic >= self.goal_horizon {
                     // The remaining safe intervals at the goal state are not reachable in time
                     continue;
@@ -354,30 +721,109 @@ where
                     heuristic,
                 };
 
-                let improved = match self.distance.entry(successor.state.clone()) {
-                    Occupied(mut e) => {
-                        if successor_cost < *e.get() {
-                            *e.get_mut() = successor_cost;
-                            true
-                        } else {
-                            false
-                        }
-                    }
-                    Vacant(e) => {
-                        e.insert(successor_cost);
-                        true
+                // Equal-cost paths are compared by the smallest margin kept along them
+                let margin = Self::get_margin(
+                    constraints,
+                    &successor.state.internal_state,
+                    &successor.state.safe_interval,
+                    successor_cost,
+                );
+                let margin = min_margin(self.margin[&current.state], margin);
+
+                let improved = self.distance.get(&successor.state).is_none_or(|distance| {
+                    successor_cost < *distance
+                        || (successor_cost == *distance
+                            && cmp_margins(&margin, &self.margin[&successor.state])
+                                == Ordering::Greater)
+                });
+                if !improved {
+                    continue;
+                }
+
+                if let Some(threshold) = partial_expansion {
+                    let value = successor_cost + heuristic;
+                    if value > current.cost + current.heuristic + threshold + precision {
+                        // Generated when the current node is popped again
+                        deferred = Some(deferred.map_or(value, |d| d.min(value)));
+                        continue;
                     }
-                };
+                }
+
+                self.distance
+                    .insert(successor.state.clone(), successor_cost);
+                self.margin.insert(successor.state.clone(), margin);
+                self.parent.insert(
+                    successor.state.clone(),
+                    (Action::new(*action, transition_cost), current.state.clone()),
+                );
+                self.queue.push(Reverse(QueueNode::new(successor, margin)))
+            }
+        }
+
+        if let Some(value) = deferred {
+            // Enqueue the current node again with the value of its best remaining successor
+            let node = SearchNode {
+                state: current.state.clone(),
+                cost: current.cost,
+                heuristic: value - current.cost,
+            };
+            self.queue
+                .push(Reverse(QueueNode::new(node, self.margin[&current.state])));
+        }
+
+        self.stats.max_open = self.stats.max_open.max(self.queue.len());
+    }
 
-                if improved {
-                    self.parent.insert(
-                        successor.state.clone(),
-                        (Action::new(*action, transition_cost), current.state.clone()),
-                    );
-                    self.queue.push(Reverse(successor))
+    /// Returns all the safe intervals of the given state, given a set of constraints,
+    /// without running a search.
+    pub fn safe_intervals_for(
+        state: &S,
+        constraints: &Arc<ConstraintSet<S, C>>,
+        precision: DC,
+    ) -> Vec<Interval<C>> {
+        let mut safe_intervals = vec![];
+        Self::get_safe_intervals(
+            constraints,
+            state,
+            &Interval::default(),
+            precision,
+            &mut safe_intervals,
+        );
+        safe_intervals
+    }
+
+    /// Updates the safe intervals of the given state, as returned by `safe_intervals_for`,
+    /// after adding the given constraint. Only the intervals overlapping the constraint are split,
+    /// which gives the same result as recomputing the safe intervals with the new constraint.
+    pub fn update_safe_intervals(
+        state: &S,
+        safe_intervals: &mut Vec<Interval<C>>,
+        constraint: &Constraint<S, C>,
+        precision: DC,
+    ) {
+        if constraint.type_ != ConstraintType::State
+            || constraint.state.canonical() != state.canonical()
+        {
+            return;
+        }
+
+        let mut updated = Vec::with_capacity(safe_intervals.len() + 1);
+        for safe_interval in safe_intervals.drain(..) {
+            if !safe_interval.overlaps(&constraint.interval) {
+                updated.push(safe_interval);
+                continue;
+            }
+
+            // Keep the parts of the interval before and after the constraint
+            let before = Interval::new(safe_interval.start, constraint.interval.start);
+            let after = Interval::new(constraint.interval.end, safe_interval.end);
+            for part in [before, after] {
+                if part.start + precision < part.end {
+                    updated.push(part);
                 }
             }
         }
+        *safe_intervals = updated;
     }
 
     /// Computes the safe intervals for the given state, given a set of constraints,
@@ -407,12 +853,36 @@ where
         }
     }
 
+    /// Returns the weighted margin between the given time and the constraints bounding
+    /// the given safe interval of the given state, if any of them carries a buffer weight.
+    fn get_margin(
+        constraints: &Arc<ConstraintSet<S, C>>,
+        state: &S,
+        safe_interval: &Interval<C>,
+        time: C,
+    ) -> Option<DC> {
+        let mut margin = None;
+        for constraint in constraints.get_state_constraints(state)?.iter() {
+            if constraint.buffer_weight <= 0.0 {
+                continue;
+            }
+            if constraint.interval.end == safe_interval.start {
+                let before = (time - constraint.interval.end) * constraint.buffer_weight;
+                margin = min_margin(margin, Some(before));
+            } else if constraint.interval.start == safe_interval.end {
+                let after = (constraint.interval.start - time) * constraint.buffer_weight;
+                margin = min_margin(margin, Some(after));
+            }
+        }
+        margin
+    }
+
     /// Reconstructs the solution from the given goal search node.
     fn get_solution(
-        &self,
+        &mut self,
         config: &GeneralizedSippConfig<TS, S, A, C, DC, H>,
         goal: &SearchNode<SippState<S, C>, C, DC>,
-    ) -> Solution<Arc<SippState<S, C>>, A, C, DC> {
+    ) -> SippSolution<S, A, C, DC> {
         let mut solution = Solution::default();
         let mut current = goal.state.clone();
 
@@ -452,6 +922,7 @@ where
         solution.actions.reverse();
 
         solution.cost = solution.steps.last().unwrap().1;
+        self.stats.reconstructed += 1;
 
         solution
     }
@@ -462,6 +933,99 @@ where
     }
 }
 
+/// Compares two margins, where an unbounded margin is larger than any other.
+fn cmp_margins<DC>(a: &Option<DC>, b: &Option<DC>) -> Ordering
+where
+    DC: PartialOrd,
+{
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// Returns the smallest of two margins.
+fn min_margin<DC>(a: Option<DC>, b: Option<DC>) -> Option<DC>
+where
+    DC: PartialOrd,
+{
+    if cmp_margins(&a, &b) == Ordering::Greater {
+        b
+    } else {
+        a
+    }
+}
+
+/// Search node in the queue of the Safe Interval Path Planning algorithm.
+/// Nodes with equal (cost + heuristic) values are expanded in priority if they keep
+/// a larger margin around the constraints, which does not affect the cost of the solutions.
+struct QueueNode<S, C, DC>
+where
+    S: Debug + Eq,
+    C: Copy + Eq + Ord + Add<DC, Output = C> + LimitValues,
+    DC: Copy,
+{
+    node: SearchNode<SippState<S, C>, C, DC>,
+    margin: Option<DC>,
+}
+
+impl<S, C, DC> QueueNode<S, C, DC>
+where
+    S: Debug + Eq,
+    C: Copy + Eq + Ord + Add<DC, Output = C> + LimitValues,
+    DC: Copy,
+{
+    fn new(node: SearchNode<SippState<S, C>, C, DC>, margin: Option<DC>) -> Self {
+        QueueNode { node, margin }
+    }
+}
+
+impl<S, C, DC> PartialEq for QueueNode<S, C, DC>
+where
+    S: Debug + Eq,
+    C: Copy + Eq + Ord + Add<DC, Output = C> + LimitValues,
+    DC: Copy + PartialOrd,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S, C, DC> Eq for QueueNode<S, C, DC>
+where
+    S: Debug + Eq,
+    C: Copy + Eq + Ord + Add<DC, Output = C> + LimitValues,
+    DC: Copy + PartialOrd,
+{
+}
+
+impl<S, C, DC> PartialOrd for QueueNode<S, C, DC>
+where
+    S: Debug + Eq,
+    C: Copy + Eq + Ord + Add<DC, Output = C> + LimitValues,
+    DC: Copy + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C, DC> Ord for QueueNode<S, C, DC>
+where
+    S: Debug + Eq,
+    C: Copy + Eq + Ord + Add<DC, Output = C> + LimitValues,
+    DC: Copy + PartialOrd,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.node.cost + self.node.heuristic)
+            .cmp(&(other.node.cost + other.node.heuristic))
+            .then_with(|| cmp_margins(&other.margin, &self.margin)) // Larger margins first
+            .then_with(|| self.node.cmp(&other.node))
+    }
+}
+
 /// Input configuration for the Safe Interval Path Planning algorithm.
 pub struct SippConfig<TS, S, A, C, DC, H>
 where
@@ -483,6 +1047,8 @@ where
     constraints: Arc<ConstraintSet<S, C>>,
     heuristic: Arc<H>,
     precision: DC,
+    /// Time until which the agent must be able to stay at its goal state.
+    horizon: C,
     _phantom: PhantomData<(TS, S, A)>,
 }
 
@@ -514,9 +1080,16 @@ where
             constraints,
             heuristic,
             precision,
+            horizon: C::max_value(),
             _phantom: PhantomData,
         }
     }
+
+    /// Only requires the agent to stay safely at its goal state until the given horizon,
+    /// e.g. the end of a finite mission, instead of forever.
+    pub fn set_horizon(&mut self, horizon: C) {
+        self.horizon = horizon;
+    }
 }
 
 /// Input configuration for the Generalized Safe Interval Path Planning algorithm.
@@ -540,6 +1113,8 @@ where
     constraints: Arc<ConstraintSet<S, C>>,
     heuristic: Arc<H>,
     precision: DC,
+    /// Time until which the agent must be able to stay at its goal state.
+    horizon: C,
     _phantom: PhantomData<(TS, S, A)>,
 }
 
@@ -570,14 +1145,25 @@ where
             constraints,
             heuristic,
             precision,
+            horizon: C::max_value(),
             _phantom: PhantomData,
         }
     }
+
+    /// Only requires the agent to stay safely at its goal state until the given horizon,
+    /// e.g. the end of a finite mission, instead of forever.
+    pub fn set_horizon(&mut self, horizon: C) {
+        self.horizon = horizon;
+    }
 }
 
+/// Solution of the Safe Interval Path Planning algorithm, whose states carry their safe interval.
+pub type SippSolution<S, A, C, DC> = Solution<Arc<SippState<S, C>>, A, C, DC>;
+
 /// State wrapper for the Safe Interval Path Planning algorithm that extends
 /// a given state definition with a safe interval.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SippState<S, C>
 where
     S: Debug + Eq,
@@ -644,19 +1230,30 @@ where
         self.internal_task
             .is_goal_state(&state.state.internal_state)
     }
+
+    fn has_goal_predicate(&self) -> bool {
+        self.internal_task.goal_predicate.is_some()
+    }
 }
 
 /// Statistics of the Safe Interval Path Planning algorithm.
 #[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SippStats {
     pub searches: usize,
     pub expanded: usize,
+    /// Number of solutions reconstructed from the parents of the goal nodes.
+    pub reconstructed: usize,
+    /// Maximum number of nodes in the queue.
+    pub max_open: usize,
 }
 
 impl AddAssign for SippStats {
     fn add_assign(&mut self, rhs: Self) {
         self.searches += rhs.searches;
         self.expanded += rhs.expanded;
+        self.reconstructed += rhs.reconstructed;
+        self.max_open = self.max_open.max(rhs.max_open);
     }
 }
 
@@ -667,44 +1264,17 @@ mod tests {
     use ordered_float::OrderedFloat;
 
     use crate::{
-        search::sipp::sipp::SippConfig, Constraint, ConstraintSet, Graph, GraphEdgeId, GraphNodeId,
-        Interval, MyTime, ReverseResumableAStar, SimpleEdgeData, SimpleHeuristic, SimpleNodeData,
+        search::sipp::sipp::SippConfig, simple_graph, Constraint, ConstraintSet, GraphEdgeId,
+        GraphNodeId, Heuristic, Interval, MyTime, ReverseResumableAStar, SimpleHeuristic,
         SimpleState, SimpleWorld, Task,
     };
 
     use super::SafeIntervalPathPlanning;
 
-    fn simple_graph(size: usize) -> Arc<Graph<SimpleNodeData, SimpleEdgeData>> {
-        let mut graph = Graph::new();
-        for x in 0..size {
-            for y in 0..size {
-                graph.add_node((x as f32, y as f32));
-            }
-        }
-        for x in 0..size {
-            for y in 0..size {
-                let node_id = GraphNodeId(x + y * size);
-                if x > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x - 1 + y * size), 1.0);
-                }
-                if y > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y - 1) * size), 1.0);
-                }
-                if x < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + 1 + y * size), 1.0);
-                }
-                if y < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y + 1) * size), 1.0);
-                }
-            }
-        }
-        Arc::new(graph)
-    }
-
     #[test]
     fn test_simple() {
         let size = 10;
-        let graph = simple_graph(size);
+        let graph = Arc::new(simple_graph(size));
         let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
         let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
 
@@ -733,7 +1303,11 @@ mod tests {
                     solution.cost,
                     OrderedFloat(((size - x - 1) + (size - y - 1)) as f32)
                 );
-                assert_eq!(after.searches, before.searches + 1);
+                // No search is needed when the agent starts at its goal
+                assert_eq!(
+                    after.searches,
+                    before.searches + usize::from(!solution.actions.is_empty())
+                );
                 // Check that the perfect heuristic works
                 assert_eq!(after.expanded - before.expanded, solution.actions.len());
             }
@@ -787,10 +1361,207 @@ mod tests {
         assert_eq!(safe_intervals[2].start, times[3]);
     }
 
+    #[test]
+    fn test_safe_intervals_for() {
+        let state = SimpleState(GraphNodeId(0));
+        let other = SimpleState(GraphNodeId(1));
+
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            state.clone(),
+            Interval::new(OrderedFloat(2.0), OrderedFloat(4.0)),
+        )));
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            state.clone(),
+            Interval::new(OrderedFloat(7.0), OrderedFloat(9.0)),
+        )));
+        let constraints = Arc::new(constraints);
+
+        let safe_intervals = SafeIntervalPathPlanning::<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            MyTime,
+            MyTime,
+            SimpleHeuristic,
+        >::safe_intervals_for(&state, &constraints, OrderedFloat(1e-6));
+
+        assert_eq!(
+            safe_intervals,
+            vec![
+                Interval::new(Interval::default().start, OrderedFloat(2.0)),
+                Interval::new(OrderedFloat(4.0), OrderedFloat(7.0)),
+                Interval::new(OrderedFloat(9.0), Interval::default().end),
+            ]
+        );
+
+        // A state without constraints is always safe
+        let safe_intervals = SafeIntervalPathPlanning::<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            MyTime,
+            MyTime,
+            SimpleHeuristic,
+        >::safe_intervals_for(&other, &constraints, OrderedFloat(1e-6));
+
+        assert_eq!(safe_intervals, vec![Interval::default()]);
+    }
+
+    #[test]
+    fn test_update_safe_intervals() {
+        type Sipp = SafeIntervalPathPlanning<
+            SimpleWorld,
+            SimpleState,
+            GraphEdgeId,
+            MyTime,
+            MyTime,
+            SimpleHeuristic,
+        >;
+
+        let state = SimpleState(GraphNodeId(0));
+        let other = SimpleState(GraphNodeId(1));
+        let precision = OrderedFloat(1e-6);
+
+        // Disjoint, overlapping, nested and touching constraints, a gap smaller than the precision,
+        // and constraints on another state or on an action that do not affect the intervals
+        let constraints = [
+            Constraint::new_state_constraint(
+                0,
+                state.clone(),
+                Interval::new(5.0.into(), 6.0.into()),
+            ),
+            Constraint::new_state_constraint(
+                0,
+                state.clone(),
+                Interval::new(1.0.into(), 2.0.into()),
+            ),
+            Constraint::new_state_constraint(
+                0,
+                state.clone(),
+                Interval::new(5.5.into(), 7.0.into()),
+            ),
+            Constraint::new_state_constraint(
+                0,
+                other.clone(),
+                Interval::new(0.0.into(), 9.0.into()),
+            ),
+            Constraint::new_state_constraint(
+                0,
+                state.clone(),
+                Interval::new(2.0.into(), 3.0.into()),
+            ),
+            Constraint::new_state_constraint(
+                0,
+                state.clone(),
+                Interval::new(1.2.into(), 1.8.into()),
+            ),
+            Constraint::new_action_constraint(
+                0,
+                state.clone(),
+                other.clone(),
+                Interval::new(3.0.into(), 5.0.into()),
+            ),
+            Constraint::new_state_constraint(
+                0,
+                state.clone(),
+                Interval::new(7.0000005.into(), 8.0.into()),
+            ),
+            Constraint::new_state_constraint(
+                0,
+                state.clone(),
+                Interval::new(Interval::default().start, 0.5.into()),
+            ),
+            Constraint::new_state_constraint(
+                0,
+                state.clone(),
+                Interval::new(10.0.into(), Interval::default().end),
+            ),
+        ];
+
+        let mut safe_intervals =
+            Sipp::safe_intervals_for(&state, &Arc::new(ConstraintSet::default()), precision);
+        for (i, constraint) in constraints.iter().enumerate() {
+            // Recompute the safe intervals from scratch with all the constraints added so far
+            let mut constraint_set = ConstraintSet::default();
+            for constraint in constraints[..=i].iter() {
+                constraint_set.add(&Arc::new(constraint.clone()));
+            }
+            constraint_set.unify();
+            let expected = Sipp::safe_intervals_for(&state, &Arc::new(constraint_set), precision);
+
+            Sipp::update_safe_intervals(&state, &mut safe_intervals, constraint, precision);
+            assert_eq!(safe_intervals, expected);
+        }
+
+        assert_eq!(
+            safe_intervals,
+            vec![
+                Interval::new(0.5.into(), 1.0.into()),
+                Interval::new(3.0.into(), 5.0.into()),
+                Interval::new(8.0.into(), 10.0.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trivial_task() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        let state = SimpleState(GraphNodeId(size + 1));
+        let task = Arc::new(Task::new(state.clone(), state.clone(), OrderedFloat(3.0)));
+        let get_config = |constraints: ConstraintSet<SimpleState, MyTime>| {
+            SippConfig::new(
+                task.clone(),
+                Default::default(),
+                Arc::new(constraints),
+                Arc::new(ReverseResumableAStar::new(
+                    transition_system.clone(),
+                    task.clone(),
+                    SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+                )),
+                1e-6.into(),
+            )
+        };
+
+        // The agent already stands at its goal, no search is run
+        let solution = solver.solve(&get_config(ConstraintSet::default())).unwrap();
+        assert_eq!(solution.cost, OrderedFloat(3.0));
+        assert_eq!(solution.steps.len(), 1);
+        assert_eq!(solution.steps[0].0.internal_state, state);
+        assert!(solution.actions.is_empty());
+        assert_eq!(solver.get_stats().searches, 0);
+
+        // The agent must step aside and come back before a constraint on its goal
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            state.clone(),
+            Interval::new(OrderedFloat(5.0), OrderedFloat(6.0)),
+        )));
+        let solution = solver.solve(&get_config(constraints)).unwrap();
+        assert!(solution.cost >= OrderedFloat(6.0));
+        assert!(!solution.actions.is_empty());
+
+        // The start is blocked at the initial time
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            state.clone(),
+            Interval::new(OrderedFloat(2.0), OrderedFloat(4.0)),
+        )));
+        assert!(solver.solve(&get_config(constraints)).is_none());
+    }
+
     #[test]
     fn test_with_constraints() {
         let size = 10;
-        let graph = simple_graph(size);
+        let graph = Arc::new(simple_graph(size));
         let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
         let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
 
@@ -844,4 +1615,286 @@ mod tests {
 
         assert_eq!(solution.cost, OrderedFloat(24.0));
     }
+
+    #[test]
+    fn test_reachable_within() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph.clone(), 0.4));
+        let mut solver: SafeIntervalPathPlanning<_, _, _, _, _, SimpleHeuristic> =
+            SafeIntervalPathPlanning::new(transition_system.clone());
+
+        // The second state is occupied for longer than the budget
+        let blocked = GraphNodeId(1);
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(blocked),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(10.0)),
+        )));
+        let constraints = Arc::new(constraints);
+
+        let budget = 4.0;
+        let reachable = solver.reachable_within(
+            &SimpleState(GraphNodeId(0)),
+            OrderedFloat(0.0),
+            OrderedFloat(budget),
+            &constraints,
+            1e-6.into(),
+        );
+
+        // Breadth-first search on the grid without the blocked state
+        let mut times = vec![None; size * size];
+        let mut queue = std::collections::VecDeque::from([(GraphNodeId(0), 0.0)]);
+        times[0] = Some(0.0);
+        while let Some((node, time)) = queue.pop_front() {
+            for edge in graph.get_edges_out(node) {
+                let next = graph.get_edge(*edge).to;
+                if next != blocked && times[next.0].is_none() && time + 1.0 <= budget {
+                    times[next.0] = Some(time + 1.0);
+                    queue.push_back((next, time + 1.0));
+                }
+            }
+        }
+
+        assert_eq!(
+            reachable.len(),
+            times.iter().filter(|t| t.is_some()).count()
+        );
+        for (node, time) in times.iter().enumerate() {
+            if let Some(time) = time {
+                assert_eq!(
+                    reachable[&SimpleState(GraphNodeId(node))],
+                    OrderedFloat(*time)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_k_best() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        // There are two paths of cost 2 around the first state of the grid
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(size + 1)),
+            OrderedFloat(0.0),
+        ));
+        let config = SippConfig::new(
+            task.clone(),
+            Default::default(),
+            Default::default(),
+            Arc::new(ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            )),
+            1e-6.into(),
+        );
+
+        let solutions = solver.solve_k_best(&config, 2);
+        assert_eq!(solutions.len(), 2);
+        assert!(solutions.iter().all(|sol| sol.cost == OrderedFloat(2.0)));
+        assert_ne!(solutions[0].steps[1].0, solutions[1].steps[1].0);
+
+        let solutions = solver.solve_k_best(&config, 3);
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions[2].cost > OrderedFloat(2.0));
+    }
+
+    #[test]
+    fn test_buffer_weight() {
+        let size = 3;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        // Two paths of equal cost to the opposite corner of a square
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(size + 1)),
+            OrderedFloat(0.0),
+        ));
+        let heuristic = Arc::new(ReverseResumableAStar::new(
+            transition_system.clone(),
+            task.clone(),
+            SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+        ));
+
+        for (tight, loose) in [(1, size), (size, 1)] {
+            // Both intermediate states are reserved shortly after the agent arrives there
+            let mut constraints = ConstraintSet::default();
+            for (node, start) in [(tight, 1.2), (loose, 1.5)] {
+                let mut constraint = Constraint::new_state_constraint(
+                    0,
+                    SimpleState(GraphNodeId(node)),
+                    Interval::new(OrderedFloat(start), OrderedFloat(3.0)),
+                );
+                constraint.buffer_weight = 1.0;
+                constraints.add(&Arc::new(constraint));
+            }
+
+            let config = SippConfig::new(
+                task.clone(),
+                Default::default(),
+                Arc::new(constraints),
+                heuristic.clone(),
+                1e-6.into(),
+            );
+            let solution = solver.solve(&config).unwrap();
+
+            assert_eq!(solution.cost, OrderedFloat(2.0));
+            assert_eq!(
+                solution.steps[1].0.internal_state,
+                SimpleState(GraphNodeId(loose))
+            );
+        }
+    }
+
+    /// Distance to the nearest node of the first column of the grid.
+    struct ColumnHeuristic(usize);
+
+    impl Heuristic<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime> for ColumnHeuristic {
+        fn get_heuristic(&self, state: &SimpleState) -> Option<MyTime> {
+            Some(OrderedFloat((state.0 .0 % self.0) as f32))
+        }
+    }
+
+    #[test]
+    fn test_goal_predicate() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        let task = Arc::new(Task::new_with_goal_predicate(
+            SimpleState(GraphNodeId(4 + 3 * size)),
+            SimpleState(GraphNodeId(3 * size)),
+            OrderedFloat(0.0),
+            Arc::new(move |state: &SimpleState| state.0 .0.is_multiple_of(size)),
+        ));
+        let get_config = |constraints: ConstraintSet<SimpleState, MyTime>| {
+            SippConfig::new(
+                task.clone(),
+                Default::default(),
+                Arc::new(constraints),
+                Arc::new(ColumnHeuristic(size)),
+                1e-6.into(),
+            )
+        };
+
+        // The agent stops at the nearest node of the first column
+        let solution = solver.solve(&get_config(ConstraintSet::default())).unwrap();
+        assert_eq!(solution.cost, OrderedFloat(4.0));
+        let last = &solution.steps.last().unwrap().0.internal_state;
+        assert_eq!(*last, SimpleState(GraphNodeId(3 * size)));
+
+        // The nearest node is blocked forever, any other node of the first column will do
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(GraphNodeId(3 * size)),
+            Interval::default(),
+        )));
+        let solution = solver.solve(&get_config(constraints)).unwrap();
+        assert_eq!(solution.cost, OrderedFloat(5.0));
+        let last = &solution.steps.last().unwrap().0.internal_state;
+        assert!(last.0 .0.is_multiple_of(size));
+        assert_ne!(*last, SimpleState(GraphNodeId(3 * size)));
+    }
+
+    #[test]
+    fn test_solve_cost() {
+        let size = 6;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(GraphNodeId(size + 1)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(5.0)),
+        )));
+        let constraints = Arc::new(constraints);
+
+        for goal in 0..size * size {
+            let task = Arc::new(Task::new(
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(goal)),
+                OrderedFloat(0.0),
+            ));
+            let config = SippConfig::new(
+                task.clone(),
+                Default::default(),
+                constraints.clone(),
+                Arc::new(ReverseResumableAStar::new(
+                    transition_system.clone(),
+                    task.clone(),
+                    SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+                )),
+                1e-6.into(),
+            );
+
+            let reconstructed = solver.get_stats().reconstructed;
+            let cost = solver.solve_cost(&config);
+            assert_eq!(solver.get_stats().reconstructed, reconstructed);
+
+            let solution = solver.solve(&config).unwrap();
+            assert_eq!(cost, Some(solution.cost));
+        }
+    }
+
+    #[test]
+    fn test_partial_expansion() {
+        let size = 20;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+
+        // A wall with a single gap on the side blocks the direct way for a while
+        let mut constraints = ConstraintSet::default();
+        for x in 1..size {
+            constraints.add(&Arc::new(Constraint::new_state_constraint(
+                0,
+                SimpleState(GraphNodeId(x + 15 * size)),
+                Interval::new(OrderedFloat(0.0), OrderedFloat(30.0)),
+            )));
+        }
+        let constraints = Arc::new(constraints);
+
+        let solve = |partial_expansion| {
+            let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+            solver.set_partial_expansion(partial_expansion);
+
+            let task = Arc::new(Task::new(
+                SimpleState(GraphNodeId(size / 2 + size / 2 * size)),
+                SimpleState(GraphNodeId(size * size - 1)),
+                OrderedFloat(0.0),
+            ));
+            let config = SippConfig::new(
+                task.clone(),
+                Default::default(),
+                constraints.clone(),
+                Arc::new(ReverseResumableAStar::new(
+                    transition_system.clone(),
+                    task.clone(),
+                    SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+                )),
+                1e-6.into(),
+            );
+            let solution = solver.solve(&config).unwrap();
+            (solution.cost, solver.get_stats())
+        };
+
+        let (cost, stats) = solve(None);
+        let (partial_cost, partial_stats) = solve(Some(OrderedFloat(0.0)));
+
+        // The costlier successors of the nodes around the wall are never generated
+        assert_eq!(partial_cost, cost);
+        assert!(partial_stats.max_open < stats.max_open);
+    }
 }
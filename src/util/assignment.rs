@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use ordered_float::OrderedFloat;
+
+use crate::{DistanceMatrix, MyTime, SimpleState, Task};
+
+/// Returns the cost of assigning each agent to each goal state, given by the distance
+/// between its initial state and that goal state, or None if the goal state is not reachable.
+pub fn get_assignment_costs(
+    distances: &DistanceMatrix<MyTime>,
+    initial_states: &[SimpleState],
+    goal_states: &[SimpleState],
+) -> Vec<Vec<Option<MyTime>>> {
+    initial_states
+        .iter()
+        .map(|initial| {
+            goal_states
+                .iter()
+                .map(|goal| distances.get(initial.0, goal.0))
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the total cost of the given assignment of agents to goals.
+pub fn get_assignment_cost(costs: &[Vec<Option<MyTime>>], assignment: &[usize]) -> Option<MyTime> {
+    assignment
+        .iter()
+        .enumerate()
+        .map(|(agent, goal)| costs[agent][*goal])
+        .sum()
+}
+
+/// Assigns each agent to a distinct goal by repeatedly picking the cheapest remaining pair.
+/// Returns None if some agent cannot reach any of the remaining goals.
+pub fn greedy_assignment(costs: &[Vec<Option<MyTime>>]) -> Option<Vec<usize>> {
+    let mut pairs = costs
+        .iter()
+        .enumerate()
+        .flat_map(|(agent, row)| {
+            row.iter()
+                .enumerate()
+                .filter_map(move |(goal, cost)| cost.map(|cost| (cost, agent, goal)))
+        })
+        .collect::<Vec<_>>();
+    pairs.sort_unstable();
+
+    let n_goals = costs.first().map_or(0, |row| row.len());
+    let mut assignment = vec![None; costs.len()];
+    let mut taken = vec![false; n_goals];
+    for (_, agent, goal) in pairs {
+        if assignment[agent].is_none() && !taken[goal] {
+            assignment[agent] = Some(goal);
+            taken[goal] = true;
+        }
+    }
+
+    assignment.into_iter().collect()
+}
+
+/// Assigns each agent to a distinct goal while minimizing the total cost, with the Hungarian algorithm.
+/// Returns None if there are more agents than goals, or if no assignment allows all the agents to reach their goal.
+pub fn optimal_assignment(costs: &[Vec<Option<MyTime>>]) -> Option<Vec<usize>> {
+    let n = costs.len();
+    let m = costs.first().map_or(0, |row| row.len());
+    if n > m {
+        return None;
+    }
+
+    // Unreachable goals are given a cost that exceeds any feasible assignment
+    let unreachable = costs
+        .iter()
+        .flatten()
+        .map(|cost| cost.map_or(0.0, |c| c.0 as f64))
+        .sum::<f64>()
+        + 1.0;
+    let cost = |i: usize, j: usize| costs[i - 1][j - 1].map_or(unreachable, |c| c.0 as f64);
+
+    // Potentials of the agents and goals, and agent assigned to each goal (1-indexed)
+    let mut u = vec![0.0; n + 1];
+    let mut v = vec![0.0; m + 1];
+    let mut p = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+
+    for i in 1..=n {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![f64::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if !used[j] {
+                    let reduced = cost(i0, j) - u[i0] - v[j];
+                    if reduced < minv[j] {
+                        minv[j] = reduced;
+                        way[j] = j0;
+                    }
+                    if minv[j] < delta {
+                        delta = minv[j];
+                        j1 = j;
+                    }
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+
+        // Augment along the alternating path
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assignment = vec![0; n];
+    for j in 1..=m {
+        if p[j] != 0 {
+            assignment[p[j] - 1] = j - 1;
+        }
+    }
+
+    get_assignment_cost(costs, &assignment).map(|_| assignment)
+}
+
+/// Builds the tasks obtained by sending each agent to its assigned goal state.
+pub fn get_assigned_tasks(
+    initial_states: &[SimpleState],
+    goal_states: &[SimpleState],
+    assignment: &[usize],
+) -> Vec<Arc<Task<SimpleState, MyTime>>> {
+    initial_states
+        .iter()
+        .zip(assignment.iter())
+        .map(|(initial, goal)| {
+            Arc::new(Task::new(
+                initial.clone(),
+                goal_states[*goal].clone(),
+                OrderedFloat(0.0),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        get_assigned_tasks, get_assignment_cost, get_assignment_costs, greedy_assignment,
+        optimal_assignment, simple_graph, CbsConfig, ConflictBasedSearch, GraphNodeId, MyTime,
+        ReverseResumableAStar, SimpleHeuristic, SimpleState, SimpleWorld,
+    };
+
+    fn brute_force(costs: &[Vec<Option<MyTime>>], agent: usize, taken: &mut Vec<bool>) -> MyTime {
+        if agent == costs.len() {
+            return OrderedFloat(0.0);
+        }
+        let mut best = OrderedFloat(f32::MAX);
+        for goal in 0..taken.len() {
+            if !taken[goal] {
+                taken[goal] = true;
+                best = best.min(costs[agent][goal].unwrap() + brute_force(costs, agent + 1, taken));
+                taken[goal] = false;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_assignment() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let distances = transition_system.all_pairs_distances();
+
+        // Pseudo-random distinct initial and goal states
+        let mut seed = 42;
+        let mut used = vec![];
+        let mut pick = || loop {
+            seed = (seed * 1103515245 + 12345) % 2147483648;
+            let node = (seed / 65536) % (size * size);
+            if !used.contains(&node) {
+                used.push(node);
+                return SimpleState(GraphNodeId(node));
+            }
+        };
+        let n_agents = 6;
+        let initial_states = (0..n_agents).map(|_| pick()).collect::<Vec<_>>();
+        let goal_states = (0..n_agents).map(|_| pick()).collect::<Vec<_>>();
+
+        let costs = get_assignment_costs(&distances, &initial_states, &goal_states);
+        let optimal = optimal_assignment(&costs).unwrap();
+        let greedy = greedy_assignment(&costs).unwrap();
+
+        let optimal_cost = get_assignment_cost(&costs, &optimal).unwrap();
+        let greedy_cost = get_assignment_cost(&costs, &greedy).unwrap();
+
+        assert_eq!(
+            optimal_cost,
+            brute_force(&costs, 0, &mut vec![false; n_agents])
+        );
+        assert!(optimal_cost <= greedy_cost);
+        assert!(greedy_cost <= optimal_cost * 1.5);
+
+        // Some agent is left without a goal
+        let costs = get_assignment_costs(&distances, &initial_states, &goal_states[1..]);
+        assert!(optimal_assignment(&costs).is_none());
+        assert!(greedy_assignment(&costs).is_none());
+
+        // The assignment seeds CBS, whose cost is bounded by the assignment cost
+        let tasks = get_assigned_tasks(&initial_states, &goal_states, &optimal);
+        let pivots = Arc::new(tasks.iter().map(|t| t.goal_state.clone()).collect());
+        let heuristic_to_pivots = Arc::new(
+            tasks
+                .iter()
+                .map(|t| {
+                    Arc::new(ReverseResumableAStar::new(
+                        transition_system.clone(),
+                        t.clone(),
+                        SimpleHeuristic::new(transition_system.clone(), Arc::new(t.reverse())),
+                    ))
+                })
+                .collect(),
+        );
+        let config = CbsConfig::new(tasks, pivots, heuristic_to_pivots, OrderedFloat(1e-6));
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+
+        assert!(solutions.iter().map(|sol| sol.cost).sum::<MyTime>() >= optimal_cost);
+    }
+}
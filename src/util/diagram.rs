@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use crate::{
+    ConstraintSet, GraphEdgeId, GraphNodeId, Interval, LimitValues, MyTime,
+    SafeIntervalPathPlanning, SimpleHeuristic, SimpleState, SimpleWorld, SippState, Solution,
+};
+
+/// Row of a time-space diagram: a cell, its safe intervals, and the interval during which
+/// the agent occupies it, if it visits the cell.
+pub type TimeSpaceRow = (GraphNodeId, Vec<Interval<MyTime>>, Option<Interval<MyTime>>);
+
+/// Returns the data of a time-space diagram of the given cells for an agent, given its constraints
+/// and its solution, which can be rendered as a Gantt chart to debug the interactions of constraints.
+/// The occupancy of a cell spans from the first arrival of the agent to its departure,
+/// and is unbounded at the last cell of the solution.
+pub fn get_time_space_diagram(
+    cells: &[GraphNodeId],
+    constraints: &Arc<ConstraintSet<SimpleState, MyTime>>,
+    solution: &Solution<Arc<SippState<SimpleState, MyTime>>, GraphEdgeId, MyTime, MyTime>,
+    precision: MyTime,
+) -> Vec<TimeSpaceRow> {
+    cells
+        .iter()
+        .map(|cell| {
+            let state = SimpleState(*cell);
+            let safe_intervals = SafeIntervalPathPlanning::<
+                SimpleWorld,
+                SimpleState,
+                GraphEdgeId,
+                MyTime,
+                MyTime,
+                SimpleHeuristic,
+            >::safe_intervals_for(&state, constraints, precision);
+
+            (*cell, safe_intervals, get_occupancy(solution, &state))
+        })
+        .collect()
+}
+
+/// Returns the interval during which the agent first occupies the given state along its solution.
+fn get_occupancy(
+    solution: &Solution<Arc<SippState<SimpleState, MyTime>>, GraphEdgeId, MyTime, MyTime>,
+    state: &SimpleState,
+) -> Option<Interval<MyTime>> {
+    let arrival = solution
+        .steps
+        .iter()
+        .position(|(s, _)| s.internal_state == *state)?;
+
+    // The agent stays until the start of the move that leaves the state, waits included
+    let departure = (arrival + 1..solution.steps.len())
+        .find(|i| solution.steps[*i].0.internal_state != *state)
+        .map_or(MyTime::max_value(), |i| solution.steps[i - 1].1);
+
+    Some(Interval::new(solution.steps[arrival].1, departure))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        get_time_space_diagram, simple_graph, Constraint, ConstraintSet, GraphNodeId, Interval,
+        LimitValues, MyTime, ReverseResumableAStar, SafeIntervalPathPlanning, SimpleHeuristic,
+        SimpleState, SimpleWorld, SippConfig, Task,
+    };
+
+    #[test]
+    fn test_time_space_diagram() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(3)),
+            OrderedFloat(0.0),
+        ));
+
+        // The agent must wait before entering the second and the third cells
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(GraphNodeId(1)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(2.0)),
+        )));
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(GraphNodeId(2)),
+            Interval::new(OrderedFloat(1.0), OrderedFloat(3.5)),
+        )));
+        let constraints = Arc::new(constraints);
+
+        let config = SippConfig::new(
+            task.clone(),
+            Default::default(),
+            constraints.clone(),
+            Arc::new(ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            )),
+            1e-6.into(),
+        );
+        let solution = solver.solve(&config).unwrap();
+
+        let cells = (0..5).map(GraphNodeId).collect::<Vec<_>>();
+        let diagram = get_time_space_diagram(&cells, &constraints, &solution, 1e-6.into());
+        assert_eq!(diagram.len(), cells.len());
+
+        // The agent never visits the last cell, and stays forever at its goal
+        assert!(diagram[4].2.is_none());
+        assert_eq!(diagram[3].2.unwrap().end, MyTime::max_value());
+        assert_eq!(diagram[2].1.len(), 2);
+
+        // Each occupancy lies within a safe interval of its cell
+        for (cell, safe_intervals, occupancy) in &diagram[..4] {
+            let occupancy = occupancy.unwrap();
+            assert!(
+                safe_intervals.iter().any(|i| i.contains(&occupancy)),
+                "{cell:?} occupied during {occupancy:?} outside of {safe_intervals:?}"
+            );
+        }
+    }
+}
@@ -5,7 +5,7 @@ use quick_xml::{de::from_str, DeError};
 use serde::Deserialize;
 
 use crate::{
-    CbsConfig, ConflictBasedSearch, Graph, GraphEdgeId, GraphNodeId, MyTime, ReverseResumableAStar,
+    build_heuristics, CbsConfig, ConflictBasedSearch, Graph, GraphEdgeId, GraphNodeId, MyTime,
     SimpleEdgeData, SimpleHeuristic, SimpleNodeData, SimpleState, SimpleWorld, Task,
 };
 
@@ -24,18 +24,7 @@ pub fn get_cbs_from_files(
     let transition_system = Arc::new(SimpleWorld::new(graph.clone(), config.agent_size));
 
     let pivots = Arc::new(tasks.iter().map(|t| t.goal_state.clone()).collect());
-    let heuristic_to_pivots = Arc::new(
-        tasks
-            .iter()
-            .map(|t| {
-                Arc::new(ReverseResumableAStar::new(
-                    transition_system.clone(),
-                    t.clone(),
-                    SimpleHeuristic::new(transition_system.clone(), Arc::new(t.reverse())),
-                ))
-            })
-            .collect(),
-    );
+    let heuristic_to_pivots = Arc::new(build_heuristics(&transition_system, &tasks));
 
     (
         graph,
@@ -1,3 +1,9 @@
+mod assignment;
+mod diagram;
 mod mapf_info;
+mod trajectory;
 
+pub use assignment::*;
+pub use diagram::*;
 pub use mapf_info::*;
+pub use trajectory::*;
@@ -0,0 +1,86 @@
+/// Timed position of an agent along its trajectory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Waypoint {
+    pub time: f32,
+    pub x: f32,
+    pub y: f32,
+    /// Speed of the agent until the next waypoint, if any.
+    pub velocity: Option<f32>,
+}
+
+/// Plan of a single agent as a sequence of waypoints, which can easily be converted
+/// to the trajectory messages of robotic frameworks.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trajectory {
+    pub agent: usize,
+    pub waypoints: Vec<Waypoint>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        simple_graph, Constraint, ConstraintSet, GraphNodeId, Interval, ReverseResumableAStar,
+        SafeIntervalPathPlanning, SimpleHeuristic, SimpleState, SimpleWorld, SippConfig,
+        SolutionSet, Task,
+    };
+
+    #[test]
+    fn test_trajectories() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph.clone(), 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(2)),
+            OrderedFloat(0.0),
+        ));
+
+        // The agent must wait one time unit before entering the second state
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            SimpleState(GraphNodeId(1)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(2.0)),
+        )));
+
+        let config = SippConfig::new(
+            task.clone(),
+            Default::default(),
+            Arc::new(constraints),
+            Arc::new(ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            )),
+            1e-6.into(),
+        );
+        let solution = solver.solve(&config).unwrap();
+
+        let trajectories = [solution].to_trajectories(|state| graph.get_node(state.0).data);
+        assert_eq!(trajectories.len(), 1);
+        assert_eq!(trajectories[0].agent, 0);
+
+        let waypoints = trajectories[0]
+            .waypoints
+            .iter()
+            .map(|w| (w.time, w.x, w.y, w.velocity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            waypoints,
+            vec![
+                (0.0, 0.0, 0.0, Some(0.0)),
+                (1.0, 0.0, 0.0, Some(1.0)),
+                (2.0, 0.0, 1.0, Some(1.0)),
+                (3.0, 0.0, 2.0, None),
+            ]
+        );
+    }
+}
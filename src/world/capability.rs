@@ -0,0 +1,421 @@
+use std::sync::Arc;
+
+use fxhash::FxHashMap;
+use tuple::{A2, T2};
+
+use crate::{
+    Graph, GraphEdgeId, GraphNodeId, Heuristic, MinimalHeuristic, Move, MyTime, SimpleEdgeData,
+    SimpleNodeData, SimpleState, SimpleWorld, State, Task, TransitionSystem,
+};
+
+/// Set of capabilities of an agent as a bit mask, e.g. to board an elevator.
+pub type Capabilities = u32;
+
+/// A world described by a directed weighted graph shared by a heterogeneous fleet, in which
+/// some edges can only be traversed by the agents that have the required capabilities.
+/// Each agent carries its capabilities in its states, so that the actions available to each agent
+/// differ while the conflicts are detected between the positions of all agents alike.
+pub struct CapabilityWorld {
+    graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
+    world: SimpleWorld,
+    /// Capabilities required to traverse each restricted edge, along with its positive duration,
+    /// which may differ from the distance between its nodes, e.g. for a teleport.
+    restricted: FxHashMap<GraphEdgeId, (Capabilities, MyTime)>,
+    /// Edges out of and into each node that can be traversed with each set of capabilities of the fleet.
+    edges: FxHashMap<Capabilities, A2<Vec<Vec<GraphEdgeId>>>>,
+    /// Connected component of each node with each set of capabilities of the fleet,
+    /// ignoring the directions of the edges.
+    components: FxHashMap<Capabilities, Vec<usize>>,
+}
+
+impl CapabilityWorld {
+    /// Creates a world with the given restricted edges, in which the agents have one of the given sets
+    /// of capabilities. Agents with other capabilities can only traverse the unrestricted edges.
+    pub fn new(
+        graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
+        agent_size: f32,
+        restricted: FxHashMap<GraphEdgeId, (Capabilities, MyTime)>,
+        fleet: &[Capabilities],
+    ) -> Self {
+        let mut edges = FxHashMap::default();
+        for capabilities in fleet.iter().copied().chain([0]) {
+            let allowed = |edges: &[GraphEdgeId]| {
+                edges
+                    .iter()
+                    .filter(|edge| {
+                        restricted
+                            .get(*edge)
+                            .is_none_or(|(required, _)| capabilities & required == *required)
+                    })
+                    .copied()
+                    .collect::<Vec<_>>()
+            };
+            let nodes = (0..graph.num_nodes()).map(GraphNodeId);
+            edges.insert(
+                capabilities,
+                T2(
+                    nodes
+                        .clone()
+                        .map(|node| allowed(graph.get_edges_out(node)))
+                        .collect(),
+                    nodes
+                        .map(|node| allowed(graph.get_edges_in(node)))
+                        .collect(),
+                ),
+            );
+        }
+
+        let components = edges
+            .iter()
+            .map(|(capabilities, edges)| (*capabilities, Self::get_components(&graph, edges)))
+            .collect();
+
+        CapabilityWorld {
+            graph: graph.clone(),
+            world: SimpleWorld::new(graph, agent_size),
+            restricted,
+            edges,
+            components,
+        }
+    }
+
+    /// Labels each node with its connected component in the graph restricted to the given edges.
+    fn get_components(
+        graph: &Graph<SimpleNodeData, SimpleEdgeData>,
+        edges: &A2<Vec<Vec<GraphEdgeId>>>,
+    ) -> Vec<usize> {
+        let mut components = vec![usize::MAX; graph.num_nodes()];
+        for root in 0..graph.num_nodes() {
+            if components[root] != usize::MAX {
+                continue;
+            }
+            components[root] = root;
+            let mut stack = vec![root];
+            while let Some(node) = stack.pop() {
+                for edge in edges.0[node].iter().chain(edges.1[node].iter()) {
+                    let edge = graph.get_edge(*edge);
+                    for next in [edge.from.0, edge.to.0] {
+                        if components[next] == usize::MAX {
+                            components[next] = root;
+                            stack.push(next);
+                        }
+                    }
+                }
+            }
+        }
+        components
+    }
+
+    pub fn time_between(&self, from: GraphNodeId, to: GraphNodeId) -> MyTime {
+        self.world.time_between(from, to)
+    }
+
+    pub fn time(&self, edge: GraphEdgeId) -> MyTime {
+        self.restricted
+            .get(&edge)
+            .map_or_else(|| self.world.time(edge), |(_, duration)| *duration)
+    }
+
+    /// Returns the restricted edges that the agents with the given capabilities can traverse,
+    /// along with their durations.
+    pub fn get_restricted_edges(
+        &self,
+        capabilities: Capabilities,
+    ) -> impl Iterator<Item = (GraphEdgeId, MyTime)> + '_ {
+        self.restricted
+            .iter()
+            .filter(move |(_, (required, _))| capabilities & required == *required)
+            .map(|(edge, (_, duration))| (*edge, *duration))
+    }
+
+    /// Returns true if both nodes belong to the same connected component for the agents
+    /// with the given capabilities, ignoring the directions of the edges.
+    pub fn connected(
+        &self,
+        from: GraphNodeId,
+        to: GraphNodeId,
+        capabilities: Capabilities,
+    ) -> bool {
+        let components = self
+            .components
+            .get(&capabilities)
+            .unwrap_or_else(|| &self.components[&0]);
+        components[from.0] == components[to.0]
+    }
+
+    fn get_edges(&self, capabilities: Capabilities) -> &A2<Vec<Vec<GraphEdgeId>>> {
+        self.edges
+            .get(&capabilities)
+            .unwrap_or_else(|| &self.edges[&0])
+    }
+}
+
+/// A node of the graph along with the capabilities of the agent that occupies it.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct CapabilityState(pub GraphNodeId, pub Capabilities);
+
+impl State for CapabilityState {
+    fn is_equivalent(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl TransitionSystem<CapabilityState, GraphEdgeId, MyTime, MyTime> for CapabilityWorld {
+    fn actions_from(&self, state: &CapabilityState) -> std::slice::Iter<'_, GraphEdgeId> {
+        self.get_edges(state.1).0[state.0 .0].iter()
+    }
+
+    fn transition(&self, state: &CapabilityState, action: &GraphEdgeId) -> CapabilityState {
+        CapabilityState(self.graph.get_edge(*action).to, state.1)
+    }
+
+    fn transition_cost(&self, _state: &CapabilityState, action: &GraphEdgeId) -> MyTime {
+        self.time(*action)
+    }
+
+    fn reverse_actions_from(&self, state: &CapabilityState) -> std::slice::Iter<'_, GraphEdgeId> {
+        self.get_edges(state.1).1[state.0 .0].iter()
+    }
+
+    fn reverse_transition(&self, state: &CapabilityState, action: &GraphEdgeId) -> CapabilityState {
+        CapabilityState(self.graph.get_edge(*action).from, state.1)
+    }
+
+    fn reverse_transition_cost(&self, _state: &CapabilityState, action: &GraphEdgeId) -> MyTime {
+        self.time(*action)
+    }
+
+    fn can_wait_at(&self, _state: &CapabilityState) -> bool {
+        true
+    }
+
+    fn reachable(&self, from: &CapabilityState, to: &CapabilityState) -> bool {
+        self.connected(from.0, to.0, from.1)
+    }
+
+    fn conflict(&self, moves: A2<&Move<CapabilityState, GraphEdgeId, MyTime>>) -> bool {
+        let to_simple = |m: &Move<CapabilityState, GraphEdgeId, MyTime>| {
+            Move::new(
+                m.agent,
+                SimpleState(m.from.0),
+                SimpleState(m.to.0),
+                m.action,
+                m.interval,
+            )
+        };
+        self.world
+            .conflict(T2(&to_simple(moves[0]), &to_simple(moves[1])))
+    }
+}
+
+/// Euclidean distance to the goal node, or the shorter bound on the paths that traverse the restricted
+/// edges available to the agent, which may be shortcuts: such a path reaches an endpoint of these edges,
+/// traverses at least one of them, and leaves from an endpoint, in either direction of the search.
+pub struct CapabilityHeuristic {
+    transition_system: Arc<CapabilityWorld>,
+    goal_state: CapabilityState,
+    /// Endpoints of the available restricted edges.
+    endpoints: Vec<GraphNodeId>,
+    /// Shortest duration of an available restricted edge plus the distance from the closest endpoint
+    /// to the goal node, if any restricted edge is available.
+    detour: Option<MyTime>,
+}
+
+impl CapabilityHeuristic {
+    pub fn new(
+        transition_system: Arc<CapabilityWorld>,
+        task: Arc<Task<CapabilityState, MyTime>>,
+    ) -> Self {
+        let goal_state = task.goal_state.clone();
+        let mut endpoints = Vec::new();
+        let mut duration = None;
+        for (edge, time) in transition_system.get_restricted_edges(goal_state.1) {
+            let edge = transition_system.graph.get_edge(edge);
+            endpoints.extend([edge.from, edge.to]);
+            duration = Some(duration.map_or(time, |duration: MyTime| duration.min(time)));
+        }
+        let detour = duration.map(|duration| {
+            duration
+                + endpoints
+                    .iter()
+                    .map(|node| transition_system.time_between(*node, goal_state.0))
+                    .min()
+                    .unwrap()
+        });
+
+        CapabilityHeuristic {
+            transition_system,
+            goal_state,
+            endpoints,
+            detour,
+        }
+    }
+}
+
+impl Heuristic<CapabilityWorld, CapabilityState, GraphEdgeId, MyTime, MyTime>
+    for CapabilityHeuristic
+{
+    fn get_heuristic(&self, state: &CapabilityState) -> Option<MyTime> {
+        let direct = self
+            .transition_system
+            .time_between(state.0, self.goal_state.0);
+        Some(self.detour.map_or(direct, |detour| {
+            self.endpoints
+                .iter()
+                .map(|node| self.transition_system.time_between(state.0, *node) + detour)
+                .min()
+                .unwrap()
+                .min(direct)
+        }))
+    }
+}
+
+impl MinimalHeuristic<CapabilityWorld, CapabilityState, GraphEdgeId, MyTime, MyTime>
+    for CapabilityHeuristic
+{
+    fn build(
+        transition_system: Arc<CapabilityWorld>,
+        task: Arc<Task<CapabilityState, MyTime>>,
+    ) -> Self {
+        Self::new(transition_system, task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use fxhash::FxHashMap;
+    use ordered_float::OrderedFloat;
+    use tuple::T2;
+
+    use crate::{
+        simple_graph, CapabilityHeuristic, CapabilityState, CapabilityWorld, CbsConfig,
+        ConflictBasedSearch, GraphNodeId, Heuristic, ReverseResumableAStar, Task, TransitionSystem,
+    };
+
+    #[test]
+    fn test_capabilities() {
+        let size = 5;
+        let corner = size * size - 1;
+        let mut graph = simple_graph(size);
+
+        // A teleport between opposite corners, which only the agents with the first capability can use
+        let teleport = graph.add_edge(GraphNodeId(0), GraphNodeId(corner), 1.0);
+        let mut restricted = FxHashMap::default();
+        restricted.insert(teleport, (1, OrderedFloat(0.5)));
+        let transition_system = Arc::new(CapabilityWorld::new(
+            Arc::new(graph),
+            0.4,
+            restricted,
+            &[0, 1],
+        ));
+
+        // The first agent can teleport to its goal once the second one has left it,
+        // the second one must walk across the grid to the initial state of the first one
+        let tasks = vec![
+            Arc::new(Task::new(
+                CapabilityState(GraphNodeId(0), 1),
+                CapabilityState(GraphNodeId(corner), 1),
+                OrderedFloat(0.0),
+            )),
+            Arc::new(Task::new(
+                CapabilityState(GraphNodeId(corner), 0),
+                CapabilityState(GraphNodeId(0), 0),
+                OrderedFloat(0.0),
+            )),
+        ];
+        let pivots = Arc::new(tasks.iter().map(|t| t.goal_state.clone()).collect());
+        let heuristic_to_pivots = Arc::new(
+            tasks
+                .iter()
+                .map(|t| {
+                    Arc::new(ReverseResumableAStar::new(
+                        transition_system.clone(),
+                        t.clone(),
+                        CapabilityHeuristic::new(transition_system.clone(), Arc::new(t.reverse())),
+                    ))
+                })
+                .collect(),
+        );
+        let config = CbsConfig::new(tasks, pivots, heuristic_to_pivots, OrderedFloat(1e-6));
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+
+        let uses_teleport = |agent: usize| {
+            solutions[agent]
+                .actions
+                .iter()
+                .any(|action| action.action == Some(teleport))
+        };
+        assert!(uses_teleport(0));
+        assert!(!uses_teleport(1));
+        assert!(solutions[0].cost < OrderedFloat(2.0));
+        assert_eq!(solutions[1].cost, OrderedFloat(2.0 * (size - 1) as f32));
+
+        // Each action is available to the agent that performs it
+        for solution in &solutions {
+            for (step, action) in solution.steps.iter().zip(solution.actions.iter()) {
+                if let Some(action) = action.action {
+                    assert!(transition_system
+                        .actions_from(&step.0.internal_state)
+                        .any(|a| *a == action));
+                }
+            }
+        }
+
+        // The plans are conflict-free
+        let moves = T2(solutions[0].to_moves(0), solutions[1].to_moves(1));
+        for first in &moves.0 {
+            for second in &moves.1 {
+                assert!(
+                    !first.interval.overlaps(&second.interval)
+                        || !transition_system.conflict(T2(first, second))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reachable() {
+        let mut graph = simple_graph(3);
+
+        // An island that only the agents with the first capability can reach
+        let island = graph.add_node((5.0, 5.0));
+        let bridge = graph.add_edge(GraphNodeId(8), island, 1.0);
+        let mut restricted = FxHashMap::default();
+        restricted.insert(bridge, (1, OrderedFloat(1.0)));
+        let transition_system = Arc::new(CapabilityWorld::new(
+            Arc::new(graph),
+            0.4,
+            restricted,
+            &[0, 1],
+        ));
+
+        let state = |node, capabilities| CapabilityState(GraphNodeId(node), capabilities);
+        assert!(transition_system.reachable(&state(0, 1), &state(island.0, 1)));
+        assert!(!transition_system.reachable(&state(0, 0), &state(island.0, 0)));
+        assert!(transition_system.reachable(&state(0, 0), &state(8, 0)));
+
+        // The bound through the bridge is tighter than the distance to the island
+        let heuristic = |capabilities| {
+            CapabilityHeuristic::new(
+                transition_system.clone(),
+                Arc::new(Task::new(
+                    state(0, capabilities),
+                    state(island.0, capabilities),
+                    OrderedFloat(0.0),
+                )),
+            )
+        };
+        assert_eq!(
+            heuristic(1).get_heuristic(&state(0, 1)),
+            Some(OrderedFloat(8.0f32.sqrt() + 1.0))
+        );
+        assert_eq!(
+            heuristic(0).get_heuristic(&state(0, 0)),
+            Some(OrderedFloat(50.0f32.sqrt()))
+        );
+    }
+}
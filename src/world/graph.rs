@@ -1,9 +1,20 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashSet},
+    fmt::Write,
+    ops::Add,
+};
+
+use crate::{SimpleEdgeData, SimpleNodeData, Solution};
+
 /// A directed graph node id.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GraphNodeId(pub usize);
 
 /// A directed graph edge id.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GraphEdgeId(pub usize);
 
 /// Definition of a directed graph node.
@@ -27,6 +38,10 @@ pub struct Graph<NodeData, EdgeData> {
     nodes: Vec<GraphNode<NodeData>>,
     edges_in: Vec<Vec<GraphEdgeId>>,
     edges_out: Vec<Vec<GraphEdgeId>>,
+    edges_after: Vec<Vec<GraphEdgeId>>,
+    /// Union-find forest of the connected components, ignoring the directions of the edges.
+    components: Vec<usize>,
+    component_sizes: Vec<usize>,
 }
 
 impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
@@ -36,6 +51,9 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
             nodes: Vec::new(),
             edges_in: Vec::new(),
             edges_out: Vec::new(),
+            edges_after: Vec::new(),
+            components: Vec::new(),
+            component_sizes: Vec::new(),
         }
     }
 
@@ -45,6 +63,8 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
         self.nodes.push(GraphNode { data });
         self.edges_in.push(Vec::new());
         self.edges_out.push(Vec::new());
+        self.components.push(id.0);
+        self.component_sizes.push(1);
         id
     }
 
@@ -52,11 +72,47 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
     pub fn add_edge(&mut self, from: GraphNodeId, to: GraphNodeId, data: EdgeData) -> GraphEdgeId {
         let id = GraphEdgeId(self.edges.len());
         self.edges.push(GraphEdge { from, to, data });
+        self.edges_after.push(self.edges_out[to.0].clone());
+        for edge in self.edges_in[from.0].iter() {
+            self.edges_after[edge.0].push(id);
+        }
         self.edges_in[to.0].push(id);
         self.edges_out[from.0].push(id);
+        if from == to {
+            self.edges_after[id.0].push(id);
+        }
+
+        // Merge the components of both nodes, the smaller one goes under the larger one
+        let (mut a, mut b) = (self.get_component(from.0), self.get_component(to.0));
+        if a != b {
+            if self.component_sizes[a] < self.component_sizes[b] {
+                std::mem::swap(&mut a, &mut b);
+            }
+            self.components[b] = a;
+            self.component_sizes[a] += self.component_sizes[b];
+        }
+
         id
     }
 
+    fn get_component(&self, mut node: usize) -> usize {
+        while self.components[node] != node {
+            node = self.components[node];
+        }
+        node
+    }
+
+    /// Returns true if both nodes belong to the same connected component, ignoring the directions
+    /// of the edges. Nodes that are not connected are not reachable from each other.
+    pub fn connected(&self, from: GraphNodeId, to: GraphNodeId) -> bool {
+        self.get_component(from.0) == self.get_component(to.0)
+    }
+
+    /// Forbids the turn that consists in taking the second edge right after the first one.
+    pub fn add_forbidden_turn(&mut self, from: GraphEdgeId, to: GraphEdgeId) {
+        self.edges_after[from.0].retain(|edge| *edge != to);
+    }
+
     /// Returns the node with the given id.
     pub fn get_node(&self, id: GraphNodeId) -> &GraphNode<NodeData> {
         &self.nodes[id.0]
@@ -77,6 +133,59 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
         &self.edges_out[id.0]
     }
 
+    /// Returns the edges that go out of the given node, along with their destination and their data.
+    pub fn neighbors(
+        &self,
+        id: GraphNodeId,
+    ) -> impl Iterator<Item = (GraphEdgeId, GraphNodeId, &EdgeData)> + '_ {
+        self.edges_out[id.0].iter().map(|edge| {
+            let edge_data = &self.edges[edge.0];
+            (*edge, edge_data.to, &edge_data.data)
+        })
+    }
+
+    /// Returns the edges that can be taken right after the given edge,
+    /// i.e. the edges that go out of its destination without making a forbidden turn.
+    pub fn get_edges_after(&self, id: GraphEdgeId) -> &[GraphEdgeId] {
+        &self.edges_after[id.0]
+    }
+
+    /// Computes the shortest distances between all pairs of nodes with Dijkstra's algorithm,
+    /// using the given cost for each edge.
+    pub fn all_pairs_distances<D, F>(&self, cost: F) -> DistanceMatrix<D>
+    where
+        D: Copy + Ord + Default + Add<D, Output = D>,
+        F: Fn(GraphEdgeId) -> D,
+    {
+        let n = self.num_nodes();
+        let mut distances = vec![None; n * n];
+
+        for source in 0..n {
+            let row = &mut distances[source * n..(source + 1) * n];
+            let mut queue = BinaryHeap::new();
+
+            row[source] = Some(D::default());
+            queue.push(Reverse((D::default(), source)));
+
+            while let Some(Reverse((distance, node))) = queue.pop() {
+                if row[node].is_some_and(|d| d < distance) {
+                    continue;
+                }
+
+                for (edge, next, _) in self.neighbors(GraphNodeId(node)) {
+                    let next = next.0;
+                    let next_distance = distance + cost(edge);
+                    if row[next].is_none_or(|d| next_distance < d) {
+                        row[next] = Some(next_distance);
+                        queue.push(Reverse((next_distance, next)));
+                    }
+                }
+            }
+        }
+
+        DistanceMatrix { n, distances }
+    }
+
     /// Returns the number of nodes in the graph.
     pub fn num_nodes(&self) -> usize {
         self.nodes.len()
@@ -87,3 +196,217 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
         self.edges.len()
     }
 }
+
+impl<NodeData, EdgeData> Graph<NodeData, EdgeData>
+where
+    EdgeData: Clone + PartialEq,
+{
+    /// Adds an edge in both directions with the same data, and returns the ids of both edges.
+    pub fn add_undirected_edge(
+        &mut self,
+        a: GraphNodeId,
+        b: GraphNodeId,
+        data: EdgeData,
+    ) -> (GraphEdgeId, GraphEdgeId) {
+        (self.add_edge(a, b, data.clone()), self.add_edge(b, a, data))
+    }
+
+    /// Returns an edge that has no reverse edge with the same data, if any.
+    pub fn find_asymmetric_edge(&self) -> Option<GraphEdgeId> {
+        (0..self.num_edges()).map(GraphEdgeId).find(|id| {
+            let edge = self.get_edge(*id);
+            !self.edges_out[edge.to.0].iter().any(|reverse| {
+                let reverse = self.get_edge(*reverse);
+                reverse.to == edge.from && reverse.data == edge.data
+            })
+        })
+    }
+
+    /// Panics if an edge has no reverse edge with the same data, which would break the admissibility
+    /// of the heuristics that assume an undirected graph. The check is only performed with the
+    /// `check-symmetry` feature.
+    pub fn assert_symmetric(&self) {
+        #[cfg(feature = "check-symmetry")]
+        if let Some(id) = self.find_asymmetric_edge() {
+            let edge = self.get_edge(id);
+            panic!(
+                "edge {:?} from {:?} to {:?} has no matching reverse edge",
+                id, edge.from, edge.to
+            );
+        }
+    }
+}
+
+impl Graph<SimpleNodeData, SimpleEdgeData> {
+    /// Renders the graph in the DOT format of Graphviz. The nodes are labeled by their id and
+    /// pinned at their position, and the edges are labeled by their cost.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with_highlights(&HashSet::new())
+    }
+
+    /// Renders the graph in the DOT format of Graphviz, see `to_dot`,
+    /// with the edges along the path of the given solution highlighted in red.
+    pub fn to_dot_with_path<S, C, DC>(&self, solution: &Solution<S, GraphEdgeId, C, DC>) -> String
+    where
+        C: Default,
+    {
+        let path = solution
+            .actions
+            .iter()
+            .filter_map(|action| action.action)
+            .collect();
+        self.to_dot_with_highlights(&path)
+    }
+
+    fn to_dot_with_highlights(&self, highlights: &HashSet<GraphEdgeId>) -> String {
+        let mut dot = String::from("digraph {\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let (x, y) = node.data;
+            writeln!(dot, "    {id} [label=\"{id}\", pos=\"{x},{y}!\"];").unwrap();
+        }
+        for (id, edge) in self.edges.iter().enumerate() {
+            write!(
+                dot,
+                "    {} -> {} [label=\"{}\"",
+                edge.from.0, edge.to.0, edge.data
+            )
+            .unwrap();
+            if highlights.contains(&GraphEdgeId(id)) {
+                dot.push_str(", color=red, penwidth=2");
+            }
+            dot.push_str("];\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Shortest distances between all pairs of nodes of a graph.
+#[derive(Debug, Clone)]
+pub struct DistanceMatrix<D> {
+    n: usize,
+    distances: Vec<Option<D>>,
+}
+
+impl<D> DistanceMatrix<D>
+where
+    D: Copy,
+{
+    /// Returns the shortest distance between the two given nodes,
+    /// or None if the second one is not reachable from the first one.
+    pub fn get(&self, from: GraphNodeId, to: GraphNodeId) -> Option<D> {
+        self.distances[from.0 * self.n + to.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        simple_graph, Action, Graph, GraphNodeId, SimpleEdgeData, SimpleNodeData, Solution,
+    };
+
+    #[test]
+    fn test_neighbors() {
+        let size = 5;
+        let graph = simple_graph(size);
+
+        let node = GraphNodeId(2 + 2 * size);
+        let mut targets = vec![];
+        for (edge, to, cost) in graph.neighbors(node) {
+            assert_eq!(graph.get_edge(edge).from, node);
+            assert_eq!(graph.get_edge(edge).to, to);
+            assert_eq!(*cost, 1.0);
+            targets.push(to.0);
+        }
+        targets.sort();
+        assert_eq!(
+            targets,
+            vec![2 + size, 1 + 2 * size, 3 + 2 * size, 2 + 3 * size]
+        );
+
+        // Corner nodes only have two neighbors
+        assert_eq!(graph.neighbors(GraphNodeId(0)).count(), 2);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let size = 3;
+        let graph = simple_graph(size);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph {"));
+        assert_eq!(dot.matches("label=").count(), 9 + 24);
+        assert_eq!(dot.matches(" -> ").count(), 24);
+        assert!(dot.contains("    4 [label=\"4\", pos=\"1,1!\"];"));
+        assert!(!dot.contains("color=red"));
+
+        // Path going from node 0 to node 2 through node 1, with a wait in node 1
+        let edge = |from: usize, to: usize| {
+            graph
+                .neighbors(GraphNodeId(from))
+                .find(|(_, next, _)| next.0 == to)
+                .unwrap()
+                .0
+        };
+        let solution: Solution<GraphNodeId, _, f32, f32> = Solution {
+            cost: 3.0,
+            steps: vec![],
+            actions: vec![
+                Action::new(edge(0, 1), 1.0),
+                Action::wait(1.0),
+                Action::new(edge(1, 2), 1.0),
+            ],
+        };
+
+        let dot = graph.to_dot_with_path(&solution);
+        assert_eq!(dot.matches("color=red").count(), 2);
+        assert!(dot.contains("    0 -> 1 [label=\"1\", color=red, penwidth=2];"));
+        assert!(dot.contains("    1 -> 2 [label=\"1\", color=red, penwidth=2];"));
+        assert!(dot.contains("    1 -> 0 [label=\"1\"];"));
+    }
+
+    #[test]
+    fn test_undirected_edges() {
+        let mut graph: Graph<SimpleNodeData, SimpleEdgeData> = Graph::new();
+        let a = graph.add_node((0.0, 0.0));
+        let b = graph.add_node((1.0, 0.0));
+        let c = graph.add_node((2.0, 0.0));
+
+        let (forward, backward) = graph.add_undirected_edge(a, b, 2.0);
+        assert_eq!(graph.num_edges(), 2);
+        assert_eq!(
+            (graph.get_edge(forward).from, graph.get_edge(forward).to),
+            (a, b)
+        );
+        assert_eq!(
+            (graph.get_edge(backward).from, graph.get_edge(backward).to),
+            (b, a)
+        );
+        assert_eq!(graph.get_edge(backward).data, 2.0);
+        assert!(graph.find_asymmetric_edge().is_none());
+        graph.assert_symmetric();
+
+        // Grids are symmetric
+        let grid = simple_graph(4);
+        assert!(grid.find_asymmetric_edge().is_none());
+        grid.assert_symmetric();
+
+        // An edge without reverse or with a different reverse cost is asymmetric
+        let edge = graph.add_edge(b, c, 1.0);
+        assert_eq!(graph.find_asymmetric_edge(), Some(edge));
+        graph.add_edge(c, b, 3.0);
+        assert_eq!(graph.find_asymmetric_edge(), Some(edge));
+    }
+
+    #[cfg(feature = "check-symmetry")]
+    #[test]
+    #[should_panic(expected = "has no matching reverse edge")]
+    fn test_assert_symmetric() {
+        let mut graph: Graph<SimpleNodeData, SimpleEdgeData> = Graph::new();
+        let a = graph.add_node((0.0, 0.0));
+        let b = graph.add_node((1.0, 0.0));
+        graph.add_edge(a, b, 1.0);
+        graph.add_edge(b, a, 2.0);
+        graph.assert_symmetric();
+    }
+}
@@ -0,0 +1,141 @@
+use std::f32::consts::SQRT_2;
+
+use crate::{Graph, GraphNodeId, SimpleEdgeData, SimpleNodeData};
+
+/// Rule that decides whether a diagonal move can cut the corner of an obstacle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CornerCutting {
+    /// Both cells orthogonally adjacent to the move must be free.
+    Never,
+    /// At least one of the cells orthogonally adjacent to the move must be free.
+    WhenOneFree,
+    /// Only the destination of the move must be free.
+    Always,
+}
+
+/// Builds the graph of a grid map given by its rows of cells, in which 1 denotes an obstacle.
+/// Each free cell is connected to its free orthogonal neighbors with unit weight, and to its free
+/// diagonal neighbors according to the given corner cutting policy, if any.
+/// Returns the graph and the node of each free cell, indexed by row and column.
+pub fn build_grid_graph(
+    rows: &[Vec<usize>],
+    diagonals: Option<CornerCutting>,
+) -> (
+    Graph<SimpleNodeData, SimpleEdgeData>,
+    Vec<Vec<Option<GraphNodeId>>>,
+) {
+    let height = rows.len();
+    let width = rows.first().map_or(0, |row| row.len());
+    let free = |x: isize, y: isize| {
+        x >= 0
+            && y >= 0
+            && (x as usize) < width
+            && (y as usize) < height
+            && rows[y as usize][x as usize] == 0
+    };
+
+    let mut graph = Graph::new();
+    let mut grid = vec![vec![None; width]; height];
+    for x in 0..width {
+        for y in 0..height {
+            if rows[y][x] == 0 {
+                grid[y][x] = Some(graph.add_node((x as f32, y as f32)));
+            }
+        }
+    }
+
+    for x in 0..width {
+        for y in 0..height {
+            let node_id = match grid[y][x] {
+                Some(node_id) => node_id,
+                None => continue,
+            };
+            let (x, y) = (x as isize, y as isize);
+
+            for (dx, dy) in [(-1, 0), (0, -1), (1, 0), (0, 1)] {
+                if free(x + dx, y + dy) {
+                    let next = grid[(y + dy) as usize][(x + dx) as usize].unwrap();
+                    graph.add_edge(node_id, next, 1.0);
+                }
+            }
+
+            if let Some(corner_cutting) = diagonals {
+                for (dx, dy) in [(-1, -1), (1, -1), (1, 1), (-1, 1)] {
+                    if !free(x + dx, y + dy) {
+                        continue;
+                    }
+                    let orthogonals = (free(x + dx, y), free(x, y + dy));
+                    let allowed = match corner_cutting {
+                        CornerCutting::Never => orthogonals.0 && orthogonals.1,
+                        CornerCutting::WhenOneFree => orthogonals.0 || orthogonals.1,
+                        CornerCutting::Always => true,
+                    };
+                    if allowed {
+                        let next = grid[(y + dy) as usize][(x + dx) as usize].unwrap();
+                        graph.add_edge(node_id, next, SQRT_2);
+                    }
+                }
+            }
+        }
+    }
+
+    (graph, grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::SQRT_2;
+
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        build_grid_graph, CornerCutting, Graph, GraphNodeId, SimpleEdgeData, SimpleNodeData,
+    };
+
+    fn has_edge(
+        graph: &Graph<SimpleNodeData, SimpleEdgeData>,
+        from: GraphNodeId,
+        to: GraphNodeId,
+    ) -> bool {
+        graph.neighbors(from).any(|(_, next, _)| next == to)
+    }
+
+    #[test]
+    fn test_corner_cutting() {
+        // An L-shaped obstacle, and another obstacle diagonally adjacent to the end of one arm
+        let rows = vec![
+            vec![0, 0, 0, 0],
+            vec![0, 1, 0, 0],
+            vec![0, 1, 1, 0],
+            vec![0, 0, 0, 1],
+        ];
+
+        let expected = [
+            (CornerCutting::Never, false, false, 8.0 + SQRT_2),
+            (CornerCutting::WhenOneFree, true, false, 2.0 + 4.0 * SQRT_2),
+            (CornerCutting::Always, true, true, SQRT_2),
+        ];
+        for (corner_cutting, one_free, none_free, cost) in expected {
+            let (graph, grid) = build_grid_graph(&rows, Some(corner_cutting));
+            let node = |x: usize, y: usize| grid[y][x].unwrap();
+
+            // Diagonals along the obstacles, with one or no free orthogonal cell
+            assert_eq!(has_edge(&graph, node(2, 1), node(1, 0)), one_free);
+            assert_eq!(has_edge(&graph, node(1, 0), node(2, 1)), one_free);
+            assert_eq!(has_edge(&graph, node(2, 3), node(3, 2)), none_free);
+            assert_eq!(has_edge(&graph, node(3, 2), node(2, 3)), none_free);
+            // Diagonals away from the obstacles always exist
+            assert!(has_edge(&graph, node(2, 0), node(3, 1)));
+
+            let distances =
+                graph.all_pairs_distances(|edge| OrderedFloat(graph.get_edge(edge).data));
+            let distance = distances.get(node(2, 3), node(3, 2)).unwrap();
+            assert!((distance.0 - cost).abs() < 1e-5);
+        }
+
+        // Without diagonals, the graph is 4-connected
+        let (graph, grid) = build_grid_graph(&rows, None);
+        assert_eq!(graph.neighbors(grid[0][0].unwrap()).count(), 2);
+        assert!(grid[1][1].is_none());
+    }
+}
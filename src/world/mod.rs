@@ -1,5 +1,18 @@
+mod capability;
 mod graph;
+mod grid;
 mod simple;
+mod time_distance;
+mod turn;
 
+pub use capability::*;
 pub use graph::*;
+pub use grid::*;
 pub use simple::*;
+pub use time_distance::*;
+pub use turn::*;
+
+#[cfg(test)]
+mod tests;
+#[cfg(test)]
+pub use tests::*;
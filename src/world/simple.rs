@@ -9,8 +9,8 @@ use ordered_float::OrderedFloat;
 use tuple::A2;
 
 use crate::{
-    Graph, GraphEdgeId, GraphNodeId, Heuristic, LimitValues, MinimalHeuristic, Move, State, Task,
-    TransitionSystem,
+    DistanceMatrix, Graph, GraphEdgeId, GraphNodeId, Heuristic, LimitValues, MinimalHeuristic,
+    Move, State, Task, TransitionSystem,
 };
 
 pub type MyTime = OrderedFloat<f32>;
@@ -21,13 +21,25 @@ pub type SimpleEdgeData = f32;
 pub struct SimpleWorld {
     graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
     ball: Ball<f32>,
+    min_duration: MyTime,
 }
 
 impl SimpleWorld {
     pub fn new(graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>, agent_size: f32) -> Self {
+        Self::new_with_min_duration(graph, agent_size, 0.0)
+    }
+
+    /// Creates a world in which no edge can be traversed in less than the given duration,
+    /// which acts as a global speed limit.
+    pub fn new_with_min_duration(
+        graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
+        agent_size: f32,
+        min_duration: f32,
+    ) -> Self {
         SimpleWorld {
             graph,
             ball: Ball { radius: agent_size },
+            min_duration: min_duration.into(),
         }
     }
 
@@ -41,7 +53,12 @@ impl SimpleWorld {
 
     pub fn time(&self, edge: GraphEdgeId) -> MyTime {
         let edge = self.graph.get_edge(edge);
-        self.time_between(edge.from, edge.to)
+        self.time_between(edge.from, edge.to).max(self.min_duration)
+    }
+
+    /// Precomputes the shortest times between all pairs of nodes.
+    pub fn all_pairs_distances(&self) -> DistanceMatrix<MyTime> {
+        self.graph.all_pairs_distances(|edge| self.time(edge))
     }
 
     pub fn get_center_and_vel(
@@ -69,6 +86,7 @@ impl SimpleWorld {
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimpleState(pub GraphNodeId);
 
 impl State for SimpleState {
@@ -106,6 +124,10 @@ impl TransitionSystem<SimpleState, GraphEdgeId, MyTime, MyTime> for SimpleWorld
         true
     }
 
+    fn reachable(&self, from: &SimpleState, to: &SimpleState) -> bool {
+        self.graph.connected(from.0, to.0)
+    }
+
     fn conflict(&self, moves: A2<&Move<SimpleState, GraphEdgeId, MyTime>>) -> bool {
         let initial_time = moves[0].interval.start.max(moves[1].interval.start);
         let max_time = moves[0].interval.end.min(moves[1].interval.end) - initial_time;
@@ -137,10 +159,15 @@ impl SimpleHeuristic {
 
 impl Heuristic<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime> for SimpleHeuristic {
     fn get_heuristic(&self, state: &SimpleState) -> Option<MyTime> {
-        Some(
-            self.transition_system
-                .time_between(state.0, self.goal_state.0),
-        )
+        let time = self
+            .transition_system
+            .time_between(state.0, self.goal_state.0);
+        if state.0 == self.goal_state.0 {
+            Some(time)
+        } else {
+            // At least one edge must be traversed
+            Some(time.max(self.transition_system.min_duration))
+        }
     }
 }
 
@@ -150,6 +177,30 @@ impl MinimalHeuristic<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime> for
     }
 }
 
+/// Perfect heuristic obtained by looking up precomputed all-pairs distances.
+pub struct MatrixHeuristic {
+    distances: Arc<DistanceMatrix<MyTime>>,
+    goal_state: SimpleState,
+}
+
+impl MatrixHeuristic {
+    pub fn new(
+        distances: Arc<DistanceMatrix<MyTime>>,
+        task: Arc<Task<SimpleState, MyTime>>,
+    ) -> Self {
+        MatrixHeuristic {
+            distances,
+            goal_state: task.goal_state.clone(),
+        }
+    }
+}
+
+impl Heuristic<SimpleWorld, SimpleState, GraphEdgeId, MyTime, MyTime> for MatrixHeuristic {
+    fn get_heuristic(&self, state: &SimpleState) -> Option<MyTime> {
+        self.distances.get(state.0, self.goal_state.0)
+    }
+}
+
 impl LimitValues for MyTime {
     fn min_value() -> Self {
         f32::MIN.into()
@@ -168,41 +219,15 @@ mod tests {
     use tuple::T2;
 
     use crate::{
-        Graph, GraphEdgeId, GraphNodeId, Interval, Move, SimpleEdgeData, SimpleNodeData,
-        SimpleState, SimpleWorld, TransitionSystem,
+        simple_graph, Graph, GraphEdgeId, GraphNodeId, Heuristic, Interval, MatrixHeuristic, Move,
+        ReverseResumableAStar, SafeIntervalPathPlanning, SimpleHeuristic, SimpleState, SimpleWorld,
+        SippConfig, Task, TransitionSystem,
     };
 
-    fn simple_graph(size: usize) -> Arc<Graph<SimpleNodeData, SimpleEdgeData>> {
-        let mut graph = Graph::new();
-        for x in 0..size {
-            for y in 0..size {
-                graph.add_node((x as f32, y as f32));
-            }
-        }
-        for x in 0..size {
-            for y in 0..size {
-                let node_id = GraphNodeId(x + y * size);
-                if x > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x - 1 + y * size), 1.0);
-                }
-                if y > 0 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y - 1) * size), 1.0);
-                }
-                if x < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + 1 + y * size), 1.0);
-                }
-                if y < size - 1 {
-                    graph.add_edge(node_id, GraphNodeId(x + (y + 1) * size), 1.0);
-                }
-            }
-        }
-        Arc::new(graph)
-    }
-
     #[test]
     fn test_simple() {
         let size = 10;
-        let graph = simple_graph(size);
+        let graph = Arc::new(simple_graph(size));
         let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
 
         let initial_time = OrderedFloat(0.0);
@@ -225,4 +250,88 @@ mod tests {
 
         assert!(transition_system.conflict(T2(&move1, &move2)));
     }
+
+    #[test]
+    fn test_min_duration() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+
+        let solve = |transition_system: Arc<SimpleWorld>| {
+            let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+            let task = Arc::new(Task::new(
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(size - 1)),
+                OrderedFloat(0.0),
+            ));
+            let config = SippConfig::new(
+                task.clone(),
+                Default::default(),
+                Default::default(),
+                Arc::new(ReverseResumableAStar::new(
+                    transition_system.clone(),
+                    task.clone(),
+                    SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+                )),
+                1e-6.into(),
+            );
+            solver.solve(&config).unwrap()
+        };
+
+        let solution = solve(Arc::new(SimpleWorld::new(graph.clone(), 0.4)));
+        let slow_solution = solve(Arc::new(SimpleWorld::new_with_min_duration(
+            graph.clone(),
+            0.4,
+            2.0,
+        )));
+
+        assert_eq!(solution.cost, OrderedFloat((size - 1) as f32));
+        assert_eq!(slow_solution.cost, OrderedFloat((2 * (size - 1)) as f32));
+        assert_eq!(solution.steps.len(), slow_solution.steps.len());
+        for (step, slow_step) in solution.steps.iter().zip(slow_solution.steps.iter()) {
+            assert_eq!(step.0.internal_state, slow_step.0.internal_state);
+            assert_eq!(step.1 * 2.0, slow_step.1);
+        }
+    }
+
+    #[test]
+    fn test_matrix_heuristic() {
+        let size = 10;
+        let graph = Arc::new(simple_graph(size));
+        let transition_system = Arc::new(SimpleWorld::new(graph, 0.4));
+        let distances = Arc::new(transition_system.all_pairs_distances());
+
+        for goal in 0..size * size {
+            let task = Arc::new(Task::new(
+                SimpleState(GraphNodeId(0)),
+                SimpleState(GraphNodeId(goal)),
+                OrderedFloat(0.0),
+            ));
+            let heuristic = MatrixHeuristic::new(distances.clone(), task.clone());
+            let search = ReverseResumableAStar::new(
+                transition_system.clone(),
+                task.clone(),
+                SimpleHeuristic::new(transition_system.clone(), Arc::new(task.reverse())),
+            );
+            for state in 0..size * size {
+                let state = SimpleState(GraphNodeId(state));
+                assert_eq!(
+                    heuristic.get_heuristic(&state),
+                    search.get_heuristic(&state)
+                );
+            }
+        }
+
+        // Nodes that are not connected have no distance
+        let mut graph = Graph::new();
+        graph.add_node((0.0, 0.0));
+        graph.add_node((1.0, 0.0));
+        graph.add_edge(GraphNodeId(0), GraphNodeId(1), 1.0);
+        let transition_system = SimpleWorld::new(Arc::new(graph), 0.4);
+        let distances = transition_system.all_pairs_distances();
+        assert_eq!(
+            distances.get(GraphNodeId(0), GraphNodeId(1)),
+            Some(OrderedFloat(1.0))
+        );
+        assert_eq!(distances.get(GraphNodeId(1), GraphNodeId(0)), None);
+    }
 }
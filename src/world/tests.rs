@@ -0,0 +1,30 @@
+use crate::{Graph, GraphNodeId, SimpleEdgeData, SimpleNodeData};
+
+/// Builds a four-connected grid of the given size with unit edges, in which node `x * size + y`
+/// is located at `(x, y)`.
+pub fn simple_graph(size: usize) -> Graph<SimpleNodeData, SimpleEdgeData> {
+    let mut graph = Graph::new();
+    for x in 0..size {
+        for y in 0..size {
+            graph.add_node((x as f32, y as f32));
+        }
+    }
+    for x in 0..size {
+        for y in 0..size {
+            let node_id = GraphNodeId(x + y * size);
+            if x > 0 {
+                graph.add_edge(node_id, GraphNodeId(x - 1 + y * size), 1.0);
+            }
+            if y > 0 {
+                graph.add_edge(node_id, GraphNodeId(x + (y - 1) * size), 1.0);
+            }
+            if x < size - 1 {
+                graph.add_edge(node_id, GraphNodeId(x + 1 + y * size), 1.0);
+            }
+            if y < size - 1 {
+                graph.add_edge(node_id, GraphNodeId(x + (y + 1) * size), 1.0);
+            }
+        }
+    }
+    graph
+}
@@ -0,0 +1,383 @@
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Add, Mul, Sub},
+    sync::Arc,
+};
+
+use ordered_float::OrderedFloat;
+use tuple::{A2, T2};
+
+use crate::{
+    Graph, GraphEdgeId, GraphNodeId, Heuristic, Interval, LimitValues, MinimalHeuristic, Move,
+    MyTime, SimpleEdgeData, SimpleNodeData, SimpleState, SimpleWorld, Task, TransitionSystem,
+};
+
+/// Weights of the time and distance components of a blended cost.
+pub trait TimeDistanceWeights {
+    const TIME: f32;
+    const DISTANCE: f32;
+}
+
+/// Cost made of a time and a distance component, ordered by the weighted blend
+/// of both components, with ties broken by time and then distance.
+pub struct TimeDistance<W> {
+    pub time: MyTime,
+    pub distance: MyTime,
+    _phantom: PhantomData<W>,
+}
+
+impl<W> TimeDistance<W>
+where
+    W: TimeDistanceWeights,
+{
+    pub fn new(time: f32, distance: f32) -> Self {
+        TimeDistance {
+            time: OrderedFloat(time),
+            distance: OrderedFloat(distance),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the weighted blend of the time and distance components.
+    pub fn blend(&self) -> MyTime {
+        self.time * W::TIME + self.distance * W::DISTANCE
+    }
+}
+
+impl<W> Debug for TimeDistance<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeDistance")
+            .field("time", &self.time)
+            .field("distance", &self.distance)
+            .finish()
+    }
+}
+
+impl<W> Clone for TimeDistance<W> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<W> Copy for TimeDistance<W> {}
+
+impl<W> Default for TimeDistance<W> {
+    fn default() -> Self {
+        TimeDistance {
+            time: OrderedFloat(0.0),
+            distance: OrderedFloat(0.0),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<W> PartialEq for TimeDistance<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.time == other.time && self.distance == other.distance
+    }
+}
+
+impl<W> Eq for TimeDistance<W> {}
+
+impl<W> Hash for TimeDistance<W> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.time.hash(state);
+        self.distance.hash(state);
+    }
+}
+
+impl<W> PartialOrd for TimeDistance<W>
+where
+    W: TimeDistanceWeights,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W> Ord for TimeDistance<W>
+where
+    W: TimeDistanceWeights,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.blend()
+            .cmp(&other.blend())
+            .then(self.time.cmp(&other.time))
+            .then(self.distance.cmp(&other.distance))
+    }
+}
+
+impl<W> Add for TimeDistance<W> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        TimeDistance {
+            time: self.time + rhs.time,
+            distance: self.distance + rhs.distance,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<W> Sub for TimeDistance<W> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        TimeDistance {
+            time: self.time - rhs.time,
+            distance: self.distance - rhs.distance,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<W> Mul<f32> for TimeDistance<W> {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        TimeDistance {
+            time: self.time * rhs,
+            distance: self.distance * rhs,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<W> LimitValues for TimeDistance<W>
+where
+    W: TimeDistanceWeights,
+{
+    fn min_value() -> Self {
+        TimeDistance {
+            time: MyTime::min_value(),
+            distance: MyTime::min_value(),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn max_value() -> Self {
+        TimeDistance {
+            time: MyTime::max_value(),
+            distance: MyTime::max_value(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// A world described by a directed weighted graph in which the weight of each edge
+/// is the time needed to traverse it, and the distance is given by the length of the edge.
+/// Costs blend both components, while conflicts are detected on the time component only.
+pub struct TimeDistanceWorld<W> {
+    graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
+    world: SimpleWorld,
+    _phantom: PhantomData<W>,
+}
+
+impl<W> TimeDistanceWorld<W>
+where
+    W: TimeDistanceWeights,
+{
+    pub fn new(graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>, agent_size: f32) -> Self {
+        TimeDistanceWorld {
+            graph: graph.clone(),
+            world: SimpleWorld::new(graph, agent_size),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn distance_between(&self, from: GraphNodeId, to: GraphNodeId) -> MyTime {
+        self.world.time_between(from, to)
+    }
+
+    pub fn cost(&self, edge: GraphEdgeId) -> TimeDistance<W> {
+        let edge = self.graph.get_edge(edge);
+        TimeDistance {
+            time: OrderedFloat(edge.data),
+            distance: self.distance_between(edge.from, edge.to),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<W> TransitionSystem<SimpleState, GraphEdgeId, TimeDistance<W>, TimeDistance<W>>
+    for TimeDistanceWorld<W>
+where
+    W: TimeDistanceWeights,
+{
+    fn actions_from(&self, state: &SimpleState) -> std::slice::Iter<'_, GraphEdgeId> {
+        self.graph.get_edges_out(state.0).iter()
+    }
+
+    fn transition(&self, _state: &SimpleState, action: &GraphEdgeId) -> SimpleState {
+        SimpleState(self.graph.get_edge(*action).to)
+    }
+
+    fn transition_cost(&self, _state: &SimpleState, action: &GraphEdgeId) -> TimeDistance<W> {
+        self.cost(*action)
+    }
+
+    fn reverse_actions_from(&self, state: &SimpleState) -> std::slice::Iter<'_, GraphEdgeId> {
+        self.graph.get_edges_in(state.0).iter()
+    }
+
+    fn reverse_transition(&self, _state: &SimpleState, action: &GraphEdgeId) -> SimpleState {
+        SimpleState(self.graph.get_edge(*action).from)
+    }
+
+    fn reverse_transition_cost(
+        &self,
+        _state: &SimpleState,
+        action: &GraphEdgeId,
+    ) -> TimeDistance<W> {
+        self.cost(*action)
+    }
+
+    fn can_wait_at(&self, _state: &SimpleState) -> bool {
+        true
+    }
+
+    fn reachable(&self, from: &SimpleState, to: &SimpleState) -> bool {
+        self.graph.connected(from.0, to.0)
+    }
+
+    fn conflict(&self, moves: A2<&Move<SimpleState, GraphEdgeId, TimeDistance<W>>>) -> bool {
+        let to_time = |m: &Move<SimpleState, GraphEdgeId, TimeDistance<W>>| {
+            let interval = Interval::new(m.interval.start.time, m.interval.end.time);
+            Move::new(m.agent, m.from.clone(), m.to.clone(), m.action, interval)
+        };
+        self.world
+            .conflict(T2(&to_time(moves[0]), &to_time(moves[1])))
+    }
+}
+
+/// Straight-line distance to the goal node, which ignores the time component.
+pub struct TimeDistanceHeuristic<W> {
+    transition_system: Arc<TimeDistanceWorld<W>>,
+    goal_state: SimpleState,
+}
+
+impl<W> TimeDistanceHeuristic<W>
+where
+    W: TimeDistanceWeights,
+{
+    pub fn new(
+        transition_system: Arc<TimeDistanceWorld<W>>,
+        task: Arc<Task<SimpleState, TimeDistance<W>>>,
+    ) -> Self {
+        TimeDistanceHeuristic {
+            transition_system,
+            goal_state: task.goal_state.clone(),
+        }
+    }
+}
+
+impl<W> Heuristic<TimeDistanceWorld<W>, SimpleState, GraphEdgeId, TimeDistance<W>, TimeDistance<W>>
+    for TimeDistanceHeuristic<W>
+where
+    W: TimeDistanceWeights,
+{
+    fn get_heuristic(&self, state: &SimpleState) -> Option<TimeDistance<W>> {
+        let distance = self
+            .transition_system
+            .distance_between(state.0, self.goal_state.0);
+        Some(TimeDistance {
+            time: OrderedFloat(0.0),
+            distance,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<W>
+    MinimalHeuristic<
+        TimeDistanceWorld<W>,
+        SimpleState,
+        GraphEdgeId,
+        TimeDistance<W>,
+        TimeDistance<W>,
+    > for TimeDistanceHeuristic<W>
+where
+    W: TimeDistanceWeights,
+{
+    fn build(
+        transition_system: Arc<TimeDistanceWorld<W>>,
+        task: Arc<Task<SimpleState, TimeDistance<W>>>,
+    ) -> Self {
+        Self::new(transition_system, task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        Graph, GraphNodeId, SafeIntervalPathPlanning, SimpleEdgeData, SimpleNodeData, SimpleState,
+        SippConfig, Task, TimeDistance, TimeDistanceHeuristic, TimeDistanceWeights,
+        TimeDistanceWorld,
+    };
+
+    struct Fastest;
+
+    impl TimeDistanceWeights for Fastest {
+        const TIME: f32 = 1.0;
+        const DISTANCE: f32 = 0.1;
+    }
+
+    struct Shortest;
+
+    impl TimeDistanceWeights for Shortest {
+        const TIME: f32 = 0.1;
+        const DISTANCE: f32 = 1.0;
+    }
+
+    /// Two routes between the first two nodes: a slow direct edge, and a fast detour.
+    fn routes_graph() -> Arc<Graph<SimpleNodeData, SimpleEdgeData>> {
+        let mut graph = Graph::new();
+        graph.add_node((0.0, 0.0));
+        graph.add_node((2.0, 0.0));
+        graph.add_node((1.0, 2.0));
+        graph.add_edge(GraphNodeId(0), GraphNodeId(1), 10.0);
+        graph.add_edge(GraphNodeId(0), GraphNodeId(2), 1.0);
+        graph.add_edge(GraphNodeId(2), GraphNodeId(1), 1.0);
+        Arc::new(graph)
+    }
+
+    fn solve<W: TimeDistanceWeights>() -> Vec<GraphNodeId> {
+        let transition_system = Arc::new(TimeDistanceWorld::<W>::new(routes_graph(), 0.4));
+        let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+        let task = Arc::new(Task::new(
+            SimpleState(GraphNodeId(0)),
+            SimpleState(GraphNodeId(1)),
+            TimeDistance::default(),
+        ));
+        let config = SippConfig::new(
+            task.clone(),
+            Default::default(),
+            Default::default(),
+            Arc::new(TimeDistanceHeuristic::new(
+                transition_system.clone(),
+                task.clone(),
+            )),
+            TimeDistance::new(1e-6, 1e-6),
+        );
+        let solution = solver.solve(&config).unwrap();
+        solution
+            .steps
+            .iter()
+            .map(|(state, _)| state.internal_state.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_weights() {
+        assert_eq!(
+            solve::<Fastest>(),
+            vec![GraphNodeId(0), GraphNodeId(2), GraphNodeId(1)]
+        );
+        assert_eq!(solve::<Shortest>(), vec![GraphNodeId(0), GraphNodeId(1)]);
+    }
+}
@@ -0,0 +1,260 @@
+use std::{borrow::Cow, sync::Arc};
+
+use tuple::{A2, T2};
+
+use crate::{
+    Graph, GraphEdgeId, GraphNodeId, Heuristic, MinimalHeuristic, Move, MyTime, SimpleEdgeData,
+    SimpleNodeData, SimpleState, SimpleWorld, State, Task, TransitionSystem,
+};
+
+/// A world described by a directed weighted graph with forbidden turns,
+/// in which the agents remember the edge they used to reach their current node.
+pub struct TurnWorld {
+    graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>,
+    world: SimpleWorld,
+}
+
+impl TurnWorld {
+    pub fn new(graph: Arc<Graph<SimpleNodeData, SimpleEdgeData>>, agent_size: f32) -> Self {
+        TurnWorld {
+            graph: graph.clone(),
+            world: SimpleWorld::new(graph, agent_size),
+        }
+    }
+
+    pub fn time_between(&self, from: GraphNodeId, to: GraphNodeId) -> MyTime {
+        self.world.time_between(from, to)
+    }
+
+    pub fn time(&self, edge: GraphEdgeId) -> MyTime {
+        self.world.time(edge)
+    }
+}
+
+/// A node of the graph along with the edge used to reach it, if any.
+/// The constraints are imposed on the node, whichever edge is used to reach it.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct TurnState(pub GraphNodeId, pub Option<GraphEdgeId>);
+
+impl State for TurnState {
+    fn is_equivalent(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn canonical(&self) -> Cow<'_, Self> {
+        match self.1 {
+            Some(_) => Cow::Owned(TurnState(self.0, None)),
+            None => Cow::Borrowed(self),
+        }
+    }
+}
+
+impl TransitionSystem<TurnState, GraphEdgeId, MyTime, MyTime> for TurnWorld {
+    fn actions_from(&self, state: &TurnState) -> std::slice::Iter<'_, GraphEdgeId> {
+        match state.1 {
+            Some(edge) => self.graph.get_edges_after(edge).iter(),
+            None => self.graph.get_edges_out(state.0).iter(),
+        }
+    }
+
+    fn transition(&self, _state: &TurnState, action: &GraphEdgeId) -> TurnState {
+        TurnState(self.graph.get_edge(*action).to, Some(*action))
+    }
+
+    fn transition_cost(&self, _state: &TurnState, action: &GraphEdgeId) -> MyTime {
+        self.time(*action)
+    }
+
+    /// The reverse transitions ignore the forbidden turns since the edge used to reach
+    /// the previous node cannot be known, they can only be used for relaxed heuristics.
+    fn reverse_actions_from(&self, state: &TurnState) -> std::slice::Iter<'_, GraphEdgeId> {
+        self.graph.get_edges_in(state.0).iter()
+    }
+
+    fn reverse_transition(&self, _state: &TurnState, action: &GraphEdgeId) -> TurnState {
+        TurnState(self.graph.get_edge(*action).from, None)
+    }
+
+    fn reverse_transition_cost(&self, _state: &TurnState, action: &GraphEdgeId) -> MyTime {
+        self.time(*action)
+    }
+
+    fn can_wait_at(&self, _state: &TurnState) -> bool {
+        true
+    }
+
+    fn reachable(&self, from: &TurnState, to: &TurnState) -> bool {
+        self.graph.connected(from.0, to.0)
+    }
+
+    fn conflict(&self, moves: A2<&Move<TurnState, GraphEdgeId, MyTime>>) -> bool {
+        let to_simple = |m: &Move<TurnState, GraphEdgeId, MyTime>| {
+            Move::new(
+                m.agent,
+                SimpleState(m.from.0),
+                SimpleState(m.to.0),
+                m.action,
+                m.interval,
+            )
+        };
+        self.world
+            .conflict(T2(&to_simple(moves[0]), &to_simple(moves[1])))
+    }
+}
+
+/// Euclidean distance to the goal node, which ignores the forbidden turns.
+pub struct TurnHeuristic {
+    transition_system: Arc<TurnWorld>,
+    goal_state: TurnState,
+}
+
+impl TurnHeuristic {
+    pub fn new(transition_system: Arc<TurnWorld>, task: Arc<Task<TurnState, MyTime>>) -> Self {
+        TurnHeuristic {
+            transition_system,
+            goal_state: task.goal_state.clone(),
+        }
+    }
+}
+
+impl Heuristic<TurnWorld, TurnState, GraphEdgeId, MyTime, MyTime> for TurnHeuristic {
+    fn get_heuristic(&self, state: &TurnState) -> Option<MyTime> {
+        Some(
+            self.transition_system
+                .time_between(state.0, self.goal_state.0),
+        )
+    }
+}
+
+impl MinimalHeuristic<TurnWorld, TurnState, GraphEdgeId, MyTime, MyTime> for TurnHeuristic {
+    fn build(transition_system: Arc<TurnWorld>, task: Arc<Task<TurnState, MyTime>>) -> Self {
+        Self::new(transition_system, task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ordered_float::OrderedFloat;
+    use tuple::T2;
+
+    use crate::{
+        simple_graph, CbsConfig, ConflictBasedSearch, Constraint, ConstraintSet, Graph,
+        GraphEdgeId, GraphNodeId, Interval, ReverseResumableAStar, SafeIntervalPathPlanning,
+        SimpleEdgeData, SimpleNodeData, SippConfig, Task, TransitionSystem, TurnHeuristic,
+        TurnState, TurnWorld,
+    };
+
+    fn get_edge(
+        graph: &Graph<SimpleNodeData, SimpleEdgeData>,
+        from: usize,
+        to: usize,
+    ) -> GraphEdgeId {
+        *graph
+            .get_edges_out(GraphNodeId(from))
+            .iter()
+            .find(|edge| graph.get_edge(**edge).to == GraphNodeId(to))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_forbidden_turn() {
+        let size = 3;
+
+        let solve = |graph: Graph<SimpleNodeData, SimpleEdgeData>| {
+            let transition_system = Arc::new(TurnWorld::new(Arc::new(graph), 0.4));
+            let mut solver = SafeIntervalPathPlanning::new(transition_system.clone());
+            let task = Arc::new(Task::new(
+                TurnState(GraphNodeId(3), None),
+                TurnState(GraphNodeId(5), None),
+                OrderedFloat(0.0),
+            ));
+            let config = SippConfig::new(
+                task.clone(),
+                Default::default(),
+                Default::default(),
+                Arc::new(TurnHeuristic::new(transition_system.clone(), task.clone())),
+                1e-6.into(),
+            );
+            solver.solve(&config).unwrap()
+        };
+
+        let graph = simple_graph(size);
+        let solution = solve(graph);
+        assert_eq!(solution.cost, OrderedFloat(2.0));
+
+        // Going straight through the center of the grid is forbidden
+        let mut graph = simple_graph(size);
+        let (in_edge, out_edge) = (get_edge(&graph, 3, 4), get_edge(&graph, 4, 5));
+        graph.add_forbidden_turn(in_edge, out_edge);
+        assert!(!graph.get_edges_after(in_edge).contains(&out_edge));
+
+        let solution = solve(graph);
+        assert_eq!(solution.cost, OrderedFloat(4.0));
+        for steps in solution.steps.windows(2) {
+            let edges = (steps[0].0.internal_state.1, steps[1].0.internal_state.1);
+            assert_ne!(edges, (Some(in_edge), Some(out_edge)));
+        }
+    }
+
+    #[test]
+    fn test_cbs() {
+        let size = 3;
+        let graph = simple_graph(size);
+        let (left, top) = (get_edge(&graph, 3, 4), get_edge(&graph, 1, 4));
+        let transition_system = Arc::new(TurnWorld::new(Arc::new(graph), 0.4));
+
+        // A constraint on the center applies whichever edge is used to reach it
+        let mut constraints = ConstraintSet::default();
+        constraints.add(&Arc::new(Constraint::new_state_constraint(
+            0,
+            TurnState(GraphNodeId(4), Some(left)),
+            Interval::new(OrderedFloat(0.0), OrderedFloat(1.0)),
+        )));
+        assert!(constraints
+            .get_state_constraints(&TurnState(GraphNodeId(4), Some(top)))
+            .is_some());
+
+        // Both agents cross the center of the grid at the same time without the other one
+        let tasks = [(3, 5), (1, 7)]
+            .iter()
+            .map(|(from, to)| {
+                Arc::new(Task::new(
+                    TurnState(GraphNodeId(*from), None),
+                    TurnState(GraphNodeId(*to), None),
+                    OrderedFloat(0.0),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let pivots = Arc::new(tasks.iter().map(|t| t.goal_state.clone()).collect());
+        let heuristic_to_pivots = Arc::new(
+            tasks
+                .iter()
+                .map(|t| {
+                    Arc::new(ReverseResumableAStar::new(
+                        transition_system.clone(),
+                        t.clone(),
+                        TurnHeuristic::new(transition_system.clone(), Arc::new(t.reverse())),
+                    ))
+                })
+                .collect(),
+        );
+        let config = CbsConfig::new(tasks, pivots, heuristic_to_pivots, OrderedFloat(1e-6));
+
+        let mut solver = ConflictBasedSearch::new(transition_system.clone());
+        let solutions = solver.solve(&config).unwrap();
+        assert!(solutions[0].cost + solutions[1].cost > OrderedFloat(4.0));
+
+        // The plans are conflict-free
+        let moves = T2(solutions[0].to_moves(0), solutions[1].to_moves(1));
+        for first in &moves.0 {
+            for second in &moves.1 {
+                assert!(
+                    !first.interval.overlaps(&second.interval)
+                        || !transition_system.conflict(T2(first, second))
+                );
+            }
+        }
+    }
+}